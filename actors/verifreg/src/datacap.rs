@@ -0,0 +1,492 @@
+// Copyright 2019-2022 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::ops::Deref;
+
+use anyhow::anyhow;
+use fil_actors_runtime::{actor_error, ActorError};
+use fvm_ipld_encoding::tuple::*;
+use fvm_shared::bigint::{bigint_ser, BigInt, Integer, Sign};
+use fvm_shared::piece::PaddedPieceSize;
+use fvm_shared::sector::StoragePower;
+use num_traits::{Signed, ToPrimitive, Zero};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{DataCap, MAX_DATACAP};
+
+/// Helper methods for [`DataCap`] values.
+///
+/// `DataCap` is an alias of `StoragePower`, so these are provided as an extension trait
+/// rather than inherent methods.
+pub trait DataCapExt {
+    /// Returns whether `self` and `other` differ by no more than `tolerance`.
+    fn approx_eq(&self, other: &DataCap, tolerance: &DataCap) -> bool;
+
+    /// Returns the length in bytes of the CBOR encoding of `self`, as written by
+    /// `bigint_ser`: a byte string holding a sign byte followed by the big-endian
+    /// magnitude, or an empty byte string for zero.
+    fn serialized_size(&self) -> usize;
+
+    /// Returns `2^exp` as a datacap, e.g. `DataCap::pow2(40)` for one TiB.
+    fn pow2(exp: u32) -> DataCap;
+
+    /// Describes the change from `self` to `other`.
+    fn diff(&self, other: &DataCap) -> DataCapDiff;
+
+    /// Borrows `self` as storage power.
+    fn as_power(&self) -> &StoragePower;
+
+    /// Converts `self` into an owned storage power.
+    fn into_power(self) -> StoragePower;
+
+    /// Converts `self` into a plain `BigInt`, for use with generic integer code.
+    fn into_bigint(self) -> BigInt;
+
+    /// Returns the verified deal space `self` bytes of datacap cover, which the market
+    /// multiplies by deal duration to weigh verified deals.
+    fn to_deal_space(&self) -> StoragePower;
+
+    /// Returns `self`, or zero if `self` is negative.
+    fn clamp_nonneg(self) -> DataCap;
+
+    /// Returns whether `self` is a whole multiple of `alignment`. Nothing is aligned to a
+    /// non-positive alignment.
+    fn is_aligned(&self, alignment: &DataCap) -> bool;
+
+    /// Compares `self` and `other` by accumulating the difference of every byte rather than
+    /// stopping at the first mismatch. This is best effort: the encoding and the compiler
+    /// give no timing guarantee. Datacap values are public chain state and the actor never
+    /// needs this, but it is available for comparisons next to secret material.
+    fn ct_eq(&self, other: &DataCap) -> bool;
+
+    /// Parses a whole number of bytes with an optional binary unit suffix, e.g. `"32GiB"`.
+    /// Accepted suffixes are `KiB`, `MiB`, `GiB`, `TiB` and `PiB`.
+    fn parse_with_units(s: &str) -> anyhow::Result<DataCap>;
+
+    /// Returns `self` in GiB as a float, for metrics. This is lossy: precision drops off
+    /// beyond 2^53 bytes and values beyond `f64::MAX` become infinite.
+    fn as_gib_f64(&self) -> f64;
+
+    /// Returns `self + other`, or `None` if the sum exceeds [`MAX_DATACAP`].
+    ///
+    /// Named apart from `checked_add`, which `BigInt` already provides inherently without
+    /// a bound.
+    fn checked_add_capped(&self, other: &DataCap) -> Option<DataCap>;
+
+    /// Encodes `self` as an unsigned LEB128 varint: seven bits per byte, least significant
+    /// group first, with the high bit set on every byte but the last. This is a compact form
+    /// for off-chain indexes; on-chain state uses the CBOR encoding. Fails for negative values.
+    fn to_varint_bytes(&self) -> anyhow::Result<Vec<u8>>;
+
+    /// Decodes a value written by [`DataCapExt::to_varint_bytes`]. The input must hold exactly
+    /// one minimally encoded varint.
+    fn from_varint_bytes(bytes: &[u8]) -> anyhow::Result<DataCap>;
+
+    /// Returns `self * numer / denom`, rounded towards zero. Panics if `denom` is zero.
+    fn scale_by_ratio(&self, numer: u64, denom: u64) -> DataCap;
+
+    /// Sums `amounts`, failing if the running total exceeds [`MAX_DATACAP`]. No valid
+    /// sequence of grants reaches a total that large, so it indicates corrupt state.
+    fn try_sum<'a, I>(amounts: I) -> anyhow::Result<DataCap>
+    where
+        I: IntoIterator<Item = &'a DataCap>;
+
+    /// Formats `self` in decimal with commas between groups of three digits, e.g.
+    /// `"1,048,576"`, for display to people. `Display` is left ungrouped.
+    fn to_grouped_string(&self) -> String;
+
+    /// Converts `self` to the padded piece size of a verified deal, as the storage market
+    /// expects. Fails unless `self` is a power of two of at least 128 bytes that fits in
+    /// a `u64`.
+    fn to_padded_piece_size(&self) -> anyhow::Result<PaddedPieceSize>;
+
+    /// Takes a storage power received in method parameters as a datacap, failing with
+    /// `USR_ILLEGAL_ARGUMENT` if it is negative. The two types are aliases, so the
+    /// conversion itself is free; only the check is added.
+    fn try_from_power(power: StoragePower) -> Result<DataCap, ActorError>;
+
+    /// Returns whether `low <= self <= high`.
+    fn is_between(&self, low: &DataCap, high: &DataCap) -> bool;
+
+    /// Returns half of `self`, rounded down.
+    fn halve(&self) -> DataCap;
+
+    /// Adds two optional datacaps, treating an absent one as zero.
+    fn add_opt(a: Option<DataCap>, b: Option<DataCap>) -> DataCap;
+
+    /// Returns the base-2 logarithm of `self` rounded down, for bucketing amounts by size
+    /// in reports. Zero and negative amounts are in bucket 0, along with 1.
+    fn order_of_magnitude(&self) -> u32;
+}
+
+/// Smallest padded piece size, matching the smallest unpadded Fr32 piece.
+const MIN_PADDED_PIECE_SIZE: u64 = 128;
+
+impl DataCapExt for DataCap {
+    fn approx_eq(&self, other: &DataCap, tolerance: &DataCap) -> bool {
+        (self - other).abs() <= *tolerance
+    }
+
+    fn serialized_size(&self) -> usize {
+        let len = if self.is_zero() { 0 } else { 1 + self.magnitude().to_bytes_be().len() };
+        cbor_header_size(len) + len
+    }
+
+    fn pow2(exp: u32) -> DataCap {
+        DataCap::from(1) << exp
+    }
+
+    fn diff(&self, other: &DataCap) -> DataCapDiff {
+        let delta = other - self;
+        let direction = if delta.is_positive() {
+            DataCapDirection::Increase
+        } else if delta.is_negative() {
+            DataCapDirection::Decrease
+        } else {
+            DataCapDirection::Unchanged
+        };
+        DataCapDiff { delta: delta.abs(), direction }
+    }
+
+    fn as_power(&self) -> &StoragePower {
+        self
+    }
+
+    fn into_power(self) -> StoragePower {
+        self
+    }
+
+    fn into_bigint(self) -> BigInt {
+        self
+    }
+
+    fn to_deal_space(&self) -> StoragePower {
+        self.clone()
+    }
+
+    fn clamp_nonneg(self) -> DataCap {
+        if self.is_negative() {
+            DataCap::zero()
+        } else {
+            self
+        }
+    }
+
+    fn is_aligned(&self, alignment: &DataCap) -> bool {
+        alignment.is_positive() && (self % alignment).is_zero()
+    }
+
+    fn ct_eq(&self, other: &DataCap) -> bool {
+        let (a_sign, a) = self.to_bytes_be();
+        let (b_sign, b) = other.to_bytes_be();
+        let len = a.len().max(b.len());
+        // Compare right-aligned, treating missing leading bytes as zero.
+        let byte_at =
+            |bytes: &[u8], i: usize| (i + bytes.len()).checked_sub(len).map_or(0, |j| bytes[j]);
+        let mut diff = u8::from(a_sign != b_sign);
+        for i in 0..len {
+            diff |= byte_at(&a, i) ^ byte_at(&b, i);
+        }
+        diff == 0
+    }
+
+    fn parse_with_units(s: &str) -> anyhow::Result<DataCap> {
+        let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+        let (digits, unit) = s.split_at(split);
+        if digits.is_empty() {
+            return Err(anyhow!("datacap {:?} does not start with a number", s));
+        }
+        let exp = match unit {
+            "" => 0,
+            "KiB" => 10,
+            "MiB" => 20,
+            "GiB" => 30,
+            "TiB" => 40,
+            "PiB" => 50,
+            _ => return Err(anyhow!("unknown datacap unit {:?} in {:?}", unit, s)),
+        };
+        let value: DataCap = digits.parse()?;
+        Ok(value << exp)
+    }
+
+    fn as_gib_f64(&self) -> f64 {
+        self.to_f64().unwrap_or(f64::NAN) / (1u64 << 30) as f64
+    }
+
+    fn checked_add_capped(&self, other: &DataCap) -> Option<DataCap> {
+        Some(self + other).filter(|sum| sum <= &*MAX_DATACAP)
+    }
+
+    fn to_varint_bytes(&self) -> anyhow::Result<Vec<u8>> {
+        if self.is_negative() {
+            return Err(anyhow!("cannot varint encode negative datacap {}", self));
+        }
+        let (_, mut groups) = self.to_radix_le(128);
+        let last = groups.len() - 1;
+        for group in &mut groups[..last] {
+            *group |= 0x80;
+        }
+        Ok(groups)
+    }
+
+    fn from_varint_bytes(bytes: &[u8]) -> anyhow::Result<DataCap> {
+        let end = bytes
+            .iter()
+            .position(|b| b & 0x80 == 0)
+            .ok_or_else(|| anyhow!("truncated datacap varint {:?}", bytes))?;
+        if end + 1 != bytes.len() {
+            return Err(anyhow!("trailing bytes after datacap varint {:?}", bytes));
+        }
+        if end > 0 && bytes[end] == 0 {
+            return Err(anyhow!("non-minimal datacap varint {:?}", bytes));
+        }
+        let groups: Vec<u8> = bytes.iter().map(|b| b & 0x7f).collect();
+        DataCap::from_radix_le(Sign::Plus, &groups, 128)
+            .ok_or_else(|| anyhow!("invalid datacap varint {:?}", bytes))
+    }
+
+    fn scale_by_ratio(&self, numer: u64, denom: u64) -> DataCap {
+        self * numer / denom
+    }
+
+    fn try_sum<'a, I>(amounts: I) -> anyhow::Result<DataCap>
+    where
+        I: IntoIterator<Item = &'a DataCap>,
+    {
+        amounts.into_iter().try_fold(DataCap::zero(), |total, amount| {
+            total.checked_add_capped(amount).ok_or_else(|| {
+                anyhow!("datacap total exceeds maximum {} adding {}", *MAX_DATACAP, amount)
+            })
+        })
+    }
+
+    fn to_grouped_string(&self) -> String {
+        let digits = self.magnitude().to_string();
+        let mut grouped = String::with_capacity(digits.len() + digits.len() / 3 + 1);
+        if self.is_negative() {
+            grouped.push('-');
+        }
+        for (i, digit) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i) % 3 == 0 {
+                grouped.push(',');
+            }
+            grouped.push(digit);
+        }
+        grouped
+    }
+
+    fn to_padded_piece_size(&self) -> anyhow::Result<PaddedPieceSize> {
+        let size = self
+            .to_u64()
+            .ok_or_else(|| anyhow!("datacap {} out of range for a piece size", self))?;
+        if size < MIN_PADDED_PIECE_SIZE {
+            return Err(anyhow!(
+                "datacap {} below minimum piece size {}",
+                size,
+                MIN_PADDED_PIECE_SIZE
+            ));
+        }
+        if !size.is_power_of_two() {
+            return Err(anyhow!("datacap {} is not a power of two", size));
+        }
+        Ok(PaddedPieceSize(size))
+    }
+
+    fn try_from_power(power: StoragePower) -> Result<DataCap, ActorError> {
+        if power.is_negative() {
+            return Err(actor_error!(illegal_argument, "power {} is negative", power));
+        }
+        Ok(power)
+    }
+
+    fn is_between(&self, low: &DataCap, high: &DataCap) -> bool {
+        low <= self && self <= high
+    }
+
+    fn halve(&self) -> DataCap {
+        self.div_floor(&DataCap::from(2))
+    }
+
+    fn add_opt(a: Option<DataCap>, b: Option<DataCap>) -> DataCap {
+        a.unwrap_or_default() + b.unwrap_or_default()
+    }
+
+    fn order_of_magnitude(&self) -> u32 {
+        if self.is_positive() {
+            (self.bits() - 1) as u32
+        } else {
+            0
+        }
+    }
+}
+
+/// Returns the length in bytes of a CBOR header carrying `len`, such as the length of
+/// a byte string or array.
+pub(crate) fn cbor_header_size(len: usize) -> usize {
+    match len {
+        0..=23 => 1,
+        24..=0xff => 2,
+        0x100..=0xffff => 3,
+        0x1_0000..=0xffff_ffff => 5,
+        _ => 9,
+    }
+}
+
+/// Direction of a change between two [`DataCap`] values.
+/// Encoded as the sign of the change: -1, 0 or 1.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DataCapDirection {
+    Decrease,
+    Unchanged,
+    Increase,
+}
+
+impl Default for DataCapDirection {
+    fn default() -> Self {
+        DataCapDirection::Unchanged
+    }
+}
+
+impl Serialize for DataCapDirection {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let sign: i8 = match self {
+            DataCapDirection::Decrease => -1,
+            DataCapDirection::Unchanged => 0,
+            DataCapDirection::Increase => 1,
+        };
+        sign.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for DataCapDirection {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match i8::deserialize(deserializer)? {
+            -1 => Ok(DataCapDirection::Decrease),
+            0 => Ok(DataCapDirection::Unchanged),
+            1 => Ok(DataCapDirection::Increase),
+            sign => Err(de::Error::custom(format!("invalid datacap direction {}", sign))),
+        }
+    }
+}
+
+/// The magnitude and direction of a change between two [`DataCap`] values.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct DataCapDiff {
+    #[serde(with = "bigint_ser")]
+    pub delta: DataCap,
+    pub direction: DataCapDirection,
+}
+
+/// Running total of a sequence of [`DataCap`] amounts.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DataCapAccumulator {
+    total: DataCap,
+    count: u64,
+}
+
+impl DataCapAccumulator {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn add(&mut self, amount: &DataCap) {
+        self.total += amount;
+        self.count += 1;
+    }
+
+    pub fn total(&self) -> &DataCap {
+        &self.total
+    }
+
+    /// Number of amounts added so far.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn into_total(self) -> DataCap {
+        self.total
+    }
+}
+
+/// A [`DataCap`] that is known to be non-negative.
+///
+/// Encodes exactly as a `DataCap`, but decoding rejects negative values, so parameters
+/// holding one never need to re-check the sign.
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NonNegDataCap(DataCap);
+
+impl NonNegDataCap {
+    pub fn into_data_cap(self) -> DataCap {
+        self.0
+    }
+}
+
+/// Error returned when constructing a [`NonNegDataCap`] from a negative value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NegativeDataCapError(pub DataCap);
+
+impl fmt::Display for NegativeDataCapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "datacap {} is negative", self.0)
+    }
+}
+
+impl std::error::Error for NegativeDataCapError {}
+
+impl TryFrom<DataCap> for NonNegDataCap {
+    type Error = NegativeDataCapError;
+
+    fn try_from(cap: DataCap) -> Result<Self, Self::Error> {
+        if cap.is_negative() {
+            return Err(NegativeDataCapError(cap));
+        }
+        Ok(NonNegDataCap(cap))
+    }
+}
+
+impl From<NonNegDataCap> for DataCap {
+    fn from(cap: NonNegDataCap) -> Self {
+        cap.0
+    }
+}
+
+impl Deref for NonNegDataCap {
+    type Target = DataCap;
+
+    fn deref(&self) -> &DataCap {
+        &self.0
+    }
+}
+
+impl fmt::Display for NonNegDataCap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl Serialize for NonNegDataCap {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        bigint_ser::serialize(&self.0, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for NonNegDataCap {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let cap: DataCap = bigint_ser::deserialize(deserializer)?;
+        NonNegDataCap::try_from(cap).map_err(de::Error::custom)
+    }
+}
@@ -1,27 +1,33 @@
 // Copyright 2019-2022 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use fil_actors_runtime::runtime::{ActorCode, Runtime};
+use fil_actors_runtime::runtime::{ActorCode, Primitives, Runtime};
 use fil_actors_runtime::{
     actor_error, cbor, make_map_with_root_and_bitwidth, resolve_to_id_addr, ActorDowncast,
-    ActorError, Map, STORAGE_MARKET_ACTOR_ADDR, SYSTEM_ACTOR_ADDR,
+    ActorError, Map, Set, STORAGE_MARKET_ACTOR_ADDR, SYSTEM_ACTOR_ADDR,
 };
 use fvm_ipld_blockstore::Blockstore;
 use fvm_ipld_encoding::RawBytes;
 use fvm_ipld_hamt::BytesKey;
-use fvm_shared::address::Address;
-use fvm_shared::bigint::bigint_ser::BigIntDe;
+use fvm_shared::address::{Address, Protocol};
+use fvm_shared::bigint::bigint_ser::{BigIntDe, BigIntSer};
+use fvm_shared::crypto::signature::Signature;
 use fvm_shared::error::ExitCode;
 use fvm_shared::{MethodNum, HAMT_BIT_WIDTH, METHOD_CONSTRUCTOR};
 use num_derive::FromPrimitive;
 use num_traits::{FromPrimitive, Signed, Zero};
 
-pub use self::state::State;
+pub use self::datacap::{
+    DataCapAccumulator, DataCapDiff, DataCapDirection, DataCapExt, NegativeDataCapError,
+    NonNegDataCap,
+};
+pub use self::state::{State, STATE_VERSION};
 pub use self::types::*;
 
 #[cfg(feature = "fil-actor")]
 fil_actors_runtime::wasm_trampoline!(Actor);
 
+mod datacap;
 mod state;
 pub mod testing;
 mod types;
@@ -39,6 +45,37 @@ pub enum Method {
     UseBytes = 5,
     RestoreBytes = 6,
     RemoveVerifiedClientDataCap = 7,
+    TotalVerifierAllowance = 8,
+    GetClientProposalIds = 9,
+    BurnVerifierAllowance = 10,
+    DecreaseVerifierAllowance = 11,
+    GetRootKey = 12,
+    NormalizeProposalIds = 13,
+    AddVerifiedClientFor = 14,
+    GetRecentRemovals = 15,
+    TransferAllowance = 16,
+    SetMinimumAllocationSize = 17,
+    GetAddressRole = 18,
+    AddVerifiedClients = 19,
+    RevokeAllClientDataCap = 20,
+    ScaleAllVerifiers = 21,
+    ImportProposalIds = 22,
+    SetClientDataCap = 23,
+    CanJointlyRemove = 24,
+    SetRequiredRemovalApprovers = 25,
+    GetVerifierUtilization = 26,
+    GetTotalRemoved = 27,
+    GetClientTotalGranted = 28,
+    SwapVerifierAllowances = 29,
+    BatchRemoveDataCap = 30,
+    SetVerifierFrozen = 31,
+    PrepareRemoval = 32,
+    ImportVerifiers = 33,
+    GetVerifierClientStatus = 34,
+    SubmitRemovalApproval = 35,
+    StepwiseRemoveDataCap = 36,
+    GetClientGranters = 37,
+    RemoveVerifiedClientDataCapV2 = 38,
 }
 
 pub struct Actor;
@@ -57,7 +94,8 @@ impl Actor {
             .resolve_address(&root_key)
             .ok_or_else(|| actor_error!(illegal_argument, "root should be an ID address"))?;
 
-        let st = State::new(rt.store(), id_addr).map_err(|e| {
+        let min_allocation_size = rt.policy().minimum_verified_deal_size.clone();
+        let st = State::new(rt.store(), id_addr, min_allocation_size).map_err(|e| {
             e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "Failed to create verifreg state")
         })?;
 
@@ -70,7 +108,7 @@ impl Actor {
         BS: Blockstore,
         RT: Runtime<BS>,
     {
-        if params.allowance < rt.policy().minimum_verified_deal_size {
+        if *params.allowance < rt.policy().minimum_verified_deal_size {
             return Err(actor_error!(
                 illegal_argument,
                 "Allowance {} below minimum deal size for add verifier {}",
@@ -125,9 +163,11 @@ impl Actor {
                 ));
             }
 
-            verifiers.set(verifier.to_bytes().into(), BigIntDe(params.allowance.clone())).map_err(
-                |e| e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to add verifier"),
-            )?;
+            verifiers
+                .set(verifier.to_bytes().into(), BigIntDe((*params.allowance).clone()))
+                .map_err(|e| {
+                    e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to add verifier")
+                })?;
             st.verifiers = verifiers.flush().map_err(|e| {
                 e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to flush verifiers")
             })?;
@@ -170,531 +210,2136 @@ impl Actor {
                 .ok_or_else(|| {
                     actor_error!(illegal_argument, "failed to remove verifier: not found")
                 })?;
+            let mut granted = make_map_with_root_and_bitwidth::<_, BigIntDe>(
+                &st.verifier_granted,
+                rt.store(),
+                HAMT_BIT_WIDTH,
+            )
+            .map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load verifier grants")
+            })?;
+            granted.delete(&verifier.to_bytes()).map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to remove verifier grants")
+            })?;
 
             st.verifiers = verifiers.flush().map_err(|e| {
                 e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to flush verifiers")
             })?;
+            st.verifier_granted = granted.flush().map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to flush verifier grants")
+            })?;
+            let mut frozen = Set::from_root(rt.store(), &st.frozen_verifiers).map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load frozen verifiers")
+            })?;
+            frozen.delete(&verifier.to_bytes()).map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to unfreeze verifier")
+            })?;
+            st.frozen_verifiers = frozen.root().map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to flush frozen verifiers")
+            })?;
             Ok(())
         })?;
 
         Ok(())
     }
 
-    pub fn add_verified_client<BS, RT>(
+    /// Zeroes a verifier's remaining allowance, adding it to the state's burned total.
+    /// The verifier itself stays registered.
+    pub fn burn_verifier_allowance<BS, RT>(
         rt: &mut RT,
-        params: AddVerifierClientParams,
+        verifier_addr: Address,
     ) -> Result<(), ActorError>
     where
         BS: Blockstore,
         RT: Runtime<BS>,
     {
-        // The caller will be verified by checking table below
-        rt.validate_immediate_caller_accept_any()?;
-
-        if params.allowance < rt.policy().minimum_verified_deal_size {
-            return Err(actor_error!(
-                illegal_argument,
-                "Allowance {} below MinVerifiedDealSize for add verified client {}",
-                params.allowance,
-                params.address
-            ));
-        }
-
-        let client = resolve_to_id_addr(rt, &params.address).map_err(|e| {
+        let verifier = resolve_to_id_addr(rt, &verifier_addr).map_err(|e| {
             e.downcast_default(
                 ExitCode::USR_ILLEGAL_STATE,
-                format!("failed to resolve addr {} to ID addr", params.address),
+                format!("failed to resolve addr {} to ID addr", verifier_addr),
             )
         })?;
 
-        let st: State = rt.state()?;
-        if client == st.root_key {
-            return Err(actor_error!(illegal_argument, "Rootkey cannot be added as verifier"));
-        }
+        let state: State = rt.state()?;
+        rt.validate_immediate_caller_is(std::iter::once(&state.root_key))?;
 
         rt.transaction(|st: &mut State, rt| {
-            let mut verifiers =
-                make_map_with_root_and_bitwidth(&st.verifiers, rt.store(), HAMT_BIT_WIDTH)
-                    .map_err(|e| {
-                        e.downcast_default(
-                            ExitCode::USR_ILLEGAL_STATE,
-                            "failed to load verified clients",
-                        )
-                    })?;
-            let mut verified_clients =
-                make_map_with_root_and_bitwidth(&st.verified_clients, rt.store(), HAMT_BIT_WIDTH)
-                    .map_err(|e| {
-                    e.downcast_default(
-                        ExitCode::USR_ILLEGAL_STATE,
-                        "failed to load verified clients",
-                    )
-                })?;
-
-            // Validate caller is one of the verifiers.
-            let verifier = rt.message().caller();
-            let BigIntDe(verifier_cap) = verifiers
+            let mut verifiers = make_map_with_root_and_bitwidth::<_, BigIntDe>(
+                &st.verifiers,
+                rt.store(),
+                HAMT_BIT_WIDTH,
+            )
+            .map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load verifiers")
+            })?;
+            let BigIntDe(burned) = verifiers
                 .get(&verifier.to_bytes())
                 .map_err(|e| {
                     e.downcast_default(
                         ExitCode::USR_ILLEGAL_STATE,
-                        format!("failed to get Verifier {}", verifier),
+                        format!("failed to get verifier {}", verifier),
                     )
                 })?
-                .ok_or_else(|| actor_error!(not_found, format!("no such Verifier {}", verifier)))?;
-
-            // Validate client to be added isn't a verifier
-            let found = verifiers.contains_key(&client.to_bytes()).map_err(|e| {
-                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to get verifier")
-            })?;
-            if found {
-                return Err(actor_error!(
-                    illegal_argument,
-                    "verifier {} cannot be added as a verified client",
-                    client
-                ));
-            }
-
-            // Compute new verifier cap and update.
-            if verifier_cap < &params.allowance {
-                return Err(actor_error!(
-                    illegal_argument,
-                    "Add more DataCap {} for VerifiedClient than allocated {}",
-                    params.allowance,
-                    verifier_cap
-                ));
-            }
-            let new_verifier_cap = verifier_cap - &params.allowance;
-
-            verifiers.set(verifier.to_bytes().into(), BigIntDe(new_verifier_cap)).map_err(|e| {
-                e.downcast_default(
-                    ExitCode::USR_ILLEGAL_STATE,
-                    format!("Failed to update new verifier cap for {}", verifier),
-                )
-            })?;
+                .cloned()
+                .ok_or_else(|| actor_error!(not_found, "no such verifier {}", verifier))?;
 
-            let client_cap = verified_clients.get(&client.to_bytes()).map_err(|e| {
+            verifiers.set(verifier.to_bytes().into(), BigIntDe(DataCap::zero())).map_err(|e| {
                 e.downcast_default(
                     ExitCode::USR_ILLEGAL_STATE,
-                    format!("Failed to get verified client {}", client),
+                    format!("failed to update verifier {}", verifier),
                 )
             })?;
-            // if verified client exists, add allowance to existing cap
-            // otherwise, create new client with allownace
-            let client_cap = if let Some(BigIntDe(client_cap)) = client_cap {
-                client_cap + params.allowance
-            } else {
-                params.allowance
-            };
-
-            verified_clients.set(client.to_bytes().into(), BigIntDe(client_cap.clone())).map_err(
-                |e| {
-                    e.downcast_default(
-                        ExitCode::USR_ILLEGAL_STATE,
-                        format!(
-                            "Failed to add verified client {} with cap {}",
-                            client, client_cap,
-                        ),
-                    )
-                },
-            )?;
-
             st.verifiers = verifiers.flush().map_err(|e| {
                 e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to flush verifiers")
             })?;
-            st.verified_clients = verified_clients.flush().map_err(|e| {
-                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to flush verified clients")
-            })?;
-
+            st.total_burned_allowance += burned;
             Ok(())
         })?;
 
         Ok(())
     }
 
-    /// Called by StorageMarketActor during PublishStorageDeals.
-    /// Do not allow partially verified deals (DealSize must be greater than equal to allowed cap).
-    /// Delete VerifiedClient if remaining DataCap is smaller than minimum VerifiedDealSize.
-    pub fn use_bytes<BS, RT>(rt: &mut RT, params: UseBytesParams) -> Result<(), ActorError>
+    /// Subtracts from a verifier's remaining allowance, returning the new allowance.
+    pub fn decrease_verifier_allowance<BS, RT>(
+        rt: &mut RT,
+        params: DecreaseVerifierAllowanceParams,
+    ) -> Result<DataCap, ActorError>
     where
         BS: Blockstore,
         RT: Runtime<BS>,
     {
-        rt.validate_immediate_caller_is(std::iter::once(&*STORAGE_MARKET_ACTOR_ADDR))?;
-
-        let client = resolve_to_id_addr(rt, &params.address).map_err(|e| {
+        let verifier = resolve_to_id_addr(rt, &params.address).map_err(|e| {
             e.downcast_default(
                 ExitCode::USR_ILLEGAL_STATE,
                 format!("failed to resolve addr {} to ID addr", params.address),
             )
         })?;
 
-        if params.deal_size < rt.policy().minimum_verified_deal_size {
-            return Err(actor_error!(
-                illegal_argument,
-                "Verified Dealsize {} is below minimum in usedbytes",
-                params.deal_size
-            ));
-        }
+        let state: State = rt.state()?;
+        rt.validate_immediate_caller_is(std::iter::once(&state.root_key))?;
 
         rt.transaction(|st: &mut State, rt| {
-            let mut verified_clients =
-                make_map_with_root_and_bitwidth(&st.verified_clients, rt.store(), HAMT_BIT_WIDTH)
-                    .map_err(|e| {
-                    e.downcast_default(
-                        ExitCode::USR_ILLEGAL_STATE,
-                        "failed to load verified clients",
-                    )
-                })?;
-
-            let BigIntDe(vc_cap) = verified_clients
-                .get(&client.to_bytes())
+            let mut verifiers = make_map_with_root_and_bitwidth::<_, BigIntDe>(
+                &st.verifiers,
+                rt.store(),
+                HAMT_BIT_WIDTH,
+            )
+            .map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load verifiers")
+            })?;
+            let BigIntDe(cap) = verifiers
+                .get(&verifier.to_bytes())
                 .map_err(|e| {
                     e.downcast_default(
                         ExitCode::USR_ILLEGAL_STATE,
-                        format!("failed to get verified client {}", &client),
+                        format!("failed to get verifier {}", verifier),
                     )
                 })?
-                .ok_or_else(|| actor_error!(not_found, "no such verified client {}", client))?;
-            if vc_cap.is_negative() {
-                return Err(actor_error!(
-                    illegal_state,
-                    "negative cap for client {}: {}",
-                    client,
-                    vc_cap
-                ));
-            }
+                .ok_or_else(|| actor_error!(not_found, "no such verifier {}", verifier))?;
 
-            if &params.deal_size > vc_cap {
-                return Err(actor_error!(
-                    illegal_argument,
-                    "Deal size of {} is greater than verifier_cap {} for verified client {}",
-                    params.deal_size,
-                    vc_cap,
-                    client
-                ));
-            };
-
-            let new_vc_cap = vc_cap - &params.deal_size;
-            if new_vc_cap < rt.policy().minimum_verified_deal_size {
-                // Delete entry if remaining DataCap is less than MinVerifiedDealSize.
-                // Will be restored later if the deal did not get activated with a ProvenSector.
-                verified_clients
-                    .delete(&client.to_bytes())
-                    .map_err(|e| {
-                        e.downcast_default(
-                            ExitCode::USR_ILLEGAL_STATE,
-                            format!("Failed to delete verified client {}", client),
-                        )
-                    })?
-                    .ok_or_else(|| {
-                        actor_error!(
-                            illegal_state,
-                            "Failed to delete verified client {}: not found",
-                            client
-                        )
-                    })?;
-            } else {
-                verified_clients.set(client.to_bytes().into(), BigIntDe(new_vc_cap)).map_err(
-                    |e| {
-                        e.downcast_default(
-                            ExitCode::USR_ILLEGAL_STATE,
-                            format!("Failed to update verified client {}", client),
-                        )
-                    },
-                )?;
-            }
+            let new_cap = cap
+                .checked_sub(&*params.allowance)
+                .filter(|c| !c.is_negative())
+                .ok_or_else(|| {
+                    actor_error!(
+                        illegal_argument,
+                        "cannot decrease allowance {} of verifier {} by {}",
+                        cap,
+                        verifier,
+                        params.allowance
+                    )
+                })?;
 
-            st.verified_clients = verified_clients.flush().map_err(|e| {
-                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to flush verified clients")
+            verifiers.set(verifier.to_bytes().into(), BigIntDe(new_cap.clone())).map_err(|e| {
+                e.downcast_default(
+                    ExitCode::USR_ILLEGAL_STATE,
+                    format!("failed to update verifier {}", verifier),
+                )
             })?;
-            Ok(())
-        })?;
+            st.verifiers = verifiers.flush().map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to flush verifiers")
+            })?;
+            Ok(new_cap)
+        })
+    }
 
-        Ok(())
+    /// Sets the smallest allowance that may be granted to a verified client.
+    pub fn set_minimum_allocation_size<BS, RT>(
+        rt: &mut RT,
+        size: NonNegDataCap,
+    ) -> Result<(), ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        let state: State = rt.state()?;
+        rt.validate_immediate_caller_is(std::iter::once(&state.root_key))?;
+
+        rt.transaction(|st: &mut State, _| {
+            st.minimum_allocation_size = size.into();
+            Ok(())
+        })
     }
 
-    /// Called by HandleInitTimeoutDeals from StorageMarketActor when a VerifiedDeal fails to init.
-    /// Restore allowable cap for the client, creating new entry if the client has been deleted.
-    pub fn restore_bytes<BS, RT>(rt: &mut RT, params: RestoreBytesParams) -> Result<(), ActorError>
+    /// Sets the number of distinct verifiers that must sign a datacap removal.
+    pub fn set_required_removal_approvers<BS, RT>(
+        rt: &mut RT,
+        approvers: u64,
+    ) -> Result<(), ActorError>
     where
         BS: Blockstore,
         RT: Runtime<BS>,
     {
-        rt.validate_immediate_caller_is(std::iter::once(&*STORAGE_MARKET_ACTOR_ADDR))?;
-        if params.deal_size < rt.policy().minimum_verified_deal_size {
-            return Err(actor_error!(
-                illegal_argument,
-                "Below minimum VerifiedDealSize requested in RestoreBytes: {}",
-                params.deal_size
-            ));
+        let state: State = rt.state()?;
+        rt.validate_immediate_caller_is(std::iter::once(&state.root_key))?;
+
+        if approvers == 0 {
+            return Err(actor_error!(illegal_argument, "removals need at least one approver"));
         }
 
-        let client = resolve_to_id_addr(rt, &params.address).map_err(|e| {
+        rt.transaction(|st: &mut State, _| {
+            st.required_removal_approvers = approvers;
+            Ok(())
+        })
+    }
+
+    /// Freezes or unfreezes a verifier. A frozen verifier keeps its allowance but can't
+    /// grant datacap or approve removals.
+    pub fn set_verifier_frozen<BS, RT>(
+        rt: &mut RT,
+        params: SetVerifierFrozenParams,
+    ) -> Result<(), ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        let verifier = resolve_to_id_addr(rt, &params.verifier).map_err(|e| {
             e.downcast_default(
-                ExitCode::USR_ILLEGAL_STATE,
-                format!("failed to resolve addr {} to ID addr", params.address),
+                ExitCode::USR_ILLEGAL_ARGUMENT,
+                format!("failed to resolve addr {} to ID addr", params.verifier),
             )
         })?;
 
-        let st: State = rt.state()?;
-        if client == st.root_key {
-            return Err(actor_error!(illegal_argument, "Cannot restore allowance for Rootkey"));
-        }
+        let state: State = rt.state()?;
+        rt.validate_immediate_caller_is(std::iter::once(&state.root_key))?;
 
         rt.transaction(|st: &mut State, rt| {
-            let mut verified_clients =
-                make_map_with_root_and_bitwidth(&st.verified_clients, rt.store(), HAMT_BIT_WIDTH)
-                    .map_err(|e| {
+            if !is_verifier(rt, st, verifier)? {
+                return Err(actor_error!(not_found, "{} is not a verifier", verifier));
+            }
+            let mut frozen = Set::from_root(rt.store(), &st.frozen_verifiers).map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load frozen verifiers")
+            })?;
+            if params.frozen {
+                frozen.put(verifier.to_bytes().into()).map_err(|e| {
                     e.downcast_default(
                         ExitCode::USR_ILLEGAL_STATE,
-                        "failed to load verified clients",
+                        format!("failed to freeze verifier {}", verifier),
                     )
                 })?;
-            let verifiers = make_map_with_root_and_bitwidth::<_, BigIntDe>(
-                &st.verifiers,
-                rt.store(),
-                HAMT_BIT_WIDTH,
-            )
-            .map_err(|e| {
-                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load verifiers")
-            })?;
-
-            // validate we are NOT attempting to do this for a verifier
-            let found = verifiers.contains_key(&client.to_bytes()).map_err(|e| {
-                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to get verifier")
-            })?;
-            if found {
-                return Err(actor_error!(
-                    illegal_argument,
-                    "cannot restore allowance for a verifier {}",
-                    client
-                ));
-            }
-
-            // Get existing cap
-            let BigIntDe(vc_cap) = verified_clients
-                .get(&client.to_bytes())
-                .map_err(|e| {
+            } else {
+                frozen.delete(&verifier.to_bytes()).map_err(|e| {
                     e.downcast_default(
                         ExitCode::USR_ILLEGAL_STATE,
-                        format!("failed to get verified client {}", &client),
+                        format!("failed to unfreeze verifier {}", verifier),
                     )
-                })?
-                .cloned()
-                .unwrap_or_default();
-
-            // Update to new cap
-            let new_vc_cap = vc_cap + &params.deal_size;
-            verified_clients.set(client.to_bytes().into(), BigIntDe(new_vc_cap)).map_err(|e| {
-                e.downcast_default(
-                    ExitCode::USR_ILLEGAL_STATE,
-                    format!("Failed to put verified client {}", client),
-                )
-            })?;
-
-            st.verified_clients = verified_clients.flush().map_err(|e| {
-                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to flush verified clients")
+                })?;
+            }
+            st.frozen_verifiers = frozen.root().map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to flush frozen verifiers")
             })?;
             Ok(())
-        })?;
-
-        Ok(())
+        })
     }
 
-    /// Removes DataCap allocated to a verified client.
-    pub fn remove_verified_client_data_cap<BS, RT>(
+    /// Exchanges the remaining allowances of two verifiers.
+    pub fn swap_verifier_allowances<BS, RT>(
         rt: &mut RT,
-        params: RemoveDataCapParams,
-    ) -> Result<RemoveDataCapReturn, ActorError>
+        params: SwapVerifierAllowancesParams,
+    ) -> Result<(), ActorError>
     where
         BS: Blockstore,
         RT: Runtime<BS>,
     {
-        let client = resolve_to_id_addr(rt, &params.verified_client_to_remove).map_err(|e| {
+        let a = resolve_to_id_addr(rt, &params.a).map_err(|e| {
             e.downcast_default(
                 ExitCode::USR_ILLEGAL_ARGUMENT,
-                format!(
-                    "failed to resolve client addr {} to ID addr",
-                    params.verified_client_to_remove
-                ),
+                format!("failed to resolve addr {} to ID addr", params.a),
+            )
+        })?;
+        let b = resolve_to_id_addr(rt, &params.b).map_err(|e| {
+            e.downcast_default(
+                ExitCode::USR_ILLEGAL_ARGUMENT,
+                format!("failed to resolve addr {} to ID addr", params.b),
             )
         })?;
+        if a == b {
+            return Err(actor_error!(
+                illegal_argument,
+                "cannot swap allowance of verifier {} with itself",
+                a
+            ));
+        }
 
-        let verifier_1 =
-            resolve_to_id_addr(rt, &params.verifier_request_1.verifier).map_err(|e| {
-                e.downcast_default(
-                    ExitCode::USR_ILLEGAL_ARGUMENT,
-                    format!(
-                        "failed to resolve verifier addr {} to ID addr",
-                        params.verifier_request_1.verifier
-                    ),
-                )
+        let state: State = rt.state()?;
+        rt.validate_immediate_caller_is(std::iter::once(&state.root_key))?;
+
+        rt.transaction(|st: &mut State, rt| {
+            let mut verifiers = make_map_with_root_and_bitwidth::<_, BigIntDe>(
+                &st.verifiers,
+                rt.store(),
+                HAMT_BIT_WIDTH,
+            )
+            .map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load verifiers")
             })?;
+            let a_cap = get_verifier_cap(&verifiers, a)?;
+            let b_cap = get_verifier_cap(&verifiers, b)?;
 
-        let verifier_2 =
-            resolve_to_id_addr(rt, &params.verifier_request_2.verifier).map_err(|e| {
-                e.downcast_default(
-                    ExitCode::USR_ILLEGAL_ARGUMENT,
-                    format!(
-                        "failed to resolve verifier addr {} to ID addr",
-                        params.verifier_request_2.verifier
-                    ),
-                )
+            for (verifier, cap) in [(a, b_cap), (b, a_cap)] {
+                verifiers.set(verifier.to_bytes().into(), BigIntDe(cap)).map_err(|e| {
+                    e.downcast_default(
+                        ExitCode::USR_ILLEGAL_STATE,
+                        format!("failed to update verifier {}", verifier),
+                    )
+                })?;
+            }
+            st.verifiers = verifiers.flush().map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to flush verifiers")
             })?;
+            Ok(())
+        })
+    }
 
-        if verifier_1 == verifier_2 {
+    /// Moves part of one verifier's remaining allowance to another verifier.
+    pub fn transfer_allowance<BS, RT>(
+        rt: &mut RT,
+        params: TransferAllowanceParams,
+    ) -> Result<(), ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        let from = resolve_to_id_addr(rt, &params.from).map_err(|e| {
+            e.downcast_default(
+                ExitCode::USR_ILLEGAL_ARGUMENT,
+                format!("failed to resolve addr {} to ID addr", params.from),
+            )
+        })?;
+        let to = resolve_to_id_addr(rt, &params.to).map_err(|e| {
+            e.downcast_default(
+                ExitCode::USR_ILLEGAL_ARGUMENT,
+                format!("failed to resolve addr {} to ID addr", params.to),
+            )
+        })?;
+        if from == to {
             return Err(actor_error!(
                 illegal_argument,
-                "need two different verifiers to send remove datacap request"
+                "cannot transfer allowance from verifier {} to itself",
+                from
             ));
         }
 
-        let mut removed_data_cap_amount = DataCap::default();
-        rt.transaction(|st: &mut State, rt| {
-            rt.validate_immediate_caller_is(std::iter::once(&st.root_key))?;
+        let state: State = rt.state()?;
+        rt.validate_immediate_caller_is(std::iter::once(&state.root_key))?;
 
-            // get current verified clients
-            let mut verified_clients = make_map_with_root_and_bitwidth::<_, BigIntDe>(
-                &st.verified_clients,
+        rt.transaction(|st: &mut State, rt| {
+            let mut verifiers = make_map_with_root_and_bitwidth::<_, BigIntDe>(
+                &st.verifiers,
                 rt.store(),
                 HAMT_BIT_WIDTH,
             )
             .map_err(|e| {
-                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load verified clients")
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load verifiers")
             })?;
+            let from_cap = get_verifier_cap(&verifiers, from)?;
+            let to_cap = get_verifier_cap(&verifiers, to)?;
 
-            // check that `client` is currently a verified client
-            let is_verified_client = verified_clients
-                .get(&client.to_bytes())
-                .map_err(|e| {
-                    e.downcast_default(
-                        ExitCode::USR_ILLEGAL_STATE,
-                        "failed to load verified clients",
-                    )
-                })?
-                .is_some();
-            if !is_verified_client {
-                return Err(actor_error!(not_found, "{} is not a verified client", client));
+            if from_cap < *params.amount {
+                return Err(actor_error!(
+                    illegal_argument,
+                    "verifier {} allowance {} is less than transfer amount {}",
+                    from,
+                    from_cap,
+                    params.amount
+                ));
             }
+            let new_from_cap = &from_cap - &*params.amount;
+            let new_to_cap = to_cap.checked_add_capped(&params.amount).ok_or_else(|| {
+                actor_error!(
+                    illegal_argument,
+                    "verifier {} allowance {} plus {} exceeds maximum {}",
+                    to,
+                    to_cap,
+                    params.amount,
+                    *MAX_DATACAP
+                )
+            })?;
 
-            // get existing cap allocated to client
-            let BigIntDe(previous_data_cap) = verified_clients
-                .get(&client.to_bytes())
-                .map_err(|e| {
+            for (verifier, cap) in [(from, new_from_cap), (to, new_to_cap)] {
+                verifiers.set(verifier.to_bytes().into(), BigIntDe(cap)).map_err(|e| {
                     e.downcast_default(
                         ExitCode::USR_ILLEGAL_STATE,
-                        format!("failed to get verified client {}", &client),
+                        format!("failed to update verifier {}", verifier),
                     )
-                })?
-                .cloned()
-                .unwrap_or_default();
-
-            // check that `verifier_1` is currently a verifier
-            if !is_verifier(rt, st, verifier_1)? {
-                return Err(actor_error!(not_found, "{} is not a verified client", verifier_1));
+                })?;
             }
+            st.verifiers = verifiers.flush().map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to flush verifiers")
+            })?;
+            Ok(())
+        })
+    }
 
-            // check that `verifier_2` is currently a verifier
-            if !is_verifier(rt, st, verifier_2)? {
-                return Err(actor_error!(not_found, "{} is not a verified client", verifier_2));
-            }
+    pub fn add_verified_client<BS, RT>(
+        rt: &mut RT,
+        params: AddVerifierClientParams,
+    ) -> Result<(), ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        // The caller will be verified by checking table below
+        rt.validate_immediate_caller_accept_any()?;
+        let verifier = rt.message().caller();
+        add_verified_client_from(rt, verifier, params)
+    }
 
-            // validate signatures
-            let mut proposal_ids = make_map_with_root_and_bitwidth::<_, RemoveDataCapProposalID>(
-                &st.remove_data_cap_proposal_ids,
+    /// Grants datacap to several clients from the caller's allowance. The caller must hold
+    /// enough allowance for the whole batch before any client is granted datacap.
+    pub fn add_verified_clients<BS, RT>(
+        rt: &mut RT,
+        params: AddVerifiedClientsParams,
+    ) -> Result<(), ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
+        let verifier = rt.message().caller();
+
+        let size = params.serialized_size();
+        if size > MAX_ADD_VERIFIED_CLIENTS_PARAMS_SIZE {
+            return Err(actor_error!(
+                illegal_argument,
+                "batch of {} verified clients is {} bytes, exceeding maximum {}",
+                params.clients.len(),
+                size,
+                MAX_ADD_VERIFIED_CLIENTS_PARAMS_SIZE
+            ));
+        }
+
+        let mut total = DataCapAccumulator::new();
+        for client in &params.clients {
+            total.add(&client.allowance);
+        }
+
+        let st: State = rt.state()?;
+        let verifier_cap = {
+            let verifiers = make_map_with_root_and_bitwidth::<_, BigIntDe>(
+                &st.verifiers,
                 rt.store(),
                 HAMT_BIT_WIDTH,
             )
             .map_err(|e| {
-                e.downcast_default(
-                    ExitCode::USR_ILLEGAL_STATE,
-                    "failed to load datacap removal proposal ids",
-                )
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load verifiers")
             })?;
+            get_verifier_cap(&verifiers, verifier)?
+        };
+        if verifier_cap < *total.total() {
+            return Err(actor_error!(
+                illegal_argument,
+                "total DataCap {} for {} verified clients exceeds verifier {} allowance {}",
+                total.total(),
+                total.count(),
+                verifier,
+                verifier_cap
+            ));
+        }
 
-            let verifier_1_id = use_proposal_id(&mut proposal_ids, verifier_1, client)?;
-            let verifier_2_id = use_proposal_id(&mut proposal_ids, verifier_2, client)?;
+        for client in params.clients {
+            add_verified_client_from(rt, verifier, client)?;
+        }
+        Ok(())
+    }
 
-            remove_data_cap_request_is_valid(
-                rt,
-                &params.verifier_request_1,
-                verifier_1_id,
-                &params.data_cap_amount_to_remove,
-                client,
-            )?;
-            remove_data_cap_request_is_valid(
-                rt,
-                &params.verifier_request_2,
-                verifier_2_id,
-                &params.data_cap_amount_to_remove,
-                client,
-            )?;
+    /// Grants datacap to a client from the allowance of a named verifier, rather than the
+    /// caller's own. Only the root key may act on a verifier's behalf.
+    pub fn add_verified_client_for<BS, RT>(
+        rt: &mut RT,
+        params: AddVerifiedClientForParams,
+    ) -> Result<(), ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        let st: State = rt.state()?;
+        rt.validate_immediate_caller_is(std::iter::once(&st.root_key))?;
 
-            let new_data_cap = &previous_data_cap - &params.data_cap_amount_to_remove;
-            if new_data_cap <= Zero::zero() {
-                // no DataCap remaining, delete verified client
-                verified_clients.delete(&client.to_bytes()).map_err(|e| {
+        let verifier = resolve_to_id_addr(rt, &params.verifier).map_err(|e| {
+            e.downcast_default(
+                ExitCode::USR_ILLEGAL_ARGUMENT,
+                format!("failed to resolve verifier addr {} to ID addr", params.verifier),
+            )
+        })?;
+        add_verified_client_from(rt, verifier, params.params)
+    }
+
+    /// Called by StorageMarketActor during PublishStorageDeals.
+    /// Do not allow partially verified deals (DealSize must be greater than equal to allowed cap).
+    /// Delete VerifiedClient if remaining DataCap is smaller than minimum VerifiedDealSize.
+    pub fn use_bytes<BS, RT>(rt: &mut RT, params: UseBytesParams) -> Result<(), ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_is(std::iter::once(&*STORAGE_MARKET_ACTOR_ADDR))?;
+
+        let client = resolve_to_id_addr(rt, &params.address).map_err(|e| {
+            e.downcast_default(
+                ExitCode::USR_ILLEGAL_STATE,
+                format!("failed to resolve addr {} to ID addr", params.address),
+            )
+        })?;
+
+        if params.deal_size < rt.policy().minimum_verified_deal_size {
+            return Err(actor_error!(
+                illegal_argument,
+                "Verified Dealsize {} is below minimum in usedbytes",
+                params.deal_size
+            ));
+        }
+
+        rt.transaction(|st: &mut State, rt| {
+            let mut verified_clients =
+                make_map_with_root_and_bitwidth(&st.verified_clients, rt.store(), HAMT_BIT_WIDTH)
+                    .map_err(|e| {
                     e.downcast_default(
                         ExitCode::USR_ILLEGAL_STATE,
-                        format!("failed to delete verified client {}", &client),
+                        "failed to load verified clients",
                     )
                 })?;
-                removed_data_cap_amount = previous_data_cap;
-            } else {
-                // update DataCap amount after removal
-                verified_clients
-                    .set(BytesKey::from(client.to_bytes()), BigIntDe(new_data_cap))
-                    .map_err(|e| {
-                        e.downcast_default(
-                            ExitCode::USR_ILLEGAL_STATE,
-                            format!("failed to update datacap for verified client {}", &client),
-                        )
-                    })?;
-                removed_data_cap_amount = params.data_cap_amount_to_remove.clone();
-            }
 
-            st.remove_data_cap_proposal_ids = proposal_ids.flush().map_err(|e| {
-                actor_error! {
+            let BigIntDe(vc_cap) = verified_clients
+                .get(&client.to_bytes())
+                .map_err(|e| {
+                    e.downcast_default(
+                        ExitCode::USR_ILLEGAL_STATE,
+                        format!("failed to get verified client {}", &client),
+                    )
+                })?
+                .ok_or_else(|| actor_error!(not_found, "no such verified client {}", client))?;
+            if vc_cap.is_negative() {
+                return Err(actor_error!(
                     illegal_state,
-                    "failed to flush proposal ids: {}",
-                    e
-                }
-            })?;
+                    "negative cap for client {}: {}",
+                    client,
+                    vc_cap
+                ));
+            }
+
+            if &params.deal_size > vc_cap {
+                return Err(actor_error!(
+                    illegal_argument,
+                    "Deal size of {} is greater than verifier_cap {} for verified client {}",
+                    params.deal_size,
+                    vc_cap,
+                    client
+                ));
+            };
+
+            let new_vc_cap = vc_cap - &params.deal_size;
+            if new_vc_cap < rt.policy().minimum_verified_deal_size {
+                // Delete entry if remaining DataCap is less than MinVerifiedDealSize.
+                // Will be restored later if the deal did not get activated with a ProvenSector.
+                verified_clients
+                    .delete(&client.to_bytes())
+                    .map_err(|e| {
+                        e.downcast_default(
+                            ExitCode::USR_ILLEGAL_STATE,
+                            format!("Failed to delete verified client {}", client),
+                        )
+                    })?
+                    .ok_or_else(|| {
+                        actor_error!(
+                            illegal_state,
+                            "Failed to delete verified client {}: not found",
+                            client
+                        )
+                    })?;
+            } else {
+                verified_clients.set(client.to_bytes().into(), BigIntDe(new_vc_cap)).map_err(
+                    |e| {
+                        e.downcast_default(
+                            ExitCode::USR_ILLEGAL_STATE,
+                            format!("Failed to update verified client {}", client),
+                        )
+                    },
+                )?;
+            }
+
             st.verified_clients = verified_clients.flush().map_err(|e| {
-                actor_error! {
-                    illegal_state,
-                    "failed to flush verified clients: {}",
-                    e
-                }
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to flush verified clients")
             })?;
             Ok(())
         })?;
 
-        Ok(RemoveDataCapReturn {
-            verified_client: params.verified_client_to_remove,
-            data_cap_removed: removed_data_cap_amount,
+        Ok(())
+    }
+
+    /// Called by HandleInitTimeoutDeals from StorageMarketActor when a VerifiedDeal fails to init.
+    /// Restore allowable cap for the client, creating new entry if the client has been deleted.
+    pub fn restore_bytes<BS, RT>(rt: &mut RT, params: RestoreBytesParams) -> Result<(), ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_is(std::iter::once(&*STORAGE_MARKET_ACTOR_ADDR))?;
+        if params.deal_size < rt.policy().minimum_verified_deal_size {
+            return Err(actor_error!(
+                illegal_argument,
+                "Below minimum VerifiedDealSize requested in RestoreBytes: {}",
+                params.deal_size
+            ));
+        }
+
+        let client = resolve_to_id_addr(rt, &params.address).map_err(|e| {
+            e.downcast_default(
+                ExitCode::USR_ILLEGAL_STATE,
+                format!("failed to resolve addr {} to ID addr", params.address),
+            )
+        })?;
+
+        let st: State = rt.state()?;
+        if client == st.root_key {
+            return Err(actor_error!(illegal_argument, "Cannot restore allowance for Rootkey"));
+        }
+
+        rt.transaction(|st: &mut State, rt| {
+            let mut verified_clients =
+                make_map_with_root_and_bitwidth(&st.verified_clients, rt.store(), HAMT_BIT_WIDTH)
+                    .map_err(|e| {
+                    e.downcast_default(
+                        ExitCode::USR_ILLEGAL_STATE,
+                        "failed to load verified clients",
+                    )
+                })?;
+            let verifiers = make_map_with_root_and_bitwidth::<_, BigIntDe>(
+                &st.verifiers,
+                rt.store(),
+                HAMT_BIT_WIDTH,
+            )
+            .map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load verifiers")
+            })?;
+
+            // validate we are NOT attempting to do this for a verifier
+            let found = verifiers.contains_key(&client.to_bytes()).map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to get verifier")
+            })?;
+            if found {
+                return Err(actor_error!(
+                    illegal_argument,
+                    "cannot restore allowance for a verifier {}",
+                    client
+                ));
+            }
+
+            // Get existing cap
+            let BigIntDe(vc_cap) = verified_clients
+                .get(&client.to_bytes())
+                .map_err(|e| {
+                    e.downcast_default(
+                        ExitCode::USR_ILLEGAL_STATE,
+                        format!("failed to get verified client {}", &client),
+                    )
+                })?
+                .cloned()
+                .unwrap_or_default();
+
+            // Update to new cap
+            let new_vc_cap = vc_cap + &params.deal_size;
+            verified_clients.set(client.to_bytes().into(), BigIntDe(new_vc_cap)).map_err(|e| {
+                e.downcast_default(
+                    ExitCode::USR_ILLEGAL_STATE,
+                    format!("Failed to put verified client {}", client),
+                )
+            })?;
+
+            st.verified_clients = verified_clients.flush().map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to flush verified clients")
+            })?;
+            Ok(())
+        })?;
+
+        Ok(())
+    }
+
+    /// Removes DataCap allocated to a verified client with a format V1 removal signed by
+    /// two verifiers. The state may require more approvers, in which case only
+    /// [`Actor::remove_verified_client_data_cap_v2`] can satisfy it.
+    pub fn remove_verified_client_data_cap<BS, RT>(
+        rt: &mut RT,
+        params: RemoveDataCapParams,
+    ) -> Result<RemoveDataCapReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        Self::remove_verified_client_data_cap_v2(rt, params.into())
+    }
+
+    /// Removes DataCap allocated to a verified client. The removal must be signed by at
+    /// least the state's required number of distinct verifiers.
+    pub fn remove_verified_client_data_cap_v2<BS, RT>(
+        rt: &mut RT,
+        params: RemoveDataCapParamsV2,
+    ) -> Result<RemoveDataCapReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        let (client, to_remove, verifiers) = resolve_removal(rt, &params)?;
+        rt.transaction(|st: &mut State, rt| {
+            rt.validate_immediate_caller_is(std::iter::once(&st.root_key))?;
+            apply_removal(st, rt, &params, client, &to_remove, &verifiers)
         })
     }
+
+    /// Removes datacap from several clients in one message. Each removal carries its own
+    /// verifier signatures; if any removal fails, none is applied.
+    pub fn batch_remove_data_cap<BS, RT>(
+        rt: &mut RT,
+        params: BatchRemoveDataCapParams,
+    ) -> Result<BatchRemoveDataCapReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        if params.removals.is_empty() {
+            return Err(actor_error!(illegal_argument, "batch remove datacap has no removals"));
+        }
+        let resolved = params
+            .removals
+            .iter()
+            .map(|removal| resolve_removal(rt, removal))
+            .collect::<Result<Vec<_>, _>>()?;
+        let results = apply_removals(rt, &params.removals, &resolved)?;
+        Ok(BatchRemoveDataCapReturn { results })
+    }
+
+    /// Removes datacap from one client in several steps within one message. Each step is
+    /// signed over the proposal IDs left by the step before, so each verifier's proposal
+    /// ID for the client advances once per step. If any step fails, none is applied.
+    pub fn stepwise_remove_data_cap<BS, RT>(
+        rt: &mut RT,
+        params: StepwiseRemoveDataCapParams,
+    ) -> Result<StepwiseRemoveDataCapReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        if params.steps.is_empty() {
+            return Err(actor_error!(illegal_argument, "stepwise remove datacap has no steps"));
+        }
+        let resolved = params
+            .steps
+            .iter()
+            .map(|step| resolve_removal(rt, step))
+            .collect::<Result<Vec<_>, _>>()?;
+        let client = resolved[0].0;
+        if let Some((other, _, _)) = resolved.iter().find(|(c, _, _)| *c != client) {
+            return Err(actor_error!(
+                illegal_argument,
+                "stepwise removal from {} includes a step for {}",
+                client,
+                other
+            ));
+        }
+        let results = apply_removals(rt, &params.steps, &resolved)?;
+        Ok(StepwiseRemoveDataCapReturn { results })
+    }
+
+    /// Records one verifier's approval of a datacap removal, signed over a format V1
+    /// proposal. Approvals are held per verifier and client until as many distinct verifiers
+    /// as the state requires have approved the same amount, when the removal executes as a
+    /// V1 removal signed by all of them. Each held approval's signature is checked when it
+    /// is submitted. An approval of a different amount than one already held for the client
+    /// is rejected; held approvals are dropped once their verifier is no longer active or
+    /// has signed another removal for the client.
+    pub fn submit_removal_approval<BS, RT>(
+        rt: &mut RT,
+        params: SubmitRemovalApprovalParams,
+    ) -> Result<SubmitRemovalApprovalReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        let client = resolve_to_id_addr(rt, &params.verified_client_to_remove).map_err(|e| {
+            e.downcast_default(
+                ExitCode::USR_ILLEGAL_ARGUMENT,
+                format!(
+                    "failed to resolve client addr {} to ID addr",
+                    params.verified_client_to_remove
+                ),
+            )
+        })?;
+        let verifier = resolve_to_id_addr(rt, &params.verifier_request.verifier).map_err(|e| {
+            e.downcast_default(
+                ExitCode::USR_ILLEGAL_ARGUMENT,
+                format!(
+                    "failed to resolve verifier addr {} to ID addr",
+                    params.verifier_request.verifier
+                ),
+            )
+        })?;
+        let amount = DataCap::from(params.data_cap_amount_to_remove);
+        let signature = params.verifier_request.signature;
+
+        rt.transaction(|st: &mut State, rt| {
+            rt.validate_immediate_caller_is(std::iter::once(&st.root_key))?;
+            require_active_verifier(rt, st, verifier)?;
+
+            let verified_clients = make_map_with_root_and_bitwidth::<_, BigIntDe>(
+                &st.verified_clients,
+                rt.store(),
+                HAMT_BIT_WIDTH,
+            )
+            .map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load verified clients")
+            })?;
+            let is_client = verified_clients.contains_key(&client.to_bytes()).map_err(|e| {
+                e.downcast_default(
+                    ExitCode::USR_ILLEGAL_STATE,
+                    format!("failed to get verified client {}", client),
+                )
+            })?;
+            if !is_client {
+                return Err(actor_error!(not_found, "{} is not a verified client", client));
+            }
+
+            let mut pending = make_map_with_root_and_bitwidth::<_, PendingRemovalApproval>(
+                &st.pending_removal_approvals,
+                rt.store(),
+                HAMT_BIT_WIDTH,
+            )
+            .map_err(|e| {
+                e.downcast_default(
+                    ExitCode::USR_ILLEGAL_STATE,
+                    "failed to load pending removal approvals",
+                )
+            })?;
+
+            // Drop approvals that can no longer execute and keep those of other verifiers.
+            let mut approvals = Vec::new();
+            for approval in client_pending_approvals(&pending, client)? {
+                if !approval_is_current(rt, st, &approval, client)? {
+                    delete_pending_approval(&mut pending, approval.verifier, client)?;
+                    continue;
+                }
+                if approval.amount != amount {
+                    return Err(actor_error!(
+                        illegal_argument,
+                        "approval to remove {} from {} conflicts with pending approval of {} by {}",
+                        amount,
+                        client,
+                        approval.amount,
+                        approval.verifier
+                    ));
+                }
+                if approval.verifier != verifier {
+                    approvals.push(approval);
+                }
+            }
+
+            if (approvals.len() as u64) + 1 < st.required_removal_approvers {
+                let proposal_id = st.proposal_id(rt.store(), &verifier, &client).map_err(|e| {
+                    e.downcast_default(
+                        ExitCode::USR_ILLEGAL_STATE,
+                        format!("failed to get proposal id for verifier {}", verifier),
+                    )
+                })?;
+                let request = RemoveDataCapRequest { verifier, signature };
+                remove_data_cap_request_is_valid(
+                    rt,
+                    &request,
+                    proposal_id.clone(),
+                    &amount,
+                    client,
+                    &DataCap::zero(),
+                    REMOVE_DATA_CAP_PROPOSAL_FORMAT_V1,
+                    None,
+                )?;
+                let approval = PendingRemovalApproval {
+                    verifier,
+                    proposal_id,
+                    amount,
+                    signature: request.signature,
+                };
+                let key = VerifierClientKey::new(verifier, client);
+                pending.set(key.to_bytes().into(), approval).map_err(|e| {
+                    e.downcast_default(
+                        ExitCode::USR_ILLEGAL_STATE,
+                        format!("failed to store removal approval of {} for {}", verifier, client),
+                    )
+                })?;
+                st.pending_removal_approvals = pending.flush().map_err(|e| {
+                    e.downcast_default(
+                        ExitCode::USR_ILLEGAL_STATE,
+                        "failed to flush pending removal approvals",
+                    )
+                })?;
+                return Ok(SubmitRemovalApprovalReturn { removal: None });
+            }
+
+            // Enough verifiers have approved: clear their approvals and execute the removal,
+            // which checks every signature.
+            delete_pending_approval(&mut pending, verifier, client)?;
+            for approval in &approvals {
+                delete_pending_approval(&mut pending, approval.verifier, client)?;
+            }
+            st.pending_removal_approvals = pending.flush().map_err(|e| {
+                e.downcast_default(
+                    ExitCode::USR_ILLEGAL_STATE,
+                    "failed to flush pending removal approvals",
+                )
+            })?;
+
+            let mut verifiers = vec![verifier];
+            verifiers.extend(approvals.iter().map(|approval| approval.verifier));
+            let mut others = approvals.into_iter().map(|approval| RemoveDataCapRequest {
+                verifier: approval.verifier,
+                signature: approval.signature,
+            });
+            let removal = RemoveDataCapParamsV2 {
+                verified_client_to_remove: client,
+                data_cap_amount_to_remove: amount.clone(),
+                verifier_request_1: RemoveDataCapRequest { verifier, signature },
+                verifier_request_2: others.next(),
+                format_version: REMOVE_DATA_CAP_PROPOSAL_FORMAT_V1,
+                expected_client_data_cap: DataCap::zero(),
+                additional_verifier_requests: others.collect(),
+                reason: None,
+            };
+            let ret = apply_removal(st, rt, &removal, client, &amount, &verifiers)?;
+            Ok(SubmitRemovalApprovalReturn { removal: Some(ret) })
+        })
+    }
+
+    /// Removes a client's entire datacap balance. This needs requests signed by at least two
+    /// verifiers, and by as many as the registry requires for removals, but the verifiers sign
+    /// over the client alone, so a balance change between signing and submission can't
+    /// invalidate them.
+    pub fn revoke_all_client_data_cap<BS, RT>(
+        rt: &mut RT,
+        params: RevokeAllDataCapParams,
+    ) -> Result<RemoveDataCapReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        let client = resolve_to_id_addr(rt, &params.verified_client).map_err(|e| {
+            e.downcast_default(
+                ExitCode::USR_ILLEGAL_ARGUMENT,
+                format!("failed to resolve client addr {} to ID addr", params.verified_client),
+            )
+        })?;
+        let mut requests = vec![&params.verifier_request_1, &params.verifier_request_2];
+        requests.extend(params.additional_verifier_requests.iter());
+        let verifiers = resolve_request_verifiers(rt, &requests)?;
+
+        let removed_data_cap_amount = rt.transaction(|st: &mut State, rt| {
+            rt.validate_immediate_caller_is(std::iter::once(&st.root_key))?;
+
+            let mut verified_clients = make_map_with_root_and_bitwidth::<_, BigIntDe>(
+                &st.verified_clients,
+                rt.store(),
+                HAMT_BIT_WIDTH,
+            )
+            .map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load verified clients")
+            })?;
+            let BigIntDe(previous_data_cap) = verified_clients
+                .get(&client.to_bytes())
+                .map_err(|e| {
+                    e.downcast_default(
+                        ExitCode::USR_ILLEGAL_STATE,
+                        format!("failed to get verified client {}", &client),
+                    )
+                })?
+                .cloned()
+                .ok_or_else(|| actor_error!(not_found, "{} is not a verified client", client))?;
+
+            require_removal_approvers(st, &verifiers)?;
+            for verifier in &verifiers {
+                require_active_verifier(rt, st, *verifier)?;
+            }
+
+            let mut proposal_ids = make_map_with_root_and_bitwidth::<_, RemoveDataCapProposalID>(
+                &st.remove_data_cap_proposal_ids,
+                rt.store(),
+                HAMT_BIT_WIDTH,
+            )
+            .map_err(|e| {
+                e.downcast_default(
+                    ExitCode::USR_ILLEGAL_STATE,
+                    "failed to load datacap removal proposal ids",
+                )
+            })?;
+            for (request, verifier) in requests.iter().zip(&verifiers) {
+                let id = use_proposal_id(&mut proposal_ids, *verifier, client)?;
+                revoke_all_request_is_valid(rt, request, id, client)?;
+            }
+
+            verified_clients.delete(&client.to_bytes()).map_err(|e| {
+                e.downcast_default(
+                    ExitCode::USR_ILLEGAL_STATE,
+                    format!("failed to delete verified client {}", &client),
+                )
+            })?;
+
+            st.append_removal_log(
+                rt.store(),
+                RemovalLogEntry {
+                    client,
+                    amount: previous_data_cap.clone(),
+                    approvers: verifiers.clone(),
+                    epoch: rt.curr_epoch(),
+                    reason: None,
+                },
+            )
+            .map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to append removal log")
+            })?;
+
+            st.remove_data_cap_proposal_ids = proposal_ids.flush().map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to flush proposal ids")
+            })?;
+            st.verified_clients = verified_clients.flush().map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to flush verified clients")
+            })?;
+            Ok(previous_data_cap)
+        })?;
+
+        Ok(RemoveDataCapReturn {
+            verified_client: params.verified_client,
+            data_cap_change: removed_data_cap_amount.diff(&DataCap::zero()),
+            data_cap_removed: removed_data_cap_amount,
+        })
+    }
+
+    /// Scales every verifier's remaining allowance by `numer / denom`, rounding down.
+    /// Clients' datacap is unaffected. Fails if any scaled allowance would exceed
+    /// [`MAX_DATACAP`].
+    pub fn scale_all_verifiers<BS, RT>(
+        rt: &mut RT,
+        params: ScaleAllVerifiersParams,
+    ) -> Result<(), ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        let state: State = rt.state()?;
+        rt.validate_immediate_caller_is(std::iter::once(&state.root_key))?;
+
+        if params.numer == 0 || params.denom == 0 {
+            return Err(actor_error!(
+                illegal_argument,
+                "invalid verifier scaling ratio {}/{}",
+                params.numer,
+                params.denom
+            ));
+        }
+
+        rt.transaction(|st: &mut State, rt| {
+            st.scale_verifiers(rt.store(), params.numer, params.denom).map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to scale verifiers")
+            })
+        })
+    }
+
+    /// Sets a client's datacap to an absolute target, with requests signed over the client
+    /// and target by at least two verifiers, and by as many as the registry requires for
+    /// removals. Lowering the datacap is logged as a removal, and a zero target removes the
+    /// client.
+    pub fn set_client_data_cap<BS, RT>(
+        rt: &mut RT,
+        params: SetClientDataCapParams,
+    ) -> Result<DataCapDiff, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        let client = resolve_to_id_addr(rt, &params.verified_client).map_err(|e| {
+            e.downcast_default(
+                ExitCode::USR_ILLEGAL_ARGUMENT,
+                format!("failed to resolve client addr {} to ID addr", params.verified_client),
+            )
+        })?;
+        let mut requests = vec![&params.verifier_request_1, &params.verifier_request_2];
+        requests.extend(params.additional_verifier_requests.iter());
+        let verifiers = resolve_request_verifiers(rt, &requests)?;
+        let target: DataCap = params.target.into();
+        if target > *MAX_DATACAP {
+            return Err(actor_error!(
+                illegal_argument,
+                "target datacap {} for verified client {} exceeds maximum {}",
+                target,
+                client,
+                *MAX_DATACAP
+            ));
+        }
+
+        rt.transaction(|st: &mut State, rt| {
+            rt.validate_immediate_caller_is(std::iter::once(&st.root_key))?;
+
+            let mut verified_clients = make_map_with_root_and_bitwidth::<_, BigIntDe>(
+                &st.verified_clients,
+                rt.store(),
+                HAMT_BIT_WIDTH,
+            )
+            .map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load verified clients")
+            })?;
+            let BigIntDe(previous_data_cap) = verified_clients
+                .get(&client.to_bytes())
+                .map_err(|e| {
+                    e.downcast_default(
+                        ExitCode::USR_ILLEGAL_STATE,
+                        format!("failed to get verified client {}", &client),
+                    )
+                })?
+                .cloned()
+                .ok_or_else(|| actor_error!(not_found, "{} is not a verified client", client))?;
+
+            require_removal_approvers(st, &verifiers)?;
+            for verifier in &verifiers {
+                require_active_verifier(rt, st, *verifier)?;
+            }
+
+            let mut proposal_ids = make_map_with_root_and_bitwidth::<_, RemoveDataCapProposalID>(
+                &st.remove_data_cap_proposal_ids,
+                rt.store(),
+                HAMT_BIT_WIDTH,
+            )
+            .map_err(|e| {
+                e.downcast_default(
+                    ExitCode::USR_ILLEGAL_STATE,
+                    "failed to load datacap removal proposal ids",
+                )
+            })?;
+            for (request, verifier) in requests.iter().zip(&verifiers) {
+                let id = use_proposal_id(&mut proposal_ids, *verifier, client)?;
+                set_data_cap_request_is_valid(rt, request, id, client, &target)?;
+            }
+
+            if target.is_zero() {
+                verified_clients.delete(&client.to_bytes()).map_err(|e| {
+                    e.downcast_default(
+                        ExitCode::USR_ILLEGAL_STATE,
+                        format!("failed to delete verified client {}", &client),
+                    )
+                })?;
+            } else {
+                verified_clients
+                    .set(BytesKey::from(client.to_bytes()), BigIntDe(target.clone()))
+                    .map_err(|e| {
+                    e.downcast_default(
+                        ExitCode::USR_ILLEGAL_STATE,
+                        format!("failed to update datacap for verified client {}", &client),
+                    )
+                })?;
+            }
+
+            let change = previous_data_cap.diff(&target);
+            if change.direction == DataCapDirection::Decrease {
+                st.append_removal_log(
+                    rt.store(),
+                    RemovalLogEntry {
+                        client,
+                        amount: change.delta.clone(),
+                        approvers: verifiers.clone(),
+                        epoch: rt.curr_epoch(),
+                        reason: None,
+                    },
+                )
+                .map_err(|e| {
+                    e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to append removal log")
+                })?;
+            }
+
+            st.remove_data_cap_proposal_ids = proposal_ids.flush().map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to flush proposal ids")
+            })?;
+            st.verified_clients = verified_clients.flush().map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to flush verified clients")
+            })?;
+            Ok(change)
+        })
+    }
+
+    /// Rewrites removal proposal ID entries keyed by non-ID addresses so that both key
+    /// components are ID addresses. Where an entry already exists under the normalized key,
+    /// the higher of the two IDs is kept so that no signed proposal can be replayed.
+    /// Entries with an address that can't be resolved are left untouched.
+    pub fn normalize_proposal_ids<BS, RT>(rt: &mut RT) -> Result<(), ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        let state: State = rt.state()?;
+        rt.validate_immediate_caller_is(std::iter::once(&state.root_key))?;
+
+        rt.transaction(|st: &mut State, rt| {
+            let mut proposal_ids = make_map_with_root_and_bitwidth::<_, RemoveDataCapProposalID>(
+                &st.remove_data_cap_proposal_ids,
+                rt.store(),
+                HAMT_BIT_WIDTH,
+            )
+            .map_err(|e| {
+                e.downcast_default(
+                    ExitCode::USR_ILLEGAL_STATE,
+                    "failed to load datacap removal proposal ids",
+                )
+            })?;
+
+            let mut stale = Vec::new();
+            proposal_ids
+                .for_each(|key, id| {
+                    let pair = VerifierClientKey::from_bytes(key)?;
+                    if pair.verifier.protocol() != Protocol::ID
+                        || pair.client.protocol() != Protocol::ID
+                    {
+                        stale.push((pair, id.0));
+                    }
+                    Ok(())
+                })
+                .map_err(|e| {
+                    e.downcast_default(
+                        ExitCode::USR_ILLEGAL_STATE,
+                        "failed to iterate datacap removal proposal ids",
+                    )
+                })?;
+
+            for (pair, id) in stale {
+                let (verifier, client) =
+                    match (rt.resolve_address(&pair.verifier), rt.resolve_address(&pair.client)) {
+                        (Some(verifier), Some(client)) => (verifier, client),
+                        _ => continue,
+                    };
+                let key = VerifierClientKey::new(verifier, client).to_bytes();
+                let existing = proposal_ids
+                    .get(&key)
+                    .map_err(|e| {
+                        e.downcast_default(
+                            ExitCode::USR_ILLEGAL_STATE,
+                            format!(
+                                "failed to get proposal id for verifier {} and client {}",
+                                verifier, client
+                            ),
+                        )
+                    })?
+                    .map_or(0, |existing| existing.0);
+
+                proposal_ids.delete(&pair.to_bytes()).map_err(|e| {
+                    e.downcast_default(
+                        ExitCode::USR_ILLEGAL_STATE,
+                        format!(
+                            "failed to delete proposal id for verifier {} and client {}",
+                            pair.verifier, pair.client
+                        ),
+                    )
+                })?;
+                proposal_ids
+                    .set(BytesKey::from(key), RemoveDataCapProposalID(id.max(existing)))
+                    .map_err(|e| {
+                        e.downcast_default(
+                            ExitCode::USR_ILLEGAL_STATE,
+                            format!(
+                                "failed to update proposal id for verifier {} and client {}",
+                                verifier, client
+                            ),
+                        )
+                    })?;
+            }
+
+            st.remove_data_cap_proposal_ids = proposal_ids.flush().map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to flush proposal ids")
+            })?;
+            Ok(())
+        })
+    }
+
+    /// Restores removal proposal IDs, e.g. from an export of another registry's state
+    /// during a migration. Existing IDs are only ever raised.
+    pub fn import_proposal_ids<BS, RT>(
+        rt: &mut RT,
+        params: ImportProposalIdsParams,
+    ) -> Result<(), ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        let state: State = rt.state()?;
+        rt.validate_immediate_caller_is(std::iter::once(&state.root_key))?;
+
+        for (verifier, client, _) in &params.proposal_ids {
+            if verifier.protocol() != Protocol::ID || client.protocol() != Protocol::ID {
+                return Err(actor_error!(
+                    illegal_argument,
+                    "proposal id for verifier {} and client {} must use ID addresses",
+                    verifier,
+                    client
+                ));
+            }
+        }
+
+        rt.transaction(|st: &mut State, rt| {
+            st.import_proposal_ids(rt.store(), &params.proposal_ids).map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to import proposal ids")
+            })
+        })
+    }
+
+    /// Adds several verifiers at once, for bootstrapping a network or migrating state.
+    /// Each verifier is checked as by `add_verifier`, and none may appear twice or already
+    /// be a verifier. Either all are added or none are.
+    pub fn import_verifiers<BS, RT>(
+        rt: &mut RT,
+        params: ImportVerifiersParams,
+    ) -> Result<(), ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        let st: State = rt.state()?;
+        rt.validate_immediate_caller_is(std::iter::once(&st.root_key))?;
+
+        let mut imports = Vec::with_capacity(params.verifiers.len());
+        for entry in params.verifiers {
+            if *entry.allowance < rt.policy().minimum_verified_deal_size {
+                return Err(actor_error!(
+                    illegal_argument,
+                    "Allowance {} below minimum deal size for add verifier {}",
+                    entry.allowance,
+                    entry.address
+                ));
+            }
+            let verifier = resolve_to_id_addr(rt, &entry.address).map_err(|e| {
+                e.downcast_default(
+                    ExitCode::USR_ILLEGAL_STATE,
+                    format!("failed to resolve addr {} to ID addr", entry.address),
+                )
+            })?;
+            if verifier == st.root_key {
+                return Err(actor_error!(illegal_argument, "Rootkey cannot be added as verifier"));
+            }
+            if imports.iter().any(|(addr, _)| *addr == verifier) {
+                return Err(actor_error!(illegal_argument, "verifier {} imported twice", verifier));
+            }
+            imports.push((verifier, entry.allowance.into_data_cap()));
+        }
+
+        rt.transaction(|st: &mut State, rt| {
+            let mut verifiers = make_map_with_root_and_bitwidth::<_, BigIntDe>(
+                &st.verifiers,
+                rt.store(),
+                HAMT_BIT_WIDTH,
+            )
+            .map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load verifiers")
+            })?;
+            let verified_clients = make_map_with_root_and_bitwidth::<_, BigIntDe>(
+                &st.verified_clients,
+                rt.store(),
+                HAMT_BIT_WIDTH,
+            )
+            .map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load verified clients")
+            })?;
+
+            for (verifier, allowance) in imports {
+                let is_client =
+                    verified_clients.contains_key(&verifier.to_bytes()).map_err(|e| {
+                        e.downcast_default(
+                            ExitCode::USR_ILLEGAL_STATE,
+                            format!("failed to get client state for {}", verifier),
+                        )
+                    })?;
+                if is_client {
+                    return Err(actor_error!(
+                        illegal_argument,
+                        "verified client {} cannot become a verifier",
+                        verifier
+                    ));
+                }
+                let is_verifier = verifiers.contains_key(&verifier.to_bytes()).map_err(|e| {
+                    e.downcast_default(
+                        ExitCode::USR_ILLEGAL_STATE,
+                        format!("failed to get verifier {}", verifier),
+                    )
+                })?;
+                if is_verifier {
+                    return Err(actor_error!(
+                        illegal_argument,
+                        "verifier {} already exists",
+                        verifier
+                    ));
+                }
+                verifiers.set(verifier.to_bytes().into(), BigIntDe(allowance)).map_err(|e| {
+                    e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to add verifier")
+                })?;
+            }
+            st.verifiers = verifiers.flush().map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to flush verifiers")
+            })?;
+            Ok(())
+        })
+    }
+
+    /// Returns the sum of the remaining allowance of all verifiers.
+    pub fn total_verifier_allowance<BS, RT>(rt: &mut RT) -> Result<DataCap, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
+        let st: State = rt.state()?;
+        st.total_verifier_allowance(rt.store()).map_err(|e| {
+            e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to sum verifier allowances")
+        })
+    }
+
+    /// Returns the cumulative datacap granted to a client by all verifiers, regardless of
+    /// how much has since been used or removed.
+    pub fn get_client_total_granted<BS, RT>(
+        rt: &mut RT,
+        client_addr: Address,
+    ) -> Result<DataCap, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
+        let client = resolve_to_id_addr(rt, &client_addr).map_err(|e| {
+            e.downcast_default(
+                ExitCode::USR_ILLEGAL_ARGUMENT,
+                format!("failed to resolve client addr {} to ID addr", client_addr),
+            )
+        })?;
+
+        let st: State = rt.state()?;
+        st.client_granted(rt.store(), &client).map_err(|e| {
+            e.downcast_default(
+                ExitCode::USR_ILLEGAL_STATE,
+                format!("failed to get grants to client {}", client),
+            )
+        })
+    }
+
+    /// Returns the distinct verifiers that have granted datacap to a client, in the order
+    /// of their first grant.
+    pub fn get_client_granters<BS, RT>(
+        rt: &mut RT,
+        client_addr: Address,
+    ) -> Result<Vec<Address>, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
+        let client = resolve_to_id_addr(rt, &client_addr).map_err(|e| {
+            e.downcast_default(
+                ExitCode::USR_ILLEGAL_ARGUMENT,
+                format!("failed to resolve client addr {} to ID addr", client_addr),
+            )
+        })?;
+
+        let st: State = rt.state()?;
+        st.client_granters(rt.store(), &client).map_err(|e| {
+            e.downcast_default(
+                ExitCode::USR_ILLEGAL_STATE,
+                format!("failed to get granters of client {}", client),
+            )
+        })
+    }
+
+    /// Returns the total client datacap ever removed.
+    pub fn get_total_removed<BS, RT>(rt: &mut RT) -> Result<DataCap, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
+        let st: State = rt.state()?;
+        Ok(st.total_removed)
+    }
+
+    /// Returns up to `count` of the most recent datacap removals, oldest first.
+    pub fn get_recent_removals<BS, RT>(
+        rt: &mut RT,
+        count: u64,
+    ) -> Result<GetRecentRemovalsReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
+        let st: State = rt.state()?;
+        let entries = st.recent_removals(rt.store(), count).map_err(|e| {
+            e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load removal log")
+        })?;
+        Ok(GetRecentRemovalsReturn { entries })
+    }
+
+    /// Returns a verifier's remaining allowance and the cumulative datacap it has granted.
+    pub fn get_verifier_utilization<BS, RT>(
+        rt: &mut RT,
+        verifier_addr: Address,
+    ) -> Result<VerifierUtilization, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
+        let verifier = resolve_to_id_addr(rt, &verifier_addr).map_err(|e| {
+            e.downcast_default(
+                ExitCode::USR_ILLEGAL_ARGUMENT,
+                format!("failed to resolve verifier addr {} to ID addr", verifier_addr),
+            )
+        })?;
+
+        let st: State = rt.state()?;
+        let verifiers = make_map_with_root_and_bitwidth::<_, BigIntDe>(
+            &st.verifiers,
+            rt.store(),
+            HAMT_BIT_WIDTH,
+        )
+        .map_err(|e| e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load verifiers"))?;
+        let remaining = get_verifier_cap(&verifiers, verifier)?;
+        let granted = st.verifier_granted(rt.store(), &verifier).map_err(|e| {
+            e.downcast_default(
+                ExitCode::USR_ILLEGAL_STATE,
+                format!("failed to get grants of verifier {}", verifier),
+            )
+        })?;
+        Ok(VerifierUtilization { remaining, granted })
+    }
+
+    /// Returns a verifier's remaining allowance, the removal proposal ID it must sign over
+    /// next for a client, and the client's datacap.
+    pub fn get_verifier_client_status<BS, RT>(
+        rt: &mut RT,
+        params: GetVerifierClientStatusParams,
+    ) -> Result<VerifierClientStatus, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
+        let verifier = resolve_to_id_addr(rt, &params.verifier).map_err(|e| {
+            e.downcast_default(
+                ExitCode::USR_ILLEGAL_ARGUMENT,
+                format!("failed to resolve verifier addr {} to ID addr", params.verifier),
+            )
+        })?;
+        let client = resolve_to_id_addr(rt, &params.client).map_err(|e| {
+            e.downcast_default(
+                ExitCode::USR_ILLEGAL_ARGUMENT,
+                format!("failed to resolve client addr {} to ID addr", params.client),
+            )
+        })?;
+
+        let st: State = rt.state()?;
+        let verifiers = make_map_with_root_and_bitwidth::<_, BigIntDe>(
+            &st.verifiers,
+            rt.store(),
+            HAMT_BIT_WIDTH,
+        )
+        .map_err(|e| e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load verifiers"))?;
+        let verifier_cap = get_verifier_cap(&verifiers, verifier)?;
+
+        let verified_clients = make_map_with_root_and_bitwidth::<_, BigIntDe>(
+            &st.verified_clients,
+            rt.store(),
+            HAMT_BIT_WIDTH,
+        )
+        .map_err(|e| {
+            e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load verified clients")
+        })?;
+        let client_balance = verified_clients
+            .get(&client.to_bytes())
+            .map_err(|e| {
+                e.downcast_default(
+                    ExitCode::USR_ILLEGAL_STATE,
+                    format!("failed to get verified client {}", &client),
+                )
+            })?
+            .map(|BigIntDe(cap)| cap.clone())
+            .unwrap_or_default();
+
+        let RemoveDataCapProposalID(proposal_id) =
+            st.proposal_id(rt.store(), &verifier, &client).map_err(|e| {
+                e.downcast_default(
+                    ExitCode::USR_ILLEGAL_STATE,
+                    format!("failed to get proposal id for verifier {}", verifier),
+                )
+            })?;
+        Ok(VerifierClientStatus { verifier_cap, proposal_id, client_balance })
+    }
+
+    /// Returns the address of the governance root key.
+    pub fn get_root_key<BS, RT>(rt: &mut RT) -> Result<Address, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
+        let st: State = rt.state()?;
+        Ok(st.root_key)
+    }
+
+    /// Returns whether an address is a verifier or a verified client, along with its
+    /// remaining allowance or datacap.
+    pub fn get_address_role<BS, RT>(rt: &mut RT, addr: Address) -> Result<AddressRole, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
+        let id_addr = match rt.resolve_address(&addr) {
+            Some(id_addr) => id_addr,
+            None => return Ok(AddressRole::None),
+        };
+
+        let st: State = rt.state()?;
+        let verifiers = make_map_with_root_and_bitwidth::<_, BigIntDe>(
+            &st.verifiers,
+            rt.store(),
+            HAMT_BIT_WIDTH,
+        )
+        .map_err(|e| e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load verifiers"))?;
+        let verifier_cap = verifiers.get(&id_addr.to_bytes()).map_err(|e| {
+            e.downcast_default(
+                ExitCode::USR_ILLEGAL_STATE,
+                format!("failed to get verifier {}", id_addr),
+            )
+        })?;
+        if let Some(BigIntDe(cap)) = verifier_cap {
+            return Ok(AddressRole::Verifier(cap.clone()));
+        }
+
+        let verified_clients = make_map_with_root_and_bitwidth::<_, BigIntDe>(
+            &st.verified_clients,
+            rt.store(),
+            HAMT_BIT_WIDTH,
+        )
+        .map_err(|e| {
+            e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load verified clients")
+        })?;
+        let client_cap = verified_clients.get(&id_addr.to_bytes()).map_err(|e| {
+            e.downcast_default(
+                ExitCode::USR_ILLEGAL_STATE,
+                format!("failed to get verified client {}", id_addr),
+            )
+        })?;
+        Ok(match client_cap {
+            Some(BigIntDe(cap)) => AddressRole::Client(cap.clone()),
+            None => AddressRole::None,
+        })
+    }
+
+    /// Returns whether the two addresses are distinct registered verifiers, and so could
+    /// together sign a datacap removal.
+    pub fn can_jointly_remove<BS, RT>(
+        rt: &mut RT,
+        params: CanJointlyRemoveParams,
+    ) -> Result<bool, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
+        let (verifier_1, verifier_2) =
+            match (rt.resolve_address(&params.verifier1), rt.resolve_address(&params.verifier2)) {
+                (Some(verifier_1), Some(verifier_2)) => (verifier_1, verifier_2),
+                _ => return Ok(false),
+            };
+        if verifier_1 == verifier_2 {
+            return Ok(false);
+        }
+
+        let st: State = rt.state()?;
+        for verifier in [verifier_1, verifier_2] {
+            if !is_verifier(rt, &st, verifier)? || is_frozen(rt, &st, verifier)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Returns the removal proposal IDs held by each verifier for a client.
+    pub fn get_client_proposal_ids<BS, RT>(
+        rt: &mut RT,
+        client_addr: Address,
+    ) -> Result<GetClientProposalIdsReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
+        let client = resolve_to_id_addr(rt, &client_addr).map_err(|e| {
+            e.downcast_default(
+                ExitCode::USR_ILLEGAL_ARGUMENT,
+                format!("failed to resolve client addr {} to ID addr", client_addr),
+            )
+        })?;
+
+        let st: State = rt.state()?;
+        let proposal_ids = st.client_proposal_ids(rt.store(), &client).map_err(|e| {
+            e.downcast_default(
+                ExitCode::USR_ILLEGAL_STATE,
+                format!("failed to load proposal ids for client {}", client),
+            )
+        })?;
+        Ok(GetClientProposalIdsReturn { proposal_ids })
+    }
+
+    /// Returns the proposal IDs two verifiers must sign over to remove `params.amount` of
+    /// datacap from a client, and whether the removal would delete the client.
+    pub fn prepare_removal<BS, RT>(
+        rt: &mut RT,
+        params: PrepareRemovalParams,
+    ) -> Result<PrepareRemovalReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
+        let amount = DataCap::from(params.amount);
+        let client = resolve_to_id_addr(rt, &params.client).map_err(|e| {
+            e.downcast_default(
+                ExitCode::USR_ILLEGAL_ARGUMENT,
+                format!("failed to resolve client addr {} to ID addr", params.client),
+            )
+        })?;
+        let mut verifiers = Vec::with_capacity(2);
+        for verifier_addr in [params.verifier1, params.verifier2] {
+            let verifier = resolve_to_id_addr(rt, &verifier_addr).map_err(|e| {
+                e.downcast_default(
+                    ExitCode::USR_ILLEGAL_ARGUMENT,
+                    format!("failed to resolve verifier addr {} to ID addr", verifier_addr),
+                )
+            })?;
+            verifiers.push(verifier);
+        }
+        if verifiers[0] == verifiers[1] {
+            return Err(actor_error!(
+                illegal_argument,
+                "need different verifiers to remove datacap, {} repeated",
+                verifiers[0]
+            ));
+        }
+
+        let st: State = rt.state()?;
+        let verified_clients = make_map_with_root_and_bitwidth::<_, BigIntDe>(
+            &st.verified_clients,
+            rt.store(),
+            HAMT_BIT_WIDTH,
+        )
+        .map_err(|e| {
+            e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load verified clients")
+        })?;
+        let BigIntDe(client_cap) = verified_clients
+            .get(&client.to_bytes())
+            .map_err(|e| {
+                e.downcast_default(
+                    ExitCode::USR_ILLEGAL_STATE,
+                    format!("failed to get verified client {}", &client),
+                )
+            })?
+            .ok_or_else(|| actor_error!(not_found, "{} is not a verified client", client))?;
+
+        let proposal_id = |verifier: &Address| {
+            st.proposal_id(rt.store(), verifier, &client).map_err(|e| {
+                e.downcast_default(
+                    ExitCode::USR_ILLEGAL_STATE,
+                    format!("failed to get proposal id for verifier {}", verifier),
+                )
+            })
+        };
+        Ok(PrepareRemovalReturn {
+            verifier1_proposal_id: proposal_id(&verifiers[0])?,
+            verifier2_proposal_id: proposal_id(&verifiers[1])?,
+            deletes_client: &amount >= client_cap,
+        })
+    }
+}
+
+/// Grants `params.allowance` of datacap to a client, drawn from `verifier`'s allowance.
+fn add_verified_client_from<BS, RT>(
+    rt: &mut RT,
+    verifier: Address,
+    params: AddVerifierClientParams,
+) -> Result<(), ActorError>
+where
+    BS: Blockstore,
+    RT: Runtime<BS>,
+{
+    let st: State = rt.state()?;
+    if !params.allowance.is_between(&st.minimum_allocation_size, &MAX_DATACAP) {
+        return Err(actor_error!(
+            illegal_argument,
+            "Allowance {} outside allocation size range [{}, {}] for add verified client {}",
+            params.allowance,
+            st.minimum_allocation_size,
+            *MAX_DATACAP,
+            params.address
+        ));
+    }
+
+    let client = resolve_to_id_addr(rt, &params.address).map_err(|e| {
+        e.downcast_default(
+            ExitCode::USR_ILLEGAL_STATE,
+            format!("failed to resolve addr {} to ID addr", params.address),
+        )
+    })?;
+
+    if client == st.root_key {
+        return Err(actor_error!(illegal_argument, "Rootkey cannot be added as verifier"));
+    }
+
+    rt.transaction(|st: &mut State, rt| {
+        let mut verifiers = make_map_with_root_and_bitwidth(
+            &st.verifiers,
+            rt.store(),
+            HAMT_BIT_WIDTH,
+        )
+        .map_err(|e| {
+            e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load verified clients")
+        })?;
+        let mut verified_clients =
+            make_map_with_root_and_bitwidth(&st.verified_clients, rt.store(), HAMT_BIT_WIDTH)
+                .map_err(|e| {
+                    e.downcast_default(
+                        ExitCode::USR_ILLEGAL_STATE,
+                        "failed to load verified clients",
+                    )
+                })?;
+
+        // Validate the granting address is one of the verifiers.
+        let BigIntDe(verifier_cap) = verifiers
+            .get(&verifier.to_bytes())
+            .map_err(|e| {
+                e.downcast_default(
+                    ExitCode::USR_ILLEGAL_STATE,
+                    format!("failed to get Verifier {}", verifier),
+                )
+            })?
+            .ok_or_else(|| actor_error!(not_found, format!("no such Verifier {}", verifier)))?;
+        if is_frozen(rt, st, verifier)? {
+            return Err(actor_error!(forbidden, "verifier {} is frozen", verifier));
+        }
+
+        // Validate client to be added isn't a verifier
+        let found = verifiers.contains_key(&client.to_bytes()).map_err(|e| {
+            e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to get verifier")
+        })?;
+        if found {
+            return Err(actor_error!(
+                illegal_argument,
+                "verifier {} cannot be added as a verified client",
+                client
+            ));
+        }
+
+        // Compute new verifier cap and update.
+        if verifier_cap < &*params.allowance {
+            return Err(actor_error!(
+                illegal_argument,
+                "Add more DataCap {} for VerifiedClient than allocated {}",
+                params.allowance,
+                verifier_cap
+            ));
+        }
+        let new_verifier_cap = verifier_cap - &*params.allowance;
+
+        verifiers.set(verifier.to_bytes().into(), BigIntDe(new_verifier_cap)).map_err(|e| {
+            e.downcast_default(
+                ExitCode::USR_ILLEGAL_STATE,
+                format!("Failed to update new verifier cap for {}", verifier),
+            )
+        })?;
+
+        let client_cap = verified_clients.get(&client.to_bytes()).map_err(|e| {
+            e.downcast_default(
+                ExitCode::USR_ILLEGAL_STATE,
+                format!("Failed to get verified client {}", client),
+            )
+        })?;
+        // if verified client exists, add allowance to existing cap
+        // otherwise, create new client with allownace
+        let client_cap = if let Some(BigIntDe(client_cap)) = client_cap {
+            client_cap + &*params.allowance
+        } else {
+            (*params.allowance).clone()
+        };
+        if client_cap > *MAX_DATACAP {
+            return Err(actor_error!(
+                illegal_argument,
+                "datacap {} for verified client {} would exceed maximum {}",
+                client_cap,
+                client,
+                *MAX_DATACAP
+            ));
+        }
+
+        verified_clients.set(client.to_bytes().into(), BigIntDe(client_cap.clone())).map_err(
+            |e| {
+                e.downcast_default(
+                    ExitCode::USR_ILLEGAL_STATE,
+                    format!("Failed to add verified client {} with cap {}", client, client_cap,),
+                )
+            },
+        )?;
+
+        // Record the grant against the verifier's and the client's cumulative totals.
+        let mut verifier_granted =
+            make_map_with_root_and_bitwidth(&st.verifier_granted, rt.store(), HAMT_BIT_WIDTH)
+                .map_err(|e| {
+                    e.downcast_default(
+                        ExitCode::USR_ILLEGAL_STATE,
+                        "failed to load verifier grants",
+                    )
+                })?;
+        add_granted(&mut verifier_granted, verifier, &params.allowance)?;
+        let mut client_granted =
+            make_map_with_root_and_bitwidth(&st.client_granted, rt.store(), HAMT_BIT_WIDTH)
+                .map_err(|e| {
+                    e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load client grants")
+                })?;
+        add_granted(&mut client_granted, client, &params.allowance)?;
+        let mut client_granters =
+            make_map_with_root_and_bitwidth(&st.client_granters, rt.store(), HAMT_BIT_WIDTH)
+                .map_err(|e| {
+                    e.downcast_default(
+                        ExitCode::USR_ILLEGAL_STATE,
+                        "failed to load client granters",
+                    )
+                })?;
+        add_granter(&mut client_granters, client, verifier)?;
+
+        st.verifiers = verifiers.flush().map_err(|e| {
+            e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to flush verifiers")
+        })?;
+        st.verified_clients = verified_clients.flush().map_err(|e| {
+            e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to flush verified clients")
+        })?;
+        st.verifier_granted = verifier_granted.flush().map_err(|e| {
+            e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to flush verifier grants")
+        })?;
+        st.client_granted = client_granted.flush().map_err(|e| {
+            e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to flush client grants")
+        })?;
+        st.client_granters = client_granters.flush().map_err(|e| {
+            e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to flush client granters")
+        })?;
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+/// Records `verifier` as a granter to `client`, unless it already is one.
+fn add_granter<BS>(
+    granters: &mut Map<BS, Vec<Address>>,
+    client: Address,
+    verifier: Address,
+) -> Result<(), ActorError>
+where
+    BS: Blockstore,
+{
+    let mut list = granters
+        .get(&client.to_bytes())
+        .map_err(|e| {
+            e.downcast_default(
+                ExitCode::USR_ILLEGAL_STATE,
+                format!("failed to get granters of client {}", client),
+            )
+        })?
+        .cloned()
+        .unwrap_or_default();
+    if list.contains(&verifier) {
+        return Ok(());
+    }
+    list.push(verifier);
+    granters.set(client.to_bytes().into(), list).map_err(|e| {
+        e.downcast_default(
+            ExitCode::USR_ILLEGAL_STATE,
+            format!("failed to update granters of client {}", client),
+        )
+    })?;
+    Ok(())
+}
+
+/// Adds `amount` to the cumulative total granted by or to `addr`.
+fn add_granted<BS>(
+    granted: &mut Map<BS, BigIntDe>,
+    addr: Address,
+    amount: &DataCap,
+) -> Result<(), ActorError>
+where
+    BS: Blockstore,
+{
+    let prev = granted
+        .get(&addr.to_bytes())
+        .map_err(|e| {
+            e.downcast_default(
+                ExitCode::USR_ILLEGAL_STATE,
+                format!("failed to get grant total of {}", addr),
+            )
+        })?
+        .map(|BigIntDe(prev)| prev.clone());
+    let total = DataCap::add_opt(prev, Some(amount.clone()));
+    granted.set(addr.to_bytes().into(), BigIntDe(total)).map_err(|e| {
+        e.downcast_default(
+            ExitCode::USR_ILLEGAL_STATE,
+            format!("failed to update grant total of {}", addr),
+        )
+    })?;
+    Ok(())
+}
+
+fn get_verifier_cap<BS>(
+    verifiers: &Map<BS, BigIntDe>,
+    verifier: Address,
+) -> Result<DataCap, ActorError>
+where
+    BS: Blockstore,
+{
+    let BigIntDe(cap) = verifiers
+        .get(&verifier.to_bytes())
+        .map_err(|e| {
+            e.downcast_default(
+                ExitCode::USR_ILLEGAL_STATE,
+                format!("failed to get verifier {}", verifier),
+            )
+        })?
+        .ok_or_else(|| actor_error!(not_found, "no such verifier {}", verifier))?;
+    Ok(cap.clone())
+}
+
+fn is_verifier<BS, RT>(rt: &RT, st: &State, address: Address) -> Result<bool, ActorError>
+where
+    BS: Blockstore,
+    RT: Runtime<BS>,
+{
+    let verifiers = make_map_with_root_and_bitwidth::<_, BigIntDe>(
+        &st.verifiers,
+        rt.store(),
+        HAMT_BIT_WIDTH,
+    )
+    .map_err(|e| e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load verifiers"))?;
+
+    // check that the `address` is currently a verified client
+    let found = verifiers
+        .contains_key(&address.to_bytes())
+        .map_err(|e| e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to get verifier"))?;
+
+    Ok(found)
+}
+
+fn is_frozen<BS, RT>(rt: &RT, st: &State, verifier: Address) -> Result<bool, ActorError>
+where
+    BS: Blockstore,
+    RT: Runtime<BS>,
+{
+    st.is_verifier_frozen(rt.store(), &verifier).map_err(|e| {
+        e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load frozen verifiers")
+    })
+}
+
+/// Fails unless `verifier` is a registered verifier that isn't frozen.
+fn require_active_verifier<BS, RT>(rt: &RT, st: &State, verifier: Address) -> Result<(), ActorError>
+where
+    BS: Blockstore,
+    RT: Runtime<BS>,
+{
+    if !is_verifier(rt, st, verifier)? {
+        return Err(actor_error!(not_found, "{} is not a verifier", verifier));
+    }
+    if is_frozen(rt, st, verifier)? {
+        return Err(actor_error!(forbidden, "verifier {} is frozen", verifier));
+    }
+    Ok(())
 }
 
-fn is_verifier<BS, RT>(rt: &RT, st: &State, address: Address) -> Result<bool, ActorError>
+/// Returns whether a pending removal approval can still be executed: its verifier is
+/// active and has not signed another removal for `client` since.
+fn approval_is_current<BS, RT>(
+    rt: &RT,
+    st: &State,
+    approval: &PendingRemovalApproval,
+    client: Address,
+) -> Result<bool, ActorError>
 where
     BS: Blockstore,
     RT: Runtime<BS>,
 {
-    let verifiers = make_map_with_root_and_bitwidth::<_, BigIntDe>(
-        &st.verifiers,
-        rt.store(),
-        HAMT_BIT_WIDTH,
-    )
-    .map_err(|e| e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load verifiers"))?;
+    if !is_verifier(rt, st, approval.verifier)? || is_frozen(rt, st, approval.verifier)? {
+        return Ok(false);
+    }
+    let proposal_id = st.proposal_id(rt.store(), &approval.verifier, &client).map_err(|e| {
+        e.downcast_default(
+            ExitCode::USR_ILLEGAL_STATE,
+            format!("failed to get proposal id for verifier {}", approval.verifier),
+        )
+    })?;
+    Ok(proposal_id == approval.proposal_id)
+}
 
-    // check that the `address` is currently a verified client
-    let found = verifiers
-        .contains_key(&address.to_bytes())
-        .map_err(|e| e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to get verifier"))?;
+/// Returns the pending removal approvals held for `client`, ordered by verifier address.
+fn client_pending_approvals<BS>(
+    pending: &Map<BS, PendingRemovalApproval>,
+    client: Address,
+) -> Result<Vec<PendingRemovalApproval>, ActorError>
+where
+    BS: Blockstore,
+{
+    let mut approvals = Vec::new();
+    pending
+        .for_each(|key, approval| {
+            if VerifierClientKey::from_bytes(key)?.client == client {
+                approvals.push(approval.clone());
+            }
+            Ok(())
+        })
+        .map_err(|e| {
+            e.downcast_default(
+                ExitCode::USR_ILLEGAL_STATE,
+                "failed to iterate pending removal approvals",
+            )
+        })?;
+    approvals.sort_by_key(|approval| approval.verifier.to_bytes());
+    Ok(approvals)
+}
 
-    Ok(found)
+fn delete_pending_approval<BS>(
+    pending: &mut Map<BS, PendingRemovalApproval>,
+    verifier: Address,
+    client: Address,
+) -> Result<(), ActorError>
+where
+    BS: Blockstore,
+{
+    pending.delete(&VerifierClientKey::new(verifier, client).to_bytes()).map_err(|e| {
+        e.downcast_default(
+            ExitCode::USR_ILLEGAL_STATE,
+            format!("failed to delete removal approval of {} for {}", verifier, client),
+        )
+    })?;
+    Ok(())
 }
 
 fn use_proposal_id<BS>(
@@ -705,7 +2350,7 @@ fn use_proposal_id<BS>(
 where
     BS: Blockstore,
 {
-    let key = AddrPairKey::new(verifier, client);
+    let key = VerifierClientKey::new(verifier, client);
 
     let maybe_id = proposal_ids.get(&key.to_bytes()).map_err(|e| {
         actor_error!(
@@ -737,32 +2382,396 @@ where
     Ok(curr_id)
 }
 
-fn remove_data_cap_request_is_valid<BS, RT>(
+/// Checks a datacap removal's parameters and resolves its client and verifiers to ID
+/// addresses, returning the client, the amount to remove and the verifiers in request order.
+fn resolve_removal<BS, RT>(
+    rt: &mut RT,
+    params: &RemoveDataCapParamsV2,
+) -> Result<(Address, DataCap, Vec<Address>), ActorError>
+where
+    BS: Blockstore,
+    RT: Runtime<BS>,
+{
+    let client = resolve_to_id_addr(rt, &params.verified_client_to_remove).map_err(|e| {
+        e.downcast_default(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            format!(
+                "failed to resolve client addr {} to ID addr",
+                params.verified_client_to_remove
+            ),
+        )
+    })?;
+
+    let to_remove = DataCap::try_from_power(params.data_cap_amount_to_remove.clone())?;
+    if let Some(reason) = &params.reason {
+        if params.format_version < REMOVE_DATA_CAP_PROPOSAL_FORMAT_V3 {
+            return Err(actor_error!(
+                illegal_argument,
+                "removal reason requires proposal format {} or later, got {}",
+                REMOVE_DATA_CAP_PROPOSAL_FORMAT_V3,
+                params.format_version
+            ));
+        }
+        if reason.len() > MAX_REMOVAL_REASON_LEN {
+            return Err(actor_error!(
+                illegal_argument,
+                "removal reason of {} bytes exceeds maximum {}",
+                reason.len(),
+                MAX_REMOVAL_REASON_LEN
+            ));
+        }
+    }
+
+    let verifiers = resolve_request_verifiers(rt, &removal_requests(params))?;
+    Ok((client, to_remove, verifiers))
+}
+
+/// Resolves the verifiers of `requests` to ID addresses in request order, failing if any
+/// verifier appears more than once.
+fn resolve_request_verifiers<BS, RT>(
+    rt: &mut RT,
+    requests: &[&RemoveDataCapRequest],
+) -> Result<Vec<Address>, ActorError>
+where
+    BS: Blockstore,
+    RT: Runtime<BS>,
+{
+    let mut verifiers = Vec::with_capacity(requests.len());
+    for request in requests {
+        let verifier = resolve_to_id_addr(rt, &request.verifier).map_err(|e| {
+            e.downcast_default(
+                ExitCode::USR_ILLEGAL_ARGUMENT,
+                format!("failed to resolve verifier addr {} to ID addr", request.verifier),
+            )
+        })?;
+        if verifiers.contains(&verifier) {
+            return Err(actor_error!(
+                illegal_argument,
+                "need different verifiers to send datacap request, {} repeated",
+                verifier
+            ));
+        }
+        verifiers.push(verifier);
+    }
+    Ok(verifiers)
+}
+
+/// Checks that a request was approved by at least as many verifiers as the registry
+/// requires for removals.
+fn require_removal_approvers(st: &State, verifiers: &[Address]) -> Result<(), ActorError> {
+    if (verifiers.len() as u64) < st.required_removal_approvers {
+        return Err(actor_error!(
+            illegal_argument,
+            "datacap request needs {} verifiers, got {}",
+            st.required_removal_approvers,
+            verifiers.len()
+        ));
+    }
+    Ok(())
+}
+
+/// Returns a removal's verifier requests in order: the first, the optional second, then
+/// any additional requests.
+fn removal_requests(params: &RemoveDataCapParamsV2) -> Vec<&RemoveDataCapRequest> {
+    let mut requests = vec![&params.verifier_request_1];
+    requests.extend(params.verifier_request_2.iter());
+    requests.extend(params.additional_verifier_requests.iter());
+    requests
+}
+
+/// Applies removals resolved by [`resolve_removal`] in order in a single transaction,
+/// after checking that the root key sent the message.
+fn apply_removals<BS, RT>(
+    rt: &mut RT,
+    removals: &[RemoveDataCapParamsV2],
+    resolved: &[(Address, DataCap, Vec<Address>)],
+) -> Result<Vec<RemoveDataCapReturn>, ActorError>
+where
+    BS: Blockstore,
+    RT: Runtime<BS>,
+{
+    rt.transaction(|st: &mut State, rt| {
+        rt.validate_immediate_caller_is(std::iter::once(&st.root_key))?;
+        removals
+            .iter()
+            .zip(resolved)
+            .map(|(removal, (client, to_remove, verifiers))| {
+                apply_removal(st, rt, removal, *client, to_remove, verifiers)
+            })
+            .collect()
+    })
+}
+
+/// Applies a removal resolved by [`resolve_removal`] to `st`, checking each verifier's
+/// signature. The caller has already validated that the root key sent the message.
+fn apply_removal<BS, RT>(
+    st: &mut State,
+    rt: &RT,
+    params: &RemoveDataCapParamsV2,
+    client: Address,
+    to_remove: &DataCap,
+    verifiers: &[Address],
+) -> Result<RemoveDataCapReturn, ActorError>
+where
+    BS: Blockstore,
+    RT: Runtime<BS>,
+{
+    // get current verified clients
+    let mut verified_clients = make_map_with_root_and_bitwidth::<_, BigIntDe>(
+        &st.verified_clients,
+        rt.store(),
+        HAMT_BIT_WIDTH,
+    )
+    .map_err(|e| {
+        e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load verified clients")
+    })?;
+
+    // check that `client` is currently a verified client
+    let is_verified_client = verified_clients
+        .get(&client.to_bytes())
+        .map_err(|e| {
+            e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load verified clients")
+        })?
+        .is_some();
+    if !is_verified_client {
+        return Err(actor_error!(not_found, "{} is not a verified client", client));
+    }
+
+    // get existing cap allocated to client
+    let BigIntDe(previous_data_cap) = verified_clients
+        .get(&client.to_bytes())
+        .map_err(|e| {
+            e.downcast_default(
+                ExitCode::USR_ILLEGAL_STATE,
+                format!("failed to get verified client {}", &client),
+            )
+        })?
+        .cloned()
+        .unwrap_or_default();
+
+    if params.format_version >= REMOVE_DATA_CAP_PROPOSAL_FORMAT_V2
+        && previous_data_cap != params.expected_client_data_cap
+    {
+        return Err(actor_error!(
+            illegal_argument,
+            "datacap {} of client {} differs from the {} the removal was signed against",
+            previous_data_cap,
+            client,
+            params.expected_client_data_cap
+        ));
+    }
+
+    require_removal_approvers(st, verifiers)?;
+
+    // check that each verifier is currently an unfrozen verifier
+    for verifier in verifiers {
+        require_active_verifier(rt, st, *verifier)?;
+    }
+
+    // validate signatures
+    let mut proposal_ids = make_map_with_root_and_bitwidth::<_, RemoveDataCapProposalID>(
+        &st.remove_data_cap_proposal_ids,
+        rt.store(),
+        HAMT_BIT_WIDTH,
+    )
+    .map_err(|e| {
+        e.downcast_default(
+            ExitCode::USR_ILLEGAL_STATE,
+            "failed to load datacap removal proposal ids",
+        )
+    })?;
+
+    for (request, verifier) in removal_requests(params).iter().zip(verifiers) {
+        let id = use_proposal_id(&mut proposal_ids, *verifier, client)?;
+        remove_data_cap_request_is_valid(
+            rt,
+            request,
+            id,
+            to_remove,
+            client,
+            &previous_data_cap,
+            params.format_version,
+            params.reason.as_deref(),
+        )?;
+    }
+
+    let new_data_cap = (&previous_data_cap - to_remove).clamp_nonneg();
+    let (removed_data_cap_amount, data_cap_change) = if new_data_cap.is_zero() {
+        // no DataCap remaining, delete verified client
+        verified_clients.delete(&client.to_bytes()).map_err(|e| {
+            e.downcast_default(
+                ExitCode::USR_ILLEGAL_STATE,
+                format!("failed to delete verified client {}", &client),
+            )
+        })?;
+        let change = previous_data_cap.diff(&DataCap::zero());
+        (previous_data_cap, change)
+    } else {
+        // update DataCap amount after removal
+        let change = previous_data_cap.diff(&new_data_cap);
+        verified_clients.set(BytesKey::from(client.to_bytes()), BigIntDe(new_data_cap)).map_err(
+            |e| {
+                e.downcast_default(
+                    ExitCode::USR_ILLEGAL_STATE,
+                    format!("failed to update datacap for verified client {}", &client),
+                )
+            },
+        )?;
+        (to_remove.clone(), change)
+    };
+
+    st.append_removal_log(
+        rt.store(),
+        RemovalLogEntry {
+            client,
+            amount: removed_data_cap_amount.clone(),
+            approvers: verifiers.to_vec(),
+            epoch: rt.curr_epoch(),
+            reason: params.reason.clone(),
+        },
+    )
+    .map_err(|e| e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to append removal log"))?;
+
+    st.remove_data_cap_proposal_ids = proposal_ids.flush().map_err(|e| {
+        actor_error! {
+            illegal_state,
+            "failed to flush proposal ids: {}",
+            e
+        }
+    })?;
+    st.verified_clients = verified_clients.flush().map_err(|e| {
+        actor_error! {
+            illegal_state,
+            "failed to flush verified clients: {}",
+            e
+        }
+    })?;
+
+    Ok(RemoveDataCapReturn {
+        verified_client: params.verified_client_to_remove,
+        data_cap_removed: removed_data_cap_amount,
+        data_cap_change,
+    })
+}
+
+fn revoke_all_request_is_valid<BS, RT>(
     rt: &RT,
     request: &RemoveDataCapRequest,
     id: RemoveDataCapProposalID,
-    to_remove: &DataCap,
     client: Address,
 ) -> Result<(), ActorError>
 where
     BS: Blockstore,
     RT: Runtime<BS>,
 {
-    let proposal = RemoveDataCapProposal {
-        removal_proposal_id: id,
-        data_cap_amount: to_remove.clone(),
+    let proposal = RevokeAllDataCapProposal { verified_client: client, removal_proposal_id: id };
+    let b = RawBytes::serialize(proposal).map_err(
+        |e| actor_error!(serialization; "failed to marshal revoke datacap request: {}", e),
+    )?;
+    let payload = [SIGNATURE_DOMAIN_SEPARATION_REVOKE_ALL_DATA_CAP, b.bytes()].concat();
+
+    rt.verify_signature(&request.signature, &request.verifier, &payload).map_err(
+        |e| actor_error!(illegal_argument; "invalid signature for datacap revoke request: {}", e),
+    )
+}
+
+fn set_data_cap_request_is_valid<BS, RT>(
+    rt: &RT,
+    request: &RemoveDataCapRequest,
+    id: RemoveDataCapProposalID,
+    client: Address,
+    target: &DataCap,
+) -> Result<(), ActorError>
+where
+    BS: Blockstore,
+    RT: Runtime<BS>,
+{
+    let proposal = SetClientDataCapProposal {
         verified_client: client,
+        target: target.clone(),
+        removal_proposal_id: id,
     };
+    let b = RawBytes::serialize(proposal)
+        .map_err(|e| actor_error!(serialization; "failed to marshal set datacap request: {}", e))?;
+    let payload = [SIGNATURE_DOMAIN_SEPARATION_SET_CLIENT_DATA_CAP, b.bytes()].concat();
 
-    let b = RawBytes::serialize(proposal).map_err(|e| {
-        actor_error!(
-                serialization; "failed to marshal remove datacap request: {}", e)
-    })?;
+    rt.verify_signature(&request.signature, &request.verifier, &payload).map_err(
+        |e| actor_error!(illegal_argument; "invalid signature for set datacap request: {}", e),
+    )
+}
 
-    let payload = [SIGNATURE_DOMAIN_SEPARATION_REMOVE_DATA_CAP, b.bytes()].concat();
+fn remove_data_cap_request_is_valid<BS, RT>(
+    rt: &RT,
+    request: &RemoveDataCapRequest,
+    id: RemoveDataCapProposalID,
+    to_remove: &DataCap,
+    client: Address,
+    client_data_cap: &DataCap,
+    format_version: u64,
+    reason: Option<&str>,
+) -> Result<(), ActorError>
+where
+    BS: Blockstore,
+    RT: Runtime<BS>,
+{
+    // The proposal is rebuilt from the client being removed rather than taken from the
+    // request, so a signature over a proposal naming any other client fails here.
+    let signer = &request.verifier;
+    let signature = &request.signature;
+    match format_version {
+        REMOVE_DATA_CAP_PROPOSAL_FORMAT_V1 => {
+            let proposal = RemoveDataCapProposal {
+                removal_proposal_id: id,
+                data_cap_amount: to_remove.clone(),
+                verified_client: client,
+            };
+            verify_remove_proposal_signature(rt, signer, &proposal, signature)
+        }
+        REMOVE_DATA_CAP_PROPOSAL_FORMAT_V2 => {
+            let proposal = RemoveDataCapProposalV2 {
+                removal_proposal_id: id,
+                data_cap_amount: to_remove.clone(),
+                verified_client: client,
+                client_data_cap: client_data_cap.clone(),
+            };
+            verify_remove_proposal_signature(rt, signer, &proposal, signature)
+        }
+        REMOVE_DATA_CAP_PROPOSAL_FORMAT_V3 => {
+            let proposal = RemoveDataCapProposalV3 {
+                removal_proposal_id: id,
+                data_cap_amount: to_remove.clone(),
+                verified_client: client,
+                client_data_cap: client_data_cap.clone(),
+                reason: reason.map(str::to_owned),
+            };
+            verify_remove_proposal_signature(rt, signer, &proposal, signature)
+        }
+        _ => Err(actor_error!(
+            illegal_argument,
+            "unknown remove datacap proposal format version {}",
+            format_version
+        )),
+    }
+}
 
-    // verify signature of proposal
-    rt.verify_signature(&request.signature, &request.verifier, &payload).map_err(
+/// Checks `verifier`'s signature over a datacap removal proposal of any format, exactly
+/// as the actor does when the removal is submitted. Verifiers' tools can call this with
+/// their own [`Primitives`] to check a signature without the chain.
+pub fn verify_remove_proposal_signature<P, T>(
+    primitives: &P,
+    verifier: &Address,
+    proposal: &T,
+    signature: &Signature,
+) -> Result<(), ActorError>
+where
+    P: Primitives + ?Sized,
+    T: RemoveDataCapProposalFormat,
+{
+    let b = RawBytes::serialize(proposal).map_err(
+        |e| actor_error!(serialization; "failed to marshal remove datacap request: {}", e),
+    )?;
+    let payload = [SIGNATURE_DOMAIN_SEPARATION_REMOVE_DATA_CAP, b.bytes()].concat();
+    primitives.verify_signature(signature, verifier, &payload).map_err(
         |e| actor_error!(illegal_argument; "invalid signature for datacap removal request: {}", e),
     )
 }
@@ -807,6 +2816,133 @@ impl ActorCode for Actor {
                     Self::remove_verified_client_data_cap(rt, cbor::deserialize_params(params)?)?;
                 Ok(RawBytes::serialize(res)?)
             }
+            Some(Method::TotalVerifierAllowance) => {
+                let res = Self::total_verifier_allowance(rt)?;
+                Ok(RawBytes::serialize(BigIntSer(&res))?)
+            }
+            Some(Method::GetClientProposalIds) => {
+                let res = Self::get_client_proposal_ids(rt, cbor::deserialize_params(params)?)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::BurnVerifierAllowance) => {
+                Self::burn_verifier_allowance(rt, cbor::deserialize_params(params)?)?;
+                Ok(RawBytes::default())
+            }
+            Some(Method::DecreaseVerifierAllowance) => {
+                let res = Self::decrease_verifier_allowance(rt, cbor::deserialize_params(params)?)?;
+                Ok(RawBytes::serialize(BigIntSer(&res))?)
+            }
+            Some(Method::GetRootKey) => {
+                let res = Self::get_root_key(rt)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::NormalizeProposalIds) => {
+                Self::normalize_proposal_ids(rt)?;
+                Ok(RawBytes::default())
+            }
+            Some(Method::AddVerifiedClientFor) => {
+                Self::add_verified_client_for(rt, cbor::deserialize_params(params)?)?;
+                Ok(RawBytes::default())
+            }
+            Some(Method::GetRecentRemovals) => {
+                let res = Self::get_recent_removals(rt, cbor::deserialize_params(params)?)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::TransferAllowance) => {
+                Self::transfer_allowance(rt, cbor::deserialize_params(params)?)?;
+                Ok(RawBytes::default())
+            }
+            Some(Method::SetMinimumAllocationSize) => {
+                Self::set_minimum_allocation_size(rt, cbor::deserialize_params(params)?)?;
+                Ok(RawBytes::default())
+            }
+            Some(Method::GetAddressRole) => {
+                let res = Self::get_address_role(rt, cbor::deserialize_params(params)?)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::AddVerifiedClients) => {
+                Self::add_verified_clients(rt, cbor::deserialize_params(params)?)?;
+                Ok(RawBytes::default())
+            }
+            Some(Method::RevokeAllClientDataCap) => {
+                let res = Self::revoke_all_client_data_cap(rt, cbor::deserialize_params(params)?)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::ScaleAllVerifiers) => {
+                Self::scale_all_verifiers(rt, cbor::deserialize_params(params)?)?;
+                Ok(RawBytes::default())
+            }
+            Some(Method::ImportProposalIds) => {
+                Self::import_proposal_ids(rt, cbor::deserialize_params(params)?)?;
+                Ok(RawBytes::default())
+            }
+            Some(Method::SetClientDataCap) => {
+                let res = Self::set_client_data_cap(rt, cbor::deserialize_params(params)?)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::CanJointlyRemove) => {
+                let res = Self::can_jointly_remove(rt, cbor::deserialize_params(params)?)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::SetRequiredRemovalApprovers) => {
+                Self::set_required_removal_approvers(rt, cbor::deserialize_params(params)?)?;
+                Ok(RawBytes::default())
+            }
+            Some(Method::GetVerifierUtilization) => {
+                let res = Self::get_verifier_utilization(rt, cbor::deserialize_params(params)?)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::GetTotalRemoved) => {
+                let res = Self::get_total_removed(rt)?;
+                Ok(RawBytes::serialize(BigIntSer(&res))?)
+            }
+            Some(Method::GetClientTotalGranted) => {
+                let res = Self::get_client_total_granted(rt, cbor::deserialize_params(params)?)?;
+                Ok(RawBytes::serialize(BigIntSer(&res))?)
+            }
+            Some(Method::SwapVerifierAllowances) => {
+                Self::swap_verifier_allowances(rt, cbor::deserialize_params(params)?)?;
+                Ok(RawBytes::default())
+            }
+            Some(Method::BatchRemoveDataCap) => {
+                let res = Self::batch_remove_data_cap(rt, cbor::deserialize_params(params)?)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::SetVerifierFrozen) => {
+                Self::set_verifier_frozen(rt, cbor::deserialize_params(params)?)?;
+                Ok(RawBytes::default())
+            }
+            Some(Method::PrepareRemoval) => {
+                let res = Self::prepare_removal(rt, cbor::deserialize_params(params)?)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::ImportVerifiers) => {
+                Self::import_verifiers(rt, cbor::deserialize_params(params)?)?;
+                Ok(RawBytes::default())
+            }
+            Some(Method::GetVerifierClientStatus) => {
+                let res = Self::get_verifier_client_status(rt, cbor::deserialize_params(params)?)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::SubmitRemovalApproval) => {
+                let res = Self::submit_removal_approval(rt, cbor::deserialize_params(params)?)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::StepwiseRemoveDataCap) => {
+                let res = Self::stepwise_remove_data_cap(rt, cbor::deserialize_params(params)?)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::GetClientGranters) => {
+                let res = Self::get_client_granters(rt, cbor::deserialize_params(params)?)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::RemoveVerifiedClientDataCapV2) => {
+                let res = Self::remove_verified_client_data_cap_v2(
+                    rt,
+                    cbor::deserialize_params(params)?,
+                )?;
+                Ok(RawBytes::serialize(res)?)
+            }
             None => Err(actor_error!(unhandled_message; "Invalid method")),
         }
     }
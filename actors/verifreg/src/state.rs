@@ -2,34 +2,511 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 use cid::Cid;
-use fil_actors_runtime::make_empty_map;
+use fil_actors_runtime::{
+    actor_error, make_empty_map, make_map_with_root_and_bitwidth, Array, Set,
+};
 use fvm_ipld_blockstore::Blockstore;
 use fvm_ipld_encoding::tuple::*;
-use fvm_ipld_encoding::Cbor;
-use fvm_shared::address::Address;
+use fvm_ipld_encoding::{Cbor, CborStore};
+use fvm_shared::address::{Address, Protocol};
+use fvm_shared::bigint::bigint_ser::{self, BigIntDe};
 use fvm_shared::HAMT_BIT_WIDTH;
 
+use crate::{
+    DataCap, DataCapAccumulator, DataCapExt, PendingRemovalApproval, RemovalLogEntry,
+    RemoveDataCapProposalID, VerifierClientKey, DEFAULT_REQUIRED_REMOVAL_APPROVERS, MAX_DATACAP,
+    REMOVAL_LOG_AMT_BITWIDTH, REMOVAL_LOG_MAX_ENTRIES,
+};
+
+/// Schema version of the verified registry state written by this code.
+/// Migrations should branch on the `version` field of loaded state.
+///
+/// Version 1 held only the root key, the verifier, client and proposal ID maps, and the
+/// version. Version 2 adds every field between the proposal ID map and the version.
+pub const STATE_VERSION: u32 = 2;
+
 #[derive(Serialize_tuple, Deserialize_tuple)]
 pub struct State {
     pub root_key: Address,
     pub verifiers: Cid,
     pub verified_clients: Cid,
     pub remove_data_cap_proposal_ids: Cid,
+    /// Running total of verifier allowance burned by the root key.
+    #[serde(with = "bigint_ser")]
+    pub total_burned_allowance: DataCap,
+    /// Array of the most recent datacap removals, indexed by sequence number.
+    pub removal_log: Cid, // AMT[u64]RemovalLogEntry
+    /// Number of removals ever appended to the log.
+    pub removal_log_count: u64,
+    /// Smallest allowance that may be granted to a verified client.
+    #[serde(with = "bigint_ser")]
+    pub minimum_allocation_size: DataCap,
+    /// Number of distinct verifiers that must sign a datacap removal.
+    pub required_removal_approvers: u64,
+    /// Cumulative datacap each verifier has granted to clients.
+    pub verifier_granted: Cid, // HAMT[Address]DataCap
+    /// Cumulative datacap granted to each client by any verifier.
+    pub client_granted: Cid, // HAMT[Address]DataCap
+    /// Running total of client datacap removed through the removal log.
+    #[serde(with = "bigint_ser")]
+    pub total_removed: DataCap,
+    /// Verifiers barred by the root key from granting or approving removals.
+    pub frozen_verifiers: Cid, // HAMT[Address]()
+    /// Removal approvals awaiting enough other verifiers, at most one per verifier and client.
+    pub pending_removal_approvals: Cid, // HAMT[VerifierClientKey]PendingRemovalApproval
+    /// The distinct verifiers that have granted datacap to each client, in grant order.
+    pub client_granters: Cid, // HAMT[Address]Vec<Address>
+    pub version: u32,
 }
 
 impl State {
-    pub fn new<BS: Blockstore>(store: &BS, root_key: Address) -> anyhow::Result<State> {
+    pub fn new<BS: Blockstore>(
+        store: &BS,
+        root_key: Address,
+        minimum_allocation_size: DataCap,
+    ) -> anyhow::Result<State> {
         let empty_map = make_empty_map::<_, ()>(store, HAMT_BIT_WIDTH)
             .flush()
             .map_err(|e| anyhow::anyhow!("Failed to create empty map: {}", e))?;
+        let empty_removal_log =
+            Array::<(), BS>::new_with_bit_width(store, REMOVAL_LOG_AMT_BITWIDTH)
+                .flush()
+                .map_err(|e| anyhow::anyhow!("Failed to create empty removal log: {}", e))?;
 
         Ok(State {
             root_key,
             verifiers: empty_map,
             verified_clients: empty_map,
             remove_data_cap_proposal_ids: empty_map,
+            total_burned_allowance: DataCap::default(),
+            removal_log: empty_removal_log,
+            removal_log_count: 0,
+            minimum_allocation_size,
+            required_removal_approvers: DEFAULT_REQUIRED_REMOVAL_APPROVERS,
+            verifier_granted: empty_map,
+            client_granted: empty_map,
+            total_removed: DataCap::default(),
+            frozen_verifiers: empty_map,
+            pending_removal_approvals: empty_map,
+            client_granters: empty_map,
+            version: STATE_VERSION,
         })
     }
+
+    /// Loads the state at `root` and checks that its root key is an ID address and that each
+    /// of its collections can be loaded, so corruption is reported before the state is used.
+    pub fn load_validated<BS: Blockstore>(store: &BS, root: &Cid) -> anyhow::Result<State> {
+        let st: State = store
+            .get_cbor(root)
+            .map_err(|e| anyhow::anyhow!("failed to decode verifreg state {}: {}", root, e))?
+            .ok_or_else(|| anyhow::anyhow!("verifreg state {} not found", root))?;
+
+        if st.version != STATE_VERSION {
+            return Err(anyhow::anyhow!(
+                "verifreg state {} has version {}, expected {}",
+                root,
+                st.version,
+                STATE_VERSION
+            ));
+        }
+        if st.root_key.protocol() != Protocol::ID {
+            return Err(anyhow::anyhow!("root key {} is not an ID address", st.root_key));
+        }
+        make_map_with_root_and_bitwidth::<_, BigIntDe>(&st.verifiers, store, HAMT_BIT_WIDTH)
+            .map_err(|e| anyhow::anyhow!("failed to load verifiers {}: {}", st.verifiers, e))?;
+        make_map_with_root_and_bitwidth::<_, BigIntDe>(&st.verified_clients, store, HAMT_BIT_WIDTH)
+            .map_err(|e| {
+                anyhow::anyhow!("failed to load verified clients {}: {}", st.verified_clients, e)
+            })?;
+        make_map_with_root_and_bitwidth::<_, RemoveDataCapProposalID>(
+            &st.remove_data_cap_proposal_ids,
+            store,
+            HAMT_BIT_WIDTH,
+        )
+        .map_err(|e| {
+            anyhow::anyhow!(
+                "failed to load removal proposal ids {}: {}",
+                st.remove_data_cap_proposal_ids,
+                e
+            )
+        })?;
+        make_map_with_root_and_bitwidth::<_, BigIntDe>(&st.verifier_granted, store, HAMT_BIT_WIDTH)
+            .map_err(|e| {
+                anyhow::anyhow!("failed to load verifier grants {}: {}", st.verifier_granted, e)
+            })?;
+        make_map_with_root_and_bitwidth::<_, BigIntDe>(&st.client_granted, store, HAMT_BIT_WIDTH)
+            .map_err(|e| {
+            anyhow::anyhow!("failed to load client grants {}: {}", st.client_granted, e)
+        })?;
+        Set::from_root(store, &st.frozen_verifiers).map_err(|e| {
+            anyhow::anyhow!("failed to load frozen verifiers {}: {}", st.frozen_verifiers, e)
+        })?;
+        make_map_with_root_and_bitwidth::<_, PendingRemovalApproval>(
+            &st.pending_removal_approvals,
+            store,
+            HAMT_BIT_WIDTH,
+        )
+        .map_err(|e| {
+            anyhow::anyhow!(
+                "failed to load pending removal approvals {}: {}",
+                st.pending_removal_approvals,
+                e
+            )
+        })?;
+        make_map_with_root_and_bitwidth::<_, Vec<Address>>(
+            &st.client_granters,
+            store,
+            HAMT_BIT_WIDTH,
+        )
+        .map_err(|e| {
+            anyhow::anyhow!("failed to load client granters {}: {}", st.client_granters, e)
+        })?;
+        Array::<RemovalLogEntry, BS>::load(&st.removal_log, store)
+            .map_err(|e| anyhow::anyhow!("failed to load removal log {}: {}", st.removal_log, e))?;
+        Ok(st)
+    }
+
+    /// Returns the sum of the remaining allowance of every verifier.
+    pub fn total_verifier_allowance<BS: Blockstore>(&self, store: &BS) -> anyhow::Result<DataCap> {
+        let verifiers =
+            make_map_with_root_and_bitwidth::<_, BigIntDe>(&self.verifiers, store, HAMT_BIT_WIDTH)?;
+
+        let mut total = DataCapAccumulator::new();
+        verifiers.for_each(|_, cap| {
+            total.add(&cap.0);
+            Ok(())
+        })?;
+        Ok(total.into_total())
+    }
+
+    /// Returns the cumulative datacap `verifier` has granted to clients.
+    pub fn verifier_granted<BS: Blockstore>(
+        &self,
+        store: &BS,
+        verifier: &Address,
+    ) -> anyhow::Result<DataCap> {
+        let granted = make_map_with_root_and_bitwidth::<_, BigIntDe>(
+            &self.verifier_granted,
+            store,
+            HAMT_BIT_WIDTH,
+        )?;
+        Ok(granted.get(&verifier.to_bytes())?.map(|cap| cap.0.clone()).unwrap_or_default())
+    }
+
+    /// Returns the cumulative datacap granted to `client`.
+    pub fn client_granted<BS: Blockstore>(
+        &self,
+        store: &BS,
+        client: &Address,
+    ) -> anyhow::Result<DataCap> {
+        let granted = make_map_with_root_and_bitwidth::<_, BigIntDe>(
+            &self.client_granted,
+            store,
+            HAMT_BIT_WIDTH,
+        )?;
+        Ok(granted.get(&client.to_bytes())?.map(|cap| cap.0.clone()).unwrap_or_default())
+    }
+
+    /// Returns the distinct verifiers that have granted datacap to `client`, in the order
+    /// of their first grant.
+    pub fn client_granters<BS: Blockstore>(
+        &self,
+        store: &BS,
+        client: &Address,
+    ) -> anyhow::Result<Vec<Address>> {
+        let granters = make_map_with_root_and_bitwidth::<_, Vec<Address>>(
+            &self.client_granters,
+            store,
+            HAMT_BIT_WIDTH,
+        )?;
+        Ok(granters.get(&client.to_bytes())?.cloned().unwrap_or_default())
+    }
+
+    /// Returns whether `verifier` has been frozen by the root key.
+    pub fn is_verifier_frozen<BS: Blockstore>(
+        &self,
+        store: &BS,
+        verifier: &Address,
+    ) -> anyhow::Result<bool> {
+        let frozen = Set::from_root(store, &self.frozen_verifiers)?;
+        Ok(frozen.has(&verifier.to_bytes())?)
+    }
+
+    /// Returns the number of registered verifiers.
+    pub fn verifier_count<BS: Blockstore>(&self, store: &BS) -> anyhow::Result<u64> {
+        let verifiers =
+            make_map_with_root_and_bitwidth::<_, BigIntDe>(&self.verifiers, store, HAMT_BIT_WIDTH)?;
+
+        let mut count = 0;
+        verifiers.for_each(|_, _| {
+            count += 1;
+            Ok(())
+        })?;
+        Ok(count)
+    }
+
+    /// Returns the number of (verifier, client) pairs holding a removal proposal ID.
+    pub fn proposal_id_count<BS: Blockstore>(&self, store: &BS) -> anyhow::Result<u64> {
+        let proposal_ids = make_map_with_root_and_bitwidth::<_, RemoveDataCapProposalID>(
+            &self.remove_data_cap_proposal_ids,
+            store,
+            HAMT_BIT_WIDTH,
+        )?;
+
+        let mut count = 0;
+        proposal_ids.for_each(|_, _| {
+            count += 1;
+            Ok(())
+        })?;
+        Ok(count)
+    }
+
+    /// Returns every verifier and its allowance, sorted by the verifier's address bytes so
+    /// that the output is independent of HAMT iteration order.
+    pub fn export_verifiers<BS: Blockstore>(
+        &self,
+        store: &BS,
+    ) -> anyhow::Result<Vec<(Address, DataCap)>> {
+        let verifiers =
+            make_map_with_root_and_bitwidth::<_, BigIntDe>(&self.verifiers, store, HAMT_BIT_WIDTH)?;
+
+        let mut entries = Vec::new();
+        verifiers.for_each(|key, cap| {
+            entries.push((key.0.clone(), cap.0.clone()));
+            Ok(())
+        })?;
+        entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+        entries.into_iter().map(|(key, cap)| Ok((Address::from_bytes(&key)?, cap))).collect()
+    }
+
+    /// Returns every verifier with at least `threshold` allowance remaining, sorted by the
+    /// verifier's address bytes.
+    pub fn verifiers_with_at_least<BS: Blockstore>(
+        &self,
+        store: &BS,
+        threshold: &DataCap,
+    ) -> anyhow::Result<Vec<(Address, DataCap)>> {
+        Ok(self.export_verifiers(store)?.into_iter().filter(|(_, cap)| cap >= threshold).collect())
+    }
+
+    /// Rebuilds the verifiers map from its entries and returns the new root.
+    ///
+    /// The HAMT is canonical: removals already collapse emptied nodes, so the rebuilt root is
+    /// always equal to the existing one. This only serves as a consistency check of the stored
+    /// map against its entries.
+    pub fn rebuild_verifiers<BS: Blockstore>(&mut self, store: &BS) -> anyhow::Result<Cid> {
+        let verifiers =
+            make_map_with_root_and_bitwidth::<_, BigIntDe>(&self.verifiers, store, HAMT_BIT_WIDTH)?;
+
+        let mut rebuilt = make_empty_map::<_, BigIntDe>(store, HAMT_BIT_WIDTH);
+        verifiers.for_each(|key, cap| {
+            rebuilt.set(key.clone(), BigIntDe(cap.0.clone()))?;
+            Ok(())
+        })?;
+        self.verifiers =
+            rebuilt.flush().map_err(|e| anyhow::anyhow!("failed to flush verifiers: {}", e))?;
+        Ok(self.verifiers)
+    }
+
+    /// Scales every verifier's allowance by `numer / denom`, rounding down, and writes the
+    /// results to a fresh verifiers map. Fails without modifying state if any scaled
+    /// allowance exceeds [`MAX_DATACAP`].
+    pub fn scale_verifiers<BS: Blockstore>(
+        &mut self,
+        store: &BS,
+        numer: u64,
+        denom: u64,
+    ) -> anyhow::Result<()> {
+        let verifiers =
+            make_map_with_root_and_bitwidth::<_, BigIntDe>(&self.verifiers, store, HAMT_BIT_WIDTH)?;
+
+        let mut scaled = make_empty_map::<_, BigIntDe>(store, HAMT_BIT_WIDTH);
+        verifiers.for_each(|key, cap| {
+            let new_cap = cap.0.scale_by_ratio(numer, denom);
+            if new_cap > *MAX_DATACAP {
+                return Err(anyhow::anyhow!(actor_error!(
+                    illegal_argument,
+                    "verifier {} allowance {} scaled by {}/{} exceeds maximum {}",
+                    Address::from_bytes(key)?,
+                    cap.0,
+                    numer,
+                    denom,
+                    *MAX_DATACAP
+                )));
+            }
+            scaled.set(key.clone(), BigIntDe(new_cap))?;
+            Ok(())
+        })?;
+        self.verifiers =
+            scaled.flush().map_err(|e| anyhow::anyhow!("failed to flush verifiers: {}", e))?;
+        Ok(())
+    }
+
+    /// Returns up to `limit` verifiers and their allowances, resuming after the verifier
+    /// whose key is `start_after`. The returned cursor is the key of the last verifier
+    /// in the page if more remain, to be passed as `start_after` for the next page.
+    ///
+    /// Pages are only consistent while the verifiers map is unchanged between calls.
+    /// The walk stops once the page is full, but entries before the cursor are still
+    /// visited to find it. Fails if `start_after` is not the key of a verifier.
+    #[allow(clippy::type_complexity)]
+    pub fn for_each_verifier_limited<BS: Blockstore>(
+        &self,
+        store: &BS,
+        limit: usize,
+        start_after: Option<&[u8]>,
+    ) -> anyhow::Result<(Vec<(Address, DataCap)>, Option<Vec<u8>>)> {
+        anyhow::ensure!(limit > 0, "page limit must be positive");
+        let verifiers =
+            make_map_with_root_and_bitwidth::<_, BigIntDe>(&self.verifiers, store, HAMT_BIT_WIDTH)?;
+
+        let mut started = start_after.is_none();
+        let mut page = Vec::new();
+        let mut cursor = None;
+        let res = verifiers.for_each(|key, cap| {
+            if !started {
+                started = start_after == Some(&key[..]);
+                return Ok(());
+            }
+            if page.len() == limit {
+                cursor = page.last().map(|(addr, _): &(Address, DataCap)| addr.to_bytes());
+                // Stop the walk; the error is discarded below.
+                return Err(anyhow::anyhow!("verifier page full"));
+            }
+            page.push((Address::from_bytes(key)?, cap.0.clone()));
+            Ok(())
+        });
+        if cursor.is_none() {
+            res?;
+        }
+        anyhow::ensure!(started, "page cursor is not a verifier");
+        Ok((page, cursor))
+    }
+
+    /// Appends an entry to the removal log, dropping the oldest entry once the log holds
+    /// `REMOVAL_LOG_MAX_ENTRIES`, and adds its amount to `total_removed`.
+    pub fn append_removal_log<BS: Blockstore>(
+        &mut self,
+        store: &BS,
+        entry: RemovalLogEntry,
+    ) -> anyhow::Result<()> {
+        let mut log = Array::<RemovalLogEntry, BS>::load(&self.removal_log, store)?;
+        let amount = entry.amount.clone();
+        log.set(self.removal_log_count, entry)?;
+        if self.removal_log_count >= REMOVAL_LOG_MAX_ENTRIES {
+            log.delete(self.removal_log_count - REMOVAL_LOG_MAX_ENTRIES)?;
+        }
+        self.removal_log = log.flush()?;
+        self.removal_log_count += 1;
+        self.total_removed += amount;
+        Ok(())
+    }
+
+    /// Returns up to `count` of the most recent removal log entries, oldest first.
+    pub fn recent_removals<BS: Blockstore>(
+        &self,
+        store: &BS,
+        count: u64,
+    ) -> anyhow::Result<Vec<RemovalLogEntry>> {
+        let log = Array::<RemovalLogEntry, BS>::load(&self.removal_log, store)?;
+        let count = count.min(REMOVAL_LOG_MAX_ENTRIES).min(self.removal_log_count);
+        (self.removal_log_count - count..self.removal_log_count)
+            .map(|i| {
+                log.get(i)?
+                    .cloned()
+                    .ok_or_else(|| anyhow::anyhow!("missing removal log entry {}", i))
+            })
+            .collect()
+    }
+
+    /// Returns the removal proposal ID `verifier` must sign over next for `client`.
+    pub fn proposal_id<BS: Blockstore>(
+        &self,
+        store: &BS,
+        verifier: &Address,
+        client: &Address,
+    ) -> anyhow::Result<RemoveDataCapProposalID> {
+        let proposal_ids = make_map_with_root_and_bitwidth::<_, RemoveDataCapProposalID>(
+            &self.remove_data_cap_proposal_ids,
+            store,
+            HAMT_BIT_WIDTH,
+        )?;
+        let key = VerifierClientKey::new(*verifier, *client);
+        Ok(proposal_ids.get(&key.to_bytes())?.cloned().unwrap_or(RemoveDataCapProposalID(0)))
+    }
+
+    /// Returns each verifier holding a removal proposal ID for `client`, along with the
+    /// ID that verifier must sign over next.
+    pub fn client_proposal_ids<BS: Blockstore>(
+        &self,
+        store: &BS,
+        client: &Address,
+    ) -> anyhow::Result<Vec<(Address, u64)>> {
+        let proposal_ids = make_map_with_root_and_bitwidth::<_, RemoveDataCapProposalID>(
+            &self.remove_data_cap_proposal_ids,
+            store,
+            HAMT_BIT_WIDTH,
+        )?;
+
+        let mut ids = Vec::new();
+        proposal_ids.for_each(|key, id| {
+            let key = VerifierClientKey::from_bytes(key)?;
+            if key.client == *client {
+                ids.push((key.verifier, id.0));
+            }
+            Ok(())
+        })?;
+        Ok(ids)
+    }
+
+    /// Returns every (verifier, client) pair in the removal proposal ID map, along with the
+    /// ID that verifier must sign over next for that client, ordered by verifier then client.
+    /// The entries are in the form accepted by [`State::import_proposal_ids`].
+    ///
+    /// A pair appears here once a removal naming it has succeeded, and its ID is the count
+    /// of such removals; the entry is never cleared.
+    pub fn list_proposal_ids<BS: Blockstore>(
+        &self,
+        store: &BS,
+    ) -> anyhow::Result<Vec<(Address, Address, u64)>> {
+        let proposal_ids = make_map_with_root_and_bitwidth::<_, RemoveDataCapProposalID>(
+            &self.remove_data_cap_proposal_ids,
+            store,
+            HAMT_BIT_WIDTH,
+        )?;
+
+        let mut pairs = Vec::new();
+        proposal_ids.for_each(|key, id| {
+            let key = VerifierClientKey::from_bytes(key)?;
+            pairs.push((key.verifier, key.client, id.0));
+            Ok(())
+        })?;
+        pairs.sort_by_key(|(verifier, client, _)| (verifier.to_bytes(), client.to_bytes()));
+        Ok(pairs)
+    }
+
+    /// Writes the given (verifier, client, id) entries to the removal proposal ID map.
+    /// Where a pair already has an ID the higher of the two is kept, so an import can
+    /// never make an already used proposal valid again.
+    pub fn import_proposal_ids<BS: Blockstore>(
+        &mut self,
+        store: &BS,
+        entries: &[(Address, Address, u64)],
+    ) -> anyhow::Result<()> {
+        let mut proposal_ids = make_map_with_root_and_bitwidth::<_, RemoveDataCapProposalID>(
+            &self.remove_data_cap_proposal_ids,
+            store,
+            HAMT_BIT_WIDTH,
+        )?;
+
+        for (verifier, client, id) in entries {
+            let key = VerifierClientKey::new(*verifier, *client).to_bytes();
+            let existing = proposal_ids.get(&key)?.map_or(0, |existing| existing.0);
+            proposal_ids.set(key.into(), RemoveDataCapProposalID((*id).max(existing)))?;
+        }
+        self.remove_data_cap_proposal_ids = proposal_ids.flush()?;
+        Ok(())
+    }
 }
 
 impl Cbor for State {}
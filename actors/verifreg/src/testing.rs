@@ -8,7 +8,29 @@ use fvm_shared::{
 };
 use num_traits::Signed;
 
-use crate::{DataCap, State};
+use crate::{DataCap, DataCapExt, State};
+
+/// Asserts that two [`DataCap`] values are equal, describing both as byte sizes on failure.
+#[macro_export]
+macro_rules! assert_datacap_eq {
+    ($left:expr, $right:expr $(,)?) => {{
+        let (left, right): (&$crate::DataCap, &$crate::DataCap) = (&$left, &$right);
+        if left != right {
+            panic!(
+                "assertion failed: `(left == right)`\n  left: {}\n right: {}\n  diff: {}",
+                $crate::testing::describe_datacap(left),
+                $crate::testing::describe_datacap(right),
+                $crate::testing::describe_datacap(&(right - left)),
+            );
+        }
+    }};
+}
+
+/// Describes `cap` as a byte count with thousands separators and in GiB, e.g.
+/// `"1,048,576 bytes (0.001 GiB)"`.
+pub fn describe_datacap(cap: &DataCap) -> String {
+    format!("{} bytes ({:.3} GiB)", cap.to_grouped_string(), cap.as_gib_f64())
+}
 
 pub struct StateSummary {
     pub verifiers: HashMap<Address, DataCap>,
@@ -69,6 +91,9 @@ pub fn check_state_invariants<BS: Blockstore>(
         Err(e) => acc.add(format!("error loading clients {e}")),
     }
 
+    // check the datacap held across all clients is plausible
+    acc.require_no_error(DataCap::try_sum(all_clients.values()), "invalid client datacap total");
+
     // check verifiers and clients are disjoint
     // No need to iterate all clients; any overlap must have been one of all verifiers.
     all_verifiers.keys().filter(|verifier| all_clients.contains_key(verifier)).for_each(
@@ -1,19 +1,24 @@
 // Copyright 2019-2022 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+use anyhow::anyhow;
 use fvm_ipld_encoding::tuple::*;
 use fvm_ipld_encoding::Cbor;
-use fvm_shared::address::Address;
-use fvm_shared::bigint::bigint_ser;
+use fvm_shared::address::{Address, Protocol, BLS_PUB_LEN, PAYLOAD_HASH_LEN};
+use fvm_shared::bigint::bigint_ser::{self, BigIntDe, BigIntSer};
+use fvm_shared::clock::ChainEpoch;
 use fvm_shared::crypto::signature::Signature;
 use fvm_shared::sector::StoragePower;
-use serde::{Deserialize, Serialize};
+use lazy_static::lazy_static;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::datacap::cbor_header_size;
+use crate::{DataCapDiff, DataCapExt, NonNegDataCap};
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
 pub struct VerifierParams {
     pub address: Address,
-    #[serde(with = "bigint_ser")]
-    pub allowance: DataCap,
+    pub allowance: NonNegDataCap,
 }
 
 impl Cbor for VerifierParams {}
@@ -22,10 +27,132 @@ pub type AddVerifierParams = VerifierParams;
 
 pub type AddVerifierClientParams = VerifierParams;
 
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct AddVerifiedClientForParams {
+    /// Verifier whose allowance the datacap is drawn from.
+    pub verifier: Address,
+    pub params: AddVerifierClientParams,
+}
+
+impl Cbor for AddVerifiedClientForParams {}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct AddVerifiedClientsParams {
+    pub clients: Vec<AddVerifierClientParams>,
+}
+
+impl Cbor for AddVerifiedClientsParams {}
+
+/// Verifiers and their allowances to add in one call, for genesis or migration.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct ImportVerifiersParams {
+    pub verifiers: Vec<AddVerifierParams>,
+}
+
+impl Cbor for ImportVerifiersParams {}
+
+/// Removal proposal ID entries to restore, as (verifier, client, id) with ID addresses.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct ImportProposalIdsParams {
+    pub proposal_ids: Vec<(Address, Address, u64)>,
+}
+
+impl Cbor for ImportProposalIdsParams {}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct CanJointlyRemoveParams {
+    pub verifier1: Address,
+    pub verifier2: Address,
+}
+
+impl Cbor for CanJointlyRemoveParams {}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct PrepareRemovalParams {
+    pub verifier1: Address,
+    pub verifier2: Address,
+    pub client: Address,
+    pub amount: NonNegDataCap,
+}
+
+impl Cbor for PrepareRemovalParams {}
+
+/// The proposal IDs two verifiers must sign over to remove datacap from a client.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct PrepareRemovalReturn {
+    pub verifier1_proposal_id: RemoveDataCapProposalID,
+    pub verifier2_proposal_id: RemoveDataCapProposalID,
+    /// Whether the removal would take all of the client's datacap, deleting the client.
+    pub deletes_client: bool,
+}
+
+impl Cbor for PrepareRemovalReturn {}
+
+/// Largest encoded `AddVerifiedClientsParams` the actor will process, in bytes.
+pub const MAX_ADD_VERIFIED_CLIENTS_PARAMS_SIZE: usize = 64 << 10;
+
+impl AddVerifiedClientsParams {
+    /// Returns the length in bytes of the CBOR encoding of these params, without
+    /// encoding them.
+    pub fn serialized_size(&self) -> usize {
+        let clients: usize = self
+            .clients
+            .iter()
+            .map(|client| {
+                let address = client.address.to_bytes().len();
+                1 + cbor_header_size(address) + address + client.allowance.serialized_size()
+            })
+            .sum();
+        1 + cbor_header_size(self.clients.len()) + clients
+    }
+}
+
+/// Scales every verifier's allowance by `numer / denom`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct ScaleAllVerifiersParams {
+    pub numer: u64,
+    pub denom: u64,
+}
+
+impl Cbor for ScaleAllVerifiersParams {}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct TransferAllowanceParams {
+    pub from: Address,
+    pub to: Address,
+    pub amount: NonNegDataCap,
+}
+
+impl Cbor for TransferAllowanceParams {}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct SwapVerifierAllowancesParams {
+    pub a: Address,
+    pub b: Address,
+}
+
+impl Cbor for SwapVerifierAllowancesParams {}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct SetVerifierFrozenParams {
+    pub verifier: Address,
+    pub frozen: bool,
+}
+
+impl Cbor for SetVerifierFrozenParams {}
+
+/// The `allowance` field holds the amount to subtract from the verifier's cap.
+pub type DecreaseVerifierAllowanceParams = VerifierParams;
+
 /// DataCap is an integer number of bytes.
 /// We can introduce policy changes and replace this in the future.
 pub type DataCap = StoragePower;
 
+lazy_static! {
+    /// The largest datacap balance a single verified client may hold (1 ZiB).
+    pub static ref MAX_DATACAP: DataCap = DataCap::from(1) << 70;
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize_tuple, Deserialize_tuple)]
 pub struct BytesParams {
     /// Address of verified client.
@@ -40,8 +167,25 @@ pub type RestoreBytesParams = BytesParams;
 
 pub const SIGNATURE_DOMAIN_SEPARATION_REMOVE_DATA_CAP: &[u8] = b"fil_removedatacap:";
 
+/// Number of distinct verifiers that must sign a datacap removal in new state.
+pub const DEFAULT_REQUIRED_REMOVAL_APPROVERS: u64 = 2;
+
+/// Format version of a [`RemoveDataCapProposal`] serialized as its CBOR tuple encoding.
+pub const REMOVE_DATA_CAP_PROPOSAL_FORMAT_V1: u64 = 1;
+
+/// Format version of a [`RemoveDataCapProposalV2`], which also binds the client's balance.
+pub const REMOVE_DATA_CAP_PROPOSAL_FORMAT_V2: u64 = 2;
+
+/// Format version of a [`RemoveDataCapProposalV3`], which also binds the removal reason.
+pub const REMOVE_DATA_CAP_PROPOSAL_FORMAT_V3: u64 = 3;
+
+/// Maximum length in bytes of the reason given for a datacap removal.
+pub const MAX_REMOVAL_REASON_LEN: usize = 256;
+
 impl Cbor for RemoveDataCapParams {}
 
+/// Parameters of `RemoveVerifiedClientDataCap`, in their original encoding: a format V1
+/// removal signed by two verifiers. [`RemoveDataCapParamsV2`] carries the later options.
 #[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
 pub struct RemoveDataCapParams {
     pub verified_client_to_remove: Address,
@@ -51,12 +195,87 @@ pub struct RemoveDataCapParams {
     pub verifier_request_2: RemoveDataCapRequest,
 }
 
+impl From<RemoveDataCapParams> for RemoveDataCapParamsV2 {
+    fn from(params: RemoveDataCapParams) -> Self {
+        RemoveDataCapParamsV2 {
+            verified_client_to_remove: params.verified_client_to_remove,
+            data_cap_amount_to_remove: params.data_cap_amount_to_remove,
+            verifier_request_1: params.verifier_request_1,
+            verifier_request_2: Some(params.verifier_request_2),
+            format_version: REMOVE_DATA_CAP_PROPOSAL_FORMAT_V1,
+            expected_client_data_cap: DataCap::default(),
+            additional_verifier_requests: vec![],
+            reason: None,
+        }
+    }
+}
+
+impl Cbor for RemoveDataCapParamsV2 {}
+
+/// Parameters of `RemoveVerifiedClientDataCapV2`, a removal of any proposal format signed
+/// by as many verifiers as the registry requires.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct RemoveDataCapParamsV2 {
+    pub verified_client_to_remove: Address,
+    #[serde(with = "bigint_ser")]
+    pub data_cap_amount_to_remove: DataCap,
+    pub verifier_request_1: RemoveDataCapRequest,
+    /// Absent only when the registry requires a single approver.
+    pub verifier_request_2: Option<RemoveDataCapRequest>,
+    /// Selects how the proposal signed by each verifier is serialized.
+    pub format_version: u64,
+    /// The client's datacap when the verifiers signed. From format V2 the removal aborts
+    /// if the client's live datacap differs; earlier formats ignore it.
+    #[serde(with = "bigint_ser")]
+    pub expected_client_data_cap: DataCap,
+    /// Requests from further verifiers, when the registry requires more than two.
+    pub additional_verifier_requests: Vec<RemoveDataCapRequest>,
+    /// Why the datacap is being removed, recorded in the removal log. Only accepted from
+    /// format V3, whose proposal binds it to the verifiers' signatures.
+    pub reason: Option<String>,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
 pub struct RemoveDataCapRequest {
     pub verifier: Address,
     pub signature: Signature,
 }
 
+pub const SIGNATURE_DOMAIN_SEPARATION_REVOKE_ALL_DATA_CAP: &[u8] = b"fil_revokealldatacap:";
+
+impl Cbor for RevokeAllDataCapParams {}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct RevokeAllDataCapParams {
+    pub verified_client: Address,
+    pub verifier_request_1: RemoveDataCapRequest,
+    pub verifier_request_2: RemoveDataCapRequest,
+    /// Requests from further verifiers, when the registry requires more than two.
+    pub additional_verifier_requests: Vec<RemoveDataCapRequest>,
+}
+
+pub const SIGNATURE_DOMAIN_SEPARATION_SET_CLIENT_DATA_CAP: &[u8] = b"fil_setclientdatacap:";
+
+impl Cbor for SetClientDataCapParams {}
+
+/// Sets a verified client's datacap to `target`, raising or lowering it as needed.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct SetClientDataCapParams {
+    pub verified_client: Address,
+    pub target: NonNegDataCap,
+    pub verifier_request_1: RemoveDataCapRequest,
+    pub verifier_request_2: RemoveDataCapRequest,
+    /// Requests from further verifiers, when the registry requires more than two.
+    pub additional_verifier_requests: Vec<RemoveDataCapRequest>,
+}
+
+impl Cbor for BatchRemoveDataCapParams {}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct BatchRemoveDataCapParams {
+    pub removals: Vec<RemoveDataCapParamsV2>,
+}
+
 impl Cbor for RemoveDataCapReturn {}
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
@@ -64,12 +283,183 @@ pub struct RemoveDataCapReturn {
     pub verified_client: Address,
     #[serde(with = "bigint_ser")]
     pub data_cap_removed: DataCap,
+    /// Change in the client's datacap caused by the removal.
+    pub data_cap_change: DataCapDiff,
 }
 
+impl Cbor for BatchRemoveDataCapReturn {}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct BatchRemoveDataCapReturn {
+    /// The result of each removal, in the order given.
+    pub results: Vec<RemoveDataCapReturn>,
+}
+
+/// Removals from a single client, applied in order within one message.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct StepwiseRemoveDataCapParams {
+    /// Each step is signed over the proposal IDs left by the step before it.
+    pub steps: Vec<RemoveDataCapParamsV2>,
+}
+
+impl Cbor for StepwiseRemoveDataCapParams {}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct StepwiseRemoveDataCapReturn {
+    /// The result of each step, in order.
+    pub results: Vec<RemoveDataCapReturn>,
+}
+
+impl Cbor for StepwiseRemoveDataCapReturn {}
+
 #[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
 #[serde(transparent)]
 pub struct RemoveDataCapProposalID(pub u64);
 
+/// One verifier's approval of a datacap removal, submitted separately from the other's.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct SubmitRemovalApprovalParams {
+    pub verified_client_to_remove: Address,
+    pub data_cap_amount_to_remove: NonNegDataCap,
+    /// Signed over a format V1 proposal.
+    pub verifier_request: RemoveDataCapRequest,
+}
+
+impl Cbor for SubmitRemovalApprovalParams {}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct SubmitRemovalApprovalReturn {
+    /// The executed removal, or `None` if the approval awaits a second verifier.
+    pub removal: Option<RemoveDataCapReturn>,
+}
+
+impl Cbor for SubmitRemovalApprovalReturn {}
+
+/// A verifier's approval of a datacap removal, held until enough other verifiers approve.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct PendingRemovalApproval {
+    pub verifier: Address,
+    /// The verifier's removal proposal ID for the client when it signed.
+    pub proposal_id: RemoveDataCapProposalID,
+    #[serde(with = "bigint_ser")]
+    pub amount: DataCap,
+    pub signature: Signature,
+}
+
+impl Cbor for PendingRemovalApproval {}
+
+impl Cbor for GetClientProposalIdsReturn {}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct GetClientProposalIdsReturn {
+    /// Each verifier with a proposal ID for the client, and the ID it must sign next.
+    pub proposal_ids: Vec<(Address, u64)>,
+}
+
+pub const REMOVAL_LOG_AMT_BITWIDTH: u32 = 5;
+
+/// Maximum number of entries retained in the removal audit log. Older entries are dropped
+/// as new ones are appended.
+pub const REMOVAL_LOG_MAX_ENTRIES: u64 = 1024;
+
+/// Audit record of a successful datacap removal.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct RemovalLogEntry {
+    pub client: Address,
+    #[serde(with = "bigint_ser")]
+    pub amount: DataCap,
+    /// Every verifier that approved the removal, in request order.
+    pub approvers: Vec<Address>,
+    pub epoch: ChainEpoch,
+    /// Reason given with the removal, if any.
+    pub reason: Option<String>,
+}
+
+impl Cbor for VerifierUtilization {}
+
+/// A verifier's remaining allowance and the datacap it has granted so far.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct VerifierUtilization {
+    #[serde(with = "bigint_ser")]
+    pub remaining: DataCap,
+    #[serde(with = "bigint_ser")]
+    pub granted: DataCap,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct GetVerifierClientStatusParams {
+    pub verifier: Address,
+    pub client: Address,
+}
+
+impl Cbor for GetVerifierClientStatusParams {}
+
+/// A verifier's remaining allowance along with its removal proposal ID for a client and
+/// that client's datacap.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct VerifierClientStatus {
+    #[serde(with = "bigint_ser")]
+    pub verifier_cap: DataCap,
+    /// The removal proposal ID the verifier must sign over next for the client.
+    pub proposal_id: u64,
+    /// The client's datacap, zero if it is not a verified client.
+    #[serde(with = "bigint_ser")]
+    pub client_balance: DataCap,
+}
+
+impl Cbor for VerifierClientStatus {}
+
+impl Cbor for GetRecentRemovalsReturn {}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct GetRecentRemovalsReturn {
+    /// Most recent removals, oldest first.
+    pub entries: Vec<RemovalLogEntry>,
+}
+
+/// The role an address holds in the registry, with its remaining allowance or datacap.
+///
+/// Encoded as a `(kind, datacap)` tuple where kind is 0 for none, 1 for a verifier and
+/// 2 for a verified client. The datacap is zero for an address with no role.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AddressRole {
+    Verifier(DataCap),
+    Client(DataCap),
+    None,
+}
+
+impl Cbor for AddressRole {}
+
+impl Serialize for AddressRole {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let zero = DataCap::default();
+        let (kind, cap): (u8, &DataCap) = match self {
+            AddressRole::None => (0, &zero),
+            AddressRole::Verifier(cap) => (1, cap),
+            AddressRole::Client(cap) => (2, cap),
+        };
+        (kind, BigIntSer(cap)).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for AddressRole {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let (kind, BigIntDe(cap)) = <(u8, BigIntDe)>::deserialize(deserializer)?;
+        match kind {
+            0 => Ok(AddressRole::None),
+            1 => Ok(AddressRole::Verifier(cap)),
+            2 => Ok(AddressRole::Client(cap)),
+            kind => Err(de::Error::custom(format!("invalid address role {}", kind))),
+        }
+    }
+}
+
 #[derive(Debug, Serialize_tuple, Deserialize_tuple)]
 pub struct RemoveDataCapProposal {
     pub verified_client: Address,
@@ -78,6 +468,64 @@ pub struct RemoveDataCapProposal {
     pub removal_proposal_id: RemoveDataCapProposalID,
 }
 
+/// A [`RemoveDataCapProposal`] that is only valid while the client holds exactly
+/// `client_data_cap`.
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct RemoveDataCapProposalV2 {
+    pub verified_client: Address,
+    #[serde(with = "bigint_ser")]
+    pub data_cap_amount: DataCap,
+    pub removal_proposal_id: RemoveDataCapProposalID,
+    #[serde(with = "bigint_ser")]
+    pub client_data_cap: DataCap,
+}
+
+/// A [`RemoveDataCapProposalV2`] that also carries the reason for the removal.
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct RemoveDataCapProposalV3 {
+    pub verified_client: Address,
+    #[serde(with = "bigint_ser")]
+    pub data_cap_amount: DataCap,
+    pub removal_proposal_id: RemoveDataCapProposalID,
+    #[serde(with = "bigint_ser")]
+    pub client_data_cap: DataCap,
+    pub reason: Option<String>,
+}
+
+/// A datacap removal proposal format that a verifier may sign, i.e. one of
+/// [`RemoveDataCapProposal`], [`RemoveDataCapProposalV2`] and [`RemoveDataCapProposalV3`].
+/// Sealed so that signatures under the removal domain are only checked over these payloads.
+pub trait RemoveDataCapProposalFormat: Serialize + private::Sealed {}
+
+impl RemoveDataCapProposalFormat for RemoveDataCapProposal {}
+impl RemoveDataCapProposalFormat for RemoveDataCapProposalV2 {}
+impl RemoveDataCapProposalFormat for RemoveDataCapProposalV3 {}
+
+mod private {
+    pub trait Sealed {}
+
+    impl Sealed for super::RemoveDataCapProposal {}
+    impl Sealed for super::RemoveDataCapProposalV2 {}
+    impl Sealed for super::RemoveDataCapProposalV3 {}
+}
+
+/// Signed by a verifier to revoke all of a client's datacap, whatever its balance when the
+/// revocation lands.
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct RevokeAllDataCapProposal {
+    pub verified_client: Address,
+    pub removal_proposal_id: RemoveDataCapProposalID,
+}
+
+/// Signed by a verifier to set a client's datacap to `target`.
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct SetClientDataCapProposal {
+    pub verified_client: Address,
+    #[serde(with = "bigint_ser")]
+    pub target: DataCap,
+    pub removal_proposal_id: RemoveDataCapProposalID,
+}
+
 pub struct AddrPairKey {
     pub first: Address,
     pub second: Address,
@@ -94,4 +542,57 @@ impl AddrPairKey {
         first.append(&mut second);
         first
     }
+
+    /// Decodes a key produced by `to_bytes`.
+    pub fn from_bytes(bz: &[u8]) -> anyhow::Result<Self> {
+        let split = encoded_address_len(bz)?;
+        if bz.len() < split {
+            return Err(anyhow!("address pair key {:?} too short", bz));
+        }
+        let first = Address::from_bytes(&bz[..split])?;
+        let second = Address::from_bytes(&bz[split..])?;
+        Ok(AddrPairKey { first, second })
+    }
+}
+
+/// Key of the removal proposal ID map: the verifier signing removal proposals and the client
+/// whose datacap they remove. Encodes as `AddrPairKey::new(verifier, client)`, so the roles
+/// cannot be swapped by accident.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VerifierClientKey {
+    pub verifier: Address,
+    pub client: Address,
+}
+
+impl VerifierClientKey {
+    pub fn new(verifier: Address, client: Address) -> Self {
+        VerifierClientKey { verifier, client }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        AddrPairKey::new(self.verifier, self.client).to_bytes()
+    }
+
+    /// Decodes a key produced by `to_bytes`.
+    pub fn from_bytes(bz: &[u8]) -> anyhow::Result<Self> {
+        let pair = AddrPairKey::from_bytes(bz)?;
+        Ok(VerifierClientKey { verifier: pair.first, client: pair.second })
+    }
+}
+
+/// Returns the length of the address encoded at the start of `bz`.
+fn encoded_address_len(bz: &[u8]) -> anyhow::Result<usize> {
+    let protocol = *bz.first().ok_or_else(|| anyhow!("empty address"))?;
+    let payload_len = if protocol == Protocol::ID as u8 {
+        // ID payloads are a varint, ending at the first byte without the continuation bit.
+        let end = bz[1..].iter().position(|b| b & 0x80 == 0);
+        end.ok_or_else(|| anyhow!("unterminated ID address payload"))? + 1
+    } else if protocol == Protocol::Secp256k1 as u8 || protocol == Protocol::Actor as u8 {
+        PAYLOAD_HASH_LEN
+    } else if protocol == Protocol::BLS as u8 {
+        BLS_PUB_LEN
+    } else {
+        return Err(anyhow!("unknown address protocol {}", protocol));
+    };
+    Ok(1 + payload_len)
 }
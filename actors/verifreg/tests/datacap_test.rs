@@ -0,0 +1,473 @@
+use std::convert::TryFrom;
+
+use fil_actor_verifreg::testing::describe_datacap;
+use fil_actor_verifreg::{
+    assert_datacap_eq, DataCap, DataCapAccumulator, DataCapDiff, DataCapDirection, DataCapExt,
+    NegativeDataCapError, NonNegDataCap, MAX_DATACAP,
+};
+use fvm_ipld_encoding::{from_slice, to_vec};
+use fvm_shared::bigint::bigint_ser::{BigIntDe, BigIntSer};
+use fvm_shared::bigint::BigInt;
+use fvm_shared::error::ExitCode;
+use fvm_shared::piece::PaddedPieceSize;
+use fvm_shared::sector::StoragePower;
+use num_traits::{Signed, ToPrimitive, Zero};
+
+#[test]
+fn multiply_by_scalar() {
+    assert_eq!(DataCap::from(15), DataCap::from(5) * 3u64);
+    assert_eq!(DataCap::from(15), &DataCap::from(5) * 3u64);
+    assert_eq!(DataCap::from(0), DataCap::from(5) * 0u64);
+    assert_eq!(DataCap::from(2 * 1048576), DataCap::from(1048576) * 2u64);
+}
+
+#[test]
+fn approx_eq_within_tolerance() {
+    let tolerance = DataCap::from(3);
+    let a = DataCap::from(1048576);
+    assert!(a.approx_eq(&a, &DataCap::from(0)));
+    assert!(a.approx_eq(&(a.clone() + 3), &tolerance));
+    assert!(a.approx_eq(&(a.clone() - 3), &tolerance));
+    assert!((a.clone() - 2).approx_eq(&a, &tolerance));
+}
+
+#[test]
+fn approx_eq_outside_tolerance() {
+    let tolerance = DataCap::from(3);
+    let a = DataCap::from(1048576);
+    assert!(!a.approx_eq(&(a.clone() + 4), &tolerance));
+    assert!(!a.approx_eq(&(a.clone() - 4), &tolerance));
+    assert!(!(a.clone() + 1).approx_eq(&a, &DataCap::from(0)));
+}
+
+#[test]
+fn serialized_size_small_values() {
+    for v in [0i64, 1, -1, 255, 256, -256, 1048576] {
+        let cap = DataCap::from(v);
+        assert_eq!(to_vec(&BigIntSer(&cap)).unwrap().len(), cap.serialized_size());
+    }
+    assert_eq!(1, DataCap::from(0).serialized_size());
+    assert_eq!(3, DataCap::from(255).serialized_size());
+}
+
+#[test]
+fn serialized_size_large_values() {
+    // Magnitudes of 22, 23 and 100 bytes straddle the one-byte CBOR header boundary.
+    for bytes in [22u32, 23, 100] {
+        let cap = DataCap::from(1) << (bytes * 8 - 1);
+        assert_eq!(to_vec(&BigIntSer(&cap)).unwrap().len(), cap.serialized_size());
+        assert_eq!(to_vec(&BigIntSer(&-cap.clone())).unwrap().len(), (-cap).serialized_size());
+    }
+    assert_eq!(24, (DataCap::from(1) << (22 * 8 - 1)).serialized_size());
+    assert_eq!(26, (DataCap::from(1) << (23 * 8 - 1)).serialized_size());
+}
+
+#[test]
+fn zero_checks_match_comparison_with_zero() {
+    for v in [-1048576i64, -1, 0, 1, 1048576] {
+        let cap = DataCap::from(v);
+        assert_eq!(cap == DataCap::zero(), cap.is_zero());
+        assert_eq!(cap <= DataCap::zero(), !cap.is_positive());
+        assert_eq!(cap < DataCap::zero(), cap.is_negative());
+    }
+}
+
+#[test]
+fn non_neg_datacap_accepts_non_negative() {
+    for v in [0i64, 1, 1048576] {
+        let cap = NonNegDataCap::try_from(DataCap::from(v)).unwrap();
+        assert_eq!(DataCap::from(v), *cap);
+        // Encodes identically to a bare DataCap.
+        let encoded = to_vec(&cap).unwrap();
+        assert_eq!(to_vec(&BigIntSer(&DataCap::from(v))).unwrap(), encoded);
+        assert_eq!(cap, from_slice::<NonNegDataCap>(&encoded).unwrap());
+    }
+}
+
+#[test]
+fn non_neg_datacap_rejects_negative() {
+    let negative = DataCap::from(-1);
+    assert_eq!(
+        Err(NegativeDataCapError(negative.clone())),
+        NonNegDataCap::try_from(negative.clone())
+    );
+    assert!(from_slice::<NonNegDataCap>(&to_vec(&BigIntSer(&negative)).unwrap()).is_err());
+}
+
+#[test]
+fn into_power_matches_as_power() {
+    let cap = DataCap::from(1048576) * 3u64;
+    let borrowed: StoragePower = cap.as_power().clone();
+    let owned: StoragePower = cap.into_power();
+    assert_eq!(borrowed, owned);
+    assert_eq!(StoragePower::from(3 * 1048576), owned);
+}
+
+#[test]
+fn into_bigint_preserves_value() {
+    for cap in [DataCap::zero(), DataCap::from(-1), DataCap::from(1048576), MAX_DATACAP.clone()] {
+        let expected: BigInt = cap.as_power().clone();
+        assert_eq!(expected, cap.into_bigint());
+    }
+}
+
+#[test]
+fn to_deal_space_matches_as_power() {
+    for cap in [DataCap::zero(), DataCap::from(1048576), DataCap::pow2(40), MAX_DATACAP.clone()] {
+        assert_eq!(cap.as_power().clone(), cap.to_deal_space());
+    }
+}
+
+#[test]
+fn pow2() {
+    assert_eq!(DataCap::from(1), DataCap::pow2(0));
+    assert_eq!(DataCap::from(1024), DataCap::pow2(10));
+    assert_eq!(DataCap::from(1u64 << 40), DataCap::pow2(40));
+    for exp in [1u32, 63, 64, 100] {
+        assert_eq!(BigInt::from(1) << exp, DataCap::pow2(exp));
+    }
+}
+
+#[test]
+fn diff_increase() {
+    let diff = DataCap::from(10).diff(&DataCap::from(25));
+    assert_eq!(
+        DataCapDiff { delta: DataCap::from(15), direction: DataCapDirection::Increase },
+        diff
+    );
+}
+
+#[test]
+fn diff_decrease() {
+    let diff = DataCap::from(25).diff(&DataCap::from(10));
+    assert_eq!(
+        DataCapDiff { delta: DataCap::from(15), direction: DataCapDirection::Decrease },
+        diff
+    );
+    let decoded: DataCapDiff = from_slice(&to_vec(&diff).unwrap()).unwrap();
+    assert_eq!(diff, decoded);
+}
+
+#[test]
+fn diff_no_change() {
+    let diff = DataCap::from(25).diff(&DataCap::from(25));
+    assert_eq!(
+        DataCapDiff { delta: DataCap::zero(), direction: DataCapDirection::Unchanged },
+        diff
+    );
+    assert_eq!(DataCapDiff::default(), diff);
+}
+
+#[test]
+fn to_i128_in_range() {
+    let cap = DataCap::from(10_000e18 as i128);
+    assert_eq!(Some(10_000e18 as i128), cap.to_i128());
+    assert_eq!(Some(0), DataCap::from(0).to_i128());
+    assert_eq!(Some(i128::MAX), DataCap::from(i128::MAX).to_i128());
+}
+
+#[test]
+fn to_i128_overflow() {
+    assert_eq!(None, (DataCap::from(i128::MAX) + 1).to_i128());
+    assert_eq!(None, (DataCap::from(i128::MIN) - 1).to_i128());
+    assert_eq!(None, DataCap::pow2(200).to_i128());
+}
+
+#[test]
+fn to_i128_negative() {
+    assert_eq!(Some(-42), DataCap::from(-42i128).to_i128());
+    assert_eq!(Some(i128::MIN), DataCap::from(i128::MIN).to_i128());
+}
+
+#[test]
+fn clamp_nonneg() {
+    assert_eq!(DataCap::zero(), DataCap::from(-5).clamp_nonneg());
+    assert_eq!(DataCap::zero(), DataCap::zero().clamp_nonneg());
+    assert_eq!(DataCap::from(5), DataCap::from(5).clamp_nonneg());
+}
+
+#[test]
+fn is_aligned() {
+    let sector = DataCap::pow2(35);
+    assert!(DataCap::zero().is_aligned(&sector));
+    assert!(sector.is_aligned(&sector));
+    assert!((sector.clone() * 3u64).is_aligned(&sector));
+    assert!(DataCap::from(1 << 20).is_aligned(&DataCap::from(1 << 10)));
+}
+
+#[test]
+fn is_misaligned() {
+    let sector = DataCap::pow2(35);
+    assert!(!(sector.clone() + 1).is_aligned(&sector));
+    assert!(!DataCap::from(1 << 10).is_aligned(&sector));
+    assert!(!DataCap::from(6).is_aligned(&DataCap::from(4)));
+    assert!(!sector.is_aligned(&DataCap::zero()));
+}
+
+#[test]
+fn ct_eq_agrees_with_eq() {
+    let values = [
+        DataCap::zero(),
+        DataCap::from(1),
+        DataCap::from(-1),
+        DataCap::from(256),
+        DataCap::from(1 << 20),
+        DataCap::pow2(70),
+        -DataCap::pow2(70),
+        DataCap::pow2(70) + 1,
+    ];
+    for a in &values {
+        for b in &values {
+            assert_eq!(a == b, a.ct_eq(b), "{} vs {}", a, b);
+        }
+    }
+}
+
+#[test]
+fn accumulator_sums_amounts() {
+    let amounts = [DataCap::from(1 << 20), DataCap::from(42), DataCap::pow2(40)];
+    let mut acc = DataCapAccumulator::new();
+    assert!(acc.total().is_zero());
+    for amount in &amounts {
+        acc.add(amount);
+    }
+    assert_eq!(3, acc.count());
+    assert_eq!(amounts.iter().sum::<DataCap>(), *acc.total());
+    assert_eq!(DataCap::pow2(40) + (1 << 20) + 42, acc.into_total());
+}
+
+#[test]
+fn parse_with_units() {
+    assert_eq!(DataCap::from(512), DataCap::parse_with_units("512").unwrap());
+    assert_eq!(DataCap::from(2 << 10), DataCap::parse_with_units("2KiB").unwrap());
+    assert_eq!(DataCap::from(3 << 20), DataCap::parse_with_units("3MiB").unwrap());
+    assert_eq!(DataCap::pow2(35), DataCap::parse_with_units("32GiB").unwrap());
+    assert_eq!(DataCap::pow2(40), DataCap::parse_with_units("1TiB").unwrap());
+    assert_eq!(DataCap::pow2(50) * 5u64, DataCap::parse_with_units("5PiB").unwrap());
+    assert_eq!(DataCap::zero(), DataCap::parse_with_units("0GiB").unwrap());
+}
+
+#[test]
+fn parse_with_units_rejects_malformed() {
+    for input in ["", "GiB", "32gib", "32GB", "32 GiB", "-1KiB", "1.5GiB", "32GiBs", "32EiB"] {
+        assert!(DataCap::parse_with_units(input).is_err(), "{:?}", input);
+    }
+}
+
+#[test]
+fn as_gib_f64() {
+    assert_eq!(0.0, DataCap::zero().as_gib_f64());
+    assert_eq!(1.0, DataCap::pow2(30).as_gib_f64());
+    assert_eq!(0.5, DataCap::pow2(29).as_gib_f64());
+    assert_eq!(32.0, DataCap::parse_with_units("32GiB").unwrap().as_gib_f64());
+    assert_eq!(1024.0 * 1024.0, DataCap::pow2(50).as_gib_f64());
+    assert_eq!(-2.0, (-DataCap::pow2(31)).as_gib_f64());
+
+    // Large values are approximate.
+    let huge = DataCap::pow2(100) + 1;
+    let expected = 2f64.powi(70);
+    assert!((huge.as_gib_f64() - expected).abs() / expected < 1e-12);
+}
+
+#[test]
+fn checked_add_capped() {
+    let max = MAX_DATACAP.clone();
+    assert_eq!(Some(DataCap::from(5)), DataCap::from(2).checked_add_capped(&DataCap::from(3)));
+    assert_eq!(Some(max.clone()), (max.clone() - 1).checked_add_capped(&DataCap::from(1)));
+    assert_eq!(Some(max.clone()), max.checked_add_capped(&DataCap::zero()));
+    assert_eq!(None, max.checked_add_capped(&DataCap::from(1)));
+    assert_eq!(None, DataCap::from(1).checked_add_capped(&max));
+}
+
+#[test]
+fn varint_bytes_round_trip() {
+    assert_eq!(vec![0x00], DataCap::zero().to_varint_bytes().unwrap());
+    assert_eq!(vec![0x7f], DataCap::from(127).to_varint_bytes().unwrap());
+    assert_eq!(vec![0x80, 0x01], DataCap::from(128).to_varint_bytes().unwrap());
+    assert_eq!(vec![0xe5, 0x8e, 0x26], DataCap::from(624485).to_varint_bytes().unwrap());
+
+    let values = [
+        DataCap::zero(),
+        DataCap::from(1),
+        DataCap::from(300),
+        DataCap::pow2(35),
+        DataCap::pow2(63) - 1,
+        DataCap::pow2(64),
+        MAX_DATACAP.clone(),
+        DataCap::pow2(200) + 12345,
+    ];
+    for value in &values {
+        let bytes = value.to_varint_bytes().unwrap();
+        assert_eq!(*value, DataCap::from_varint_bytes(&bytes).unwrap(), "{:?}", bytes);
+    }
+}
+
+#[test]
+fn varint_bytes_rejects_invalid() {
+    assert!(DataCap::from(-1).to_varint_bytes().is_err());
+    for bytes in
+        [&[][..], &[0x80], &[0xff, 0xff], &[0x01, 0x02], &[0x80, 0x00], &[0x81, 0x80, 0x00]]
+    {
+        assert!(DataCap::from_varint_bytes(bytes).is_err(), "{:?}", bytes);
+    }
+}
+
+#[test]
+fn scale_by_ratio() {
+    let cap = DataCap::pow2(40);
+    assert_eq!(DataCap::pow2(41), cap.scale_by_ratio(2, 1));
+    assert_eq!(DataCap::pow2(38) * 3u64, cap.scale_by_ratio(3, 4));
+    assert_eq!(cap, cap.scale_by_ratio(7, 7));
+    assert_eq!(DataCap::from(3), DataCap::from(10).scale_by_ratio(1, 3));
+    assert_eq!(DataCap::zero(), DataCap::from(2).scale_by_ratio(1, 3));
+}
+
+#[test]
+fn try_sum() {
+    let max = MAX_DATACAP.clone();
+    let amounts = [DataCap::from(1), DataCap::pow2(40), DataCap::from(7)];
+    assert_eq!(DataCap::zero(), DataCap::try_sum(&amounts[..0]).unwrap());
+    assert_eq!(DataCap::pow2(40) + 8, DataCap::try_sum(&amounts).unwrap());
+
+    // Up to the bound is fine.
+    let near = [&max - DataCap::pow2(40), DataCap::pow2(39), DataCap::pow2(39)];
+    assert_eq!(max, DataCap::try_sum(&near).unwrap());
+    let over = [&max - DataCap::pow2(40), DataCap::pow2(40), DataCap::from(1)];
+    assert!(DataCap::try_sum(&over).is_err());
+    assert!(DataCap::try_sum(vec![&max, &max]).is_err());
+}
+
+#[test]
+fn to_grouped_string() {
+    assert_eq!("0", DataCap::zero().to_grouped_string());
+    assert_eq!("7", DataCap::from(7).to_grouped_string());
+    assert_eq!("999", DataCap::from(999).to_grouped_string());
+    assert_eq!("1,000", DataCap::from(1000).to_grouped_string());
+    assert_eq!("1,048,576", DataCap::pow2(20).to_grouped_string());
+    assert_eq!("-34,359,738,368", (-DataCap::pow2(35)).to_grouped_string());
+    assert_eq!("1,180,591,620,717,411,303,424", MAX_DATACAP.to_grouped_string());
+
+    // Display is unaffected.
+    assert_eq!("1048576", DataCap::pow2(20).to_string());
+}
+
+#[test]
+fn to_padded_piece_size() {
+    assert_eq!(PaddedPieceSize(128), DataCap::from(128).to_padded_piece_size().unwrap());
+    assert_eq!(PaddedPieceSize(2048), DataCap::pow2(11).to_padded_piece_size().unwrap());
+    assert_eq!(PaddedPieceSize(1 << 35), DataCap::pow2(35).to_padded_piece_size().unwrap());
+    assert_eq!(PaddedPieceSize(1 << 63), DataCap::pow2(63).to_padded_piece_size().unwrap());
+
+    for invalid in [
+        DataCap::zero(),
+        DataCap::from(64),
+        DataCap::from(127),
+        DataCap::from(129),
+        DataCap::from(3 << 20),
+        DataCap::pow2(64),
+        -DataCap::pow2(20),
+    ] {
+        assert!(invalid.to_padded_piece_size().is_err(), "{} accepted", invalid);
+    }
+}
+
+#[test]
+fn try_from_power() {
+    assert_eq!(DataCap::zero(), DataCap::try_from_power(StoragePower::zero()).unwrap());
+    assert_eq!(DataCap::pow2(35), DataCap::try_from_power(StoragePower::from(1) << 35).unwrap());
+
+    let err = DataCap::try_from_power(StoragePower::from(-1)).unwrap_err();
+    assert_eq!(ExitCode::USR_ILLEGAL_ARGUMENT, err.exit_code());
+}
+
+#[test]
+fn is_between() {
+    let low = DataCap::from(10);
+    let high = DataCap::from(20);
+    assert!(low.is_between(&low, &high));
+    assert!(high.is_between(&low, &high));
+    assert!(DataCap::from(15).is_between(&low, &high));
+    assert!(!DataCap::from(9).is_between(&low, &high));
+    assert!(!DataCap::from(21).is_between(&low, &high));
+    assert!(!DataCap::from(-15).is_between(&low, &high));
+    // An empty range contains nothing.
+    assert!(!DataCap::from(15).is_between(&high, &low));
+}
+
+#[test]
+fn halve() {
+    assert_eq!(DataCap::from(1048576), DataCap::from(2 * 1048576).halve());
+    assert_eq!(DataCap::from(3), DataCap::from(7).halve());
+    assert_eq!(DataCap::zero(), DataCap::from(1).halve());
+    assert_eq!(DataCap::zero(), DataCap::zero().halve());
+    assert_eq!(DataCap::pow2(69), MAX_DATACAP.halve());
+    // Rounds towards negative infinity, not zero.
+    assert_eq!(DataCap::from(-4), DataCap::from(-7).halve());
+}
+
+#[test]
+fn add_opt() {
+    let a = DataCap::from(1024);
+    let b = DataCap::from(2048);
+    assert_eq!(DataCap::from(3072), DataCap::add_opt(Some(a.clone()), Some(b.clone())));
+    assert_eq!(a, DataCap::add_opt(Some(a.clone()), None));
+    assert_eq!(b, DataCap::add_opt(None, Some(b.clone())));
+    assert_eq!(DataCap::zero(), DataCap::add_opt(None, None));
+}
+
+#[test]
+fn order_of_magnitude() {
+    assert_eq!(0, DataCap::zero().order_of_magnitude());
+    assert_eq!(0, DataCap::from(-1024).order_of_magnitude());
+    assert_eq!(0, DataCap::from(1).order_of_magnitude());
+    assert_eq!(1, DataCap::from(3).order_of_magnitude());
+    assert_eq!(10, DataCap::from(1024).order_of_magnitude());
+    assert_eq!(19, DataCap::from(1048575).order_of_magnitude());
+    assert_eq!(20, DataCap::from(1048576).order_of_magnitude());
+    assert_eq!(40, DataCap::pow2(40).order_of_magnitude());
+    assert_eq!(70, MAX_DATACAP.order_of_magnitude());
+}
+
+/// Signed removal proposals cover the CBOR encoding of datacap amounts, so these bytes
+/// must not change.
+#[test]
+fn cbor_encoding_matches_golden_vectors() {
+    let mut large = vec![0x58, 0x1b, 0x00, 0x01];
+    large.extend([0u8; 25]);
+    let cases: Vec<(DataCap, Vec<u8>)> = vec![
+        (DataCap::zero(), vec![0x40]),
+        (DataCap::from(1), vec![0x42, 0x00, 0x01]),
+        (DataCap::from(-1), vec![0x42, 0x01, 0x01]),
+        (DataCap::from(1024), vec![0x43, 0x00, 0x04, 0x00]),
+        (DataCap::pow2(35), vec![0x46, 0x00, 0x08, 0x00, 0x00, 0x00, 0x00]),
+        (
+            MAX_DATACAP.clone(),
+            vec![0x4a, 0x00, 0x40, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+        ),
+        (DataCap::pow2(200), large),
+    ];
+    for (cap, golden) in cases {
+        assert_eq!(golden, to_vec(&BigIntSer(&cap)).unwrap(), "encoding of {}", cap);
+        let decoded: BigIntDe = from_slice(&golden).unwrap();
+        assert_eq!(cap, decoded.0);
+        if !cap.is_negative() {
+            assert_eq!(golden, to_vec(&NonNegDataCap::try_from(cap).unwrap()).unwrap());
+        }
+    }
+}
+
+#[test]
+fn describe_datacap_as_bytes() {
+    assert_eq!("0 bytes (0.000 GiB)", describe_datacap(&DataCap::zero()));
+    assert_eq!("1,048,576 bytes (0.001 GiB)", describe_datacap(&DataCap::pow2(20)));
+    assert_eq!("34,359,738,368 bytes (32.000 GiB)", describe_datacap(&DataCap::pow2(35)));
+    assert_datacap_eq!(DataCap::pow2(35), DataCap::parse_with_units("32GiB").unwrap());
+}
+
+#[test]
+#[should_panic(
+    expected = "left: 1,048,576 bytes (0.001 GiB)\n right: 1,048,575 bytes (0.001 GiB)\n  diff: -1 bytes"
+)]
+fn assert_datacap_eq_describes_mismatch() {
+    assert_datacap_eq!(DataCap::pow2(20), DataCap::pow2(20) - 1);
+}
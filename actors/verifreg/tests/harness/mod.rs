@@ -4,12 +4,29 @@ use fvm_ipld_blockstore::MemoryBlockstore;
 use fvm_ipld_encoding::RawBytes;
 use fvm_shared::address::Address;
 use fvm_shared::bigint::bigint_ser::BigIntDe;
+use fvm_shared::crypto::signature::{Signature, SignatureType};
 use fvm_shared::{MethodNum, HAMT_BIT_WIDTH};
 use lazy_static::lazy_static;
+use num_traits::Zero;
+use std::convert::TryFrom;
 
 use fil_actor_verifreg::{
-    Actor as VerifregActor, AddVerifierClientParams, AddVerifierParams, DataCap, Method,
-    RestoreBytesParams, State, UseBytesParams,
+    Actor as VerifregActor, AddVerifiedClientForParams, AddVerifiedClientsParams,
+    AddVerifierClientParams, AddVerifierParams, AddressRole, BatchRemoveDataCapParams,
+    BatchRemoveDataCapReturn, CanJointlyRemoveParams, DataCap, DataCapDiff,
+    DecreaseVerifierAllowanceParams, GetVerifierClientStatusParams, ImportProposalIdsParams,
+    ImportVerifiersParams, Method, NonNegDataCap, PrepareRemovalParams, PrepareRemovalReturn,
+    RemoveDataCapParams, RemoveDataCapParamsV2, RemoveDataCapProposal, RemoveDataCapProposalID,
+    RemoveDataCapProposalV2, RemoveDataCapProposalV3, RemoveDataCapRequest, RemoveDataCapReturn,
+    RestoreBytesParams, RevokeAllDataCapParams, RevokeAllDataCapProposal, ScaleAllVerifiersParams,
+    SetClientDataCapParams, SetClientDataCapProposal, SetVerifierFrozenParams, State,
+    StepwiseRemoveDataCapParams, StepwiseRemoveDataCapReturn, SubmitRemovalApprovalParams,
+    SubmitRemovalApprovalReturn, SwapVerifierAllowancesParams, TransferAllowanceParams,
+    UseBytesParams, VerifierClientKey, VerifierClientStatus, VerifierUtilization,
+    DEFAULT_REQUIRED_REMOVAL_APPROVERS, REMOVE_DATA_CAP_PROPOSAL_FORMAT_V1,
+    REMOVE_DATA_CAP_PROPOSAL_FORMAT_V2, REMOVE_DATA_CAP_PROPOSAL_FORMAT_V3,
+    SIGNATURE_DOMAIN_SEPARATION_REMOVE_DATA_CAP, SIGNATURE_DOMAIN_SEPARATION_REVOKE_ALL_DATA_CAP,
+    SIGNATURE_DOMAIN_SEPARATION_SET_CLIENT_DATA_CAP, STATE_VERSION,
 };
 use fil_actors_runtime::test_utils::*;
 use fil_actors_runtime::{
@@ -30,6 +47,10 @@ pub fn new_runtime() -> MockRuntime {
     }
 }
 
+pub fn non_neg(cap: &DataCap) -> NonNegDataCap {
+    NonNegDataCap::try_from(cap.clone()).unwrap()
+}
+
 pub fn new_harness() -> (Harness, MockRuntime) {
     let mut rt = new_runtime();
     let h = Harness { root: *ROOT_ADDR };
@@ -59,6 +80,10 @@ impl Harness {
         assert_eq!(self.root, state.root_key);
         assert_eq!(empty_map, state.verified_clients);
         assert_eq!(empty_map, state.verifiers);
+        assert!(state.total_burned_allowance.is_zero());
+        assert_eq!(rt.policy.minimum_verified_deal_size, state.minimum_allocation_size);
+        assert_eq!(DEFAULT_REQUIRED_REMOVAL_APPROVERS, state.required_removal_approvers);
+        assert_eq!(STATE_VERSION, state.version);
     }
 
     pub fn add_verifier(
@@ -69,7 +94,7 @@ impl Harness {
     ) -> Result<(), ActorError> {
         rt.expect_validate_caller_addr(vec![self.root]);
         rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, self.root);
-        let params = AddVerifierParams { address: *verifier, allowance: allowance.clone() };
+        let params = AddVerifierParams { address: *verifier, allowance: non_neg(allowance) };
         let ret = rt.call::<VerifregActor>(
             Method::AddVerifier as MethodNum,
             &RawBytes::serialize(params).unwrap(),
@@ -81,6 +106,35 @@ impl Harness {
         Ok(())
     }
 
+    pub fn import_verifiers(
+        &self,
+        rt: &mut MockRuntime,
+        verifiers: &[(Address, DataCap)],
+    ) -> Result<(), ActorError> {
+        rt.expect_validate_caller_addr(vec![self.root]);
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, self.root);
+        let params = ImportVerifiersParams {
+            verifiers: verifiers
+                .iter()
+                .map(|(address, allowance)| AddVerifierParams {
+                    address: *address,
+                    allowance: non_neg(allowance),
+                })
+                .collect(),
+        };
+        let ret = rt.call::<VerifregActor>(
+            Method::ImportVerifiers as MethodNum,
+            &RawBytes::serialize(params).unwrap(),
+        )?;
+        assert_eq!(RawBytes::default(), ret);
+        rt.verify();
+
+        for (verifier, allowance) in verifiers {
+            self.assert_verifier_allowance(rt, verifier, allowance);
+        }
+        Ok(())
+    }
+
     pub fn remove_verifier(
         &self,
         rt: &mut MockRuntime,
@@ -99,6 +153,160 @@ impl Harness {
         Ok(())
     }
 
+    pub fn set_minimum_allocation_size(
+        &self,
+        rt: &mut MockRuntime,
+        size: &DataCap,
+    ) -> Result<(), ActorError> {
+        rt.expect_validate_caller_addr(vec![self.root]);
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, self.root);
+        let ret = rt.call::<VerifregActor>(
+            Method::SetMinimumAllocationSize as MethodNum,
+            &RawBytes::serialize(non_neg(size)).unwrap(),
+        )?;
+        assert_eq!(RawBytes::default(), ret);
+        rt.verify();
+
+        let st: State = rt.get_state();
+        assert_eq!(*size, st.minimum_allocation_size);
+        Ok(())
+    }
+
+    pub fn set_required_removal_approvers(
+        &self,
+        rt: &mut MockRuntime,
+        approvers: u64,
+    ) -> Result<(), ActorError> {
+        rt.expect_validate_caller_addr(vec![self.root]);
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, self.root);
+        let ret = rt.call::<VerifregActor>(
+            Method::SetRequiredRemovalApprovers as MethodNum,
+            &RawBytes::serialize(approvers).unwrap(),
+        )?;
+        assert_eq!(RawBytes::default(), ret);
+        rt.verify();
+
+        let st: State = rt.get_state();
+        assert_eq!(approvers, st.required_removal_approvers);
+        Ok(())
+    }
+
+    pub fn burn_verifier_allowance(
+        &self,
+        rt: &mut MockRuntime,
+        verifier: &Address,
+    ) -> Result<(), ActorError> {
+        rt.expect_validate_caller_addr(vec![self.root]);
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, self.root);
+        let ret = rt.call::<VerifregActor>(
+            Method::BurnVerifierAllowance as MethodNum,
+            &RawBytes::serialize(verifier).unwrap(),
+        )?;
+        assert_eq!(RawBytes::default(), ret);
+        rt.verify();
+
+        self.assert_verifier_allowance(rt, verifier, &DataCap::zero());
+        Ok(())
+    }
+
+    pub fn decrease_verifier_allowance(
+        &self,
+        rt: &mut MockRuntime,
+        verifier: &Address,
+        amount: &DataCap,
+    ) -> Result<DataCap, ActorError> {
+        rt.expect_validate_caller_addr(vec![self.root]);
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, self.root);
+        let params =
+            DecreaseVerifierAllowanceParams { address: *verifier, allowance: non_neg(amount) };
+        let ret = rt.call::<VerifregActor>(
+            Method::DecreaseVerifierAllowance as MethodNum,
+            &RawBytes::serialize(params).unwrap(),
+        )?;
+        rt.verify();
+
+        let BigIntDe(new_cap) = ret.deserialize().unwrap();
+        self.assert_verifier_allowance(rt, verifier, &new_cap);
+        Ok(new_cap)
+    }
+
+    pub fn transfer_allowance(
+        &self,
+        rt: &mut MockRuntime,
+        from: &Address,
+        to: &Address,
+        amount: &DataCap,
+    ) -> Result<(), ActorError> {
+        rt.expect_validate_caller_addr(vec![self.root]);
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, self.root);
+        let params = TransferAllowanceParams { from: *from, to: *to, amount: non_neg(amount) };
+        let ret = rt.call::<VerifregActor>(
+            Method::TransferAllowance as MethodNum,
+            &RawBytes::serialize(params).unwrap(),
+        )?;
+        assert_eq!(RawBytes::default(), ret);
+        rt.verify();
+        Ok(())
+    }
+
+    pub fn set_verifier_frozen(
+        &self,
+        rt: &mut MockRuntime,
+        verifier: &Address,
+        frozen: bool,
+    ) -> Result<(), ActorError> {
+        rt.expect_validate_caller_addr(vec![self.root]);
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, self.root);
+        let params = SetVerifierFrozenParams { verifier: *verifier, frozen };
+        let ret = rt.call::<VerifregActor>(
+            Method::SetVerifierFrozen as MethodNum,
+            &RawBytes::serialize(params).unwrap(),
+        )?;
+        assert_eq!(RawBytes::default(), ret);
+        rt.verify();
+
+        let st: State = rt.get_state();
+        let verifier_id_addr = rt.get_id_address(verifier).unwrap();
+        assert_eq!(frozen, st.is_verifier_frozen(&rt.store, &verifier_id_addr).unwrap());
+        Ok(())
+    }
+
+    pub fn swap_verifier_allowances(
+        &self,
+        rt: &mut MockRuntime,
+        a: &Address,
+        b: &Address,
+    ) -> Result<(), ActorError> {
+        rt.expect_validate_caller_addr(vec![self.root]);
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, self.root);
+        let params = SwapVerifierAllowancesParams { a: *a, b: *b };
+        let ret = rt.call::<VerifregActor>(
+            Method::SwapVerifierAllowances as MethodNum,
+            &RawBytes::serialize(params).unwrap(),
+        )?;
+        assert_eq!(RawBytes::default(), ret);
+        rt.verify();
+        Ok(())
+    }
+
+    pub fn scale_all_verifiers(
+        &self,
+        rt: &mut MockRuntime,
+        numer: u64,
+        denom: u64,
+    ) -> Result<(), ActorError> {
+        rt.expect_validate_caller_addr(vec![self.root]);
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, self.root);
+        let params = ScaleAllVerifiersParams { numer, denom };
+        let ret = rt.call::<VerifregActor>(
+            Method::ScaleAllVerifiers as MethodNum,
+            &RawBytes::serialize(params).unwrap(),
+        )?;
+        assert_eq!(RawBytes::default(), ret);
+        rt.verify();
+        Ok(())
+    }
+
     pub fn assert_verifier_allowance(
         &self,
         rt: &MockRuntime,
@@ -115,6 +323,137 @@ impl Harness {
         allowance.clone()
     }
 
+    pub fn total_verifier_allowance(&self, rt: &mut MockRuntime) -> DataCap {
+        rt.expect_validate_caller_any();
+        let ret = rt
+            .call::<VerifregActor>(
+                Method::TotalVerifierAllowance as MethodNum,
+                &RawBytes::default(),
+            )
+            .unwrap();
+        rt.verify();
+        let BigIntDe(total) = ret.deserialize().unwrap();
+        total
+    }
+
+    pub fn get_client_total_granted(&self, rt: &mut MockRuntime, client: &Address) -> DataCap {
+        rt.expect_validate_caller_any();
+        let ret = rt
+            .call::<VerifregActor>(
+                Method::GetClientTotalGranted as MethodNum,
+                &RawBytes::serialize(client).unwrap(),
+            )
+            .unwrap();
+        rt.verify();
+        let BigIntDe(total) = ret.deserialize().unwrap();
+        total
+    }
+
+    pub fn get_client_granters(&self, rt: &mut MockRuntime, client: &Address) -> Vec<Address> {
+        rt.expect_validate_caller_any();
+        let ret = rt
+            .call::<VerifregActor>(
+                Method::GetClientGranters as MethodNum,
+                &RawBytes::serialize(client).unwrap(),
+            )
+            .unwrap();
+        rt.verify();
+        ret.deserialize().unwrap()
+    }
+
+    pub fn get_total_removed(&self, rt: &mut MockRuntime) -> DataCap {
+        rt.expect_validate_caller_any();
+        let ret = rt
+            .call::<VerifregActor>(Method::GetTotalRemoved as MethodNum, &RawBytes::default())
+            .unwrap();
+        rt.verify();
+        let BigIntDe(total) = ret.deserialize().unwrap();
+        total
+    }
+
+    pub fn get_address_role(&self, rt: &mut MockRuntime, addr: &Address) -> AddressRole {
+        rt.expect_validate_caller_any();
+        let ret = rt
+            .call::<VerifregActor>(
+                Method::GetAddressRole as MethodNum,
+                &RawBytes::serialize(addr).unwrap(),
+            )
+            .unwrap();
+        rt.verify();
+        ret.deserialize().unwrap()
+    }
+
+    pub fn get_verifier_utilization(
+        &self,
+        rt: &mut MockRuntime,
+        verifier: &Address,
+    ) -> Result<VerifierUtilization, ActorError> {
+        rt.expect_validate_caller_any();
+        let ret = rt.call::<VerifregActor>(
+            Method::GetVerifierUtilization as MethodNum,
+            &RawBytes::serialize(verifier).unwrap(),
+        )?;
+        rt.verify();
+        Ok(ret.deserialize().unwrap())
+    }
+
+    pub fn prepare_removal(
+        &self,
+        rt: &mut MockRuntime,
+        verifier1: &Address,
+        verifier2: &Address,
+        client: &Address,
+        amount: &DataCap,
+    ) -> Result<PrepareRemovalReturn, ActorError> {
+        rt.expect_validate_caller_any();
+        let params = PrepareRemovalParams {
+            verifier1: *verifier1,
+            verifier2: *verifier2,
+            client: *client,
+            amount: non_neg(amount),
+        };
+        let ret = rt.call::<VerifregActor>(
+            Method::PrepareRemoval as MethodNum,
+            &RawBytes::serialize(params).unwrap(),
+        )?;
+        rt.verify();
+        Ok(ret.deserialize().unwrap())
+    }
+
+    pub fn get_verifier_client_status(
+        &self,
+        rt: &mut MockRuntime,
+        verifier: &Address,
+        client: &Address,
+    ) -> Result<VerifierClientStatus, ActorError> {
+        rt.expect_validate_caller_any();
+        let params = GetVerifierClientStatusParams { verifier: *verifier, client: *client };
+        let ret = rt.call::<VerifregActor>(
+            Method::GetVerifierClientStatus as MethodNum,
+            &RawBytes::serialize(params).unwrap(),
+        )?;
+        rt.verify();
+        Ok(ret.deserialize().unwrap())
+    }
+
+    pub fn can_jointly_remove(
+        &self,
+        rt: &mut MockRuntime,
+        verifier1: &Address,
+        verifier2: &Address,
+    ) -> bool {
+        rt.expect_validate_caller_any();
+        let params = CanJointlyRemoveParams { verifier1: *verifier1, verifier2: *verifier2 };
+        let ret = rt
+            .call::<VerifregActor>(
+                Method::CanJointlyRemove as MethodNum,
+                &RawBytes::serialize(params).unwrap(),
+            )
+            .unwrap();
+        rt.verify();
+        ret.deserialize().unwrap()
+    }
+
     pub fn assert_verifier_removed(&self, rt: &MockRuntime, verifier: &Address) {
         let verifier_id_addr = rt.get_id_address(verifier).unwrap();
         let verifiers = load_verifiers(rt);
@@ -131,7 +470,7 @@ impl Harness {
     ) -> Result<(), ActorError> {
         rt.expect_validate_caller_any();
         rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, *verifier);
-        let params = AddVerifierClientParams { address: *client, allowance: allowance.clone() };
+        let params = AddVerifierClientParams { address: *client, allowance: non_neg(allowance) };
         let ret = rt.call::<VerifregActor>(
             Method::AddVerifiedClient as MethodNum,
             &RawBytes::serialize(params).unwrap(),
@@ -144,6 +483,54 @@ impl Harness {
         Ok(())
     }
 
+    pub fn add_clients(
+        &self,
+        rt: &mut MockRuntime,
+        verifier: &Address,
+        clients: &[(Address, DataCap)],
+    ) -> Result<(), ActorError> {
+        rt.expect_validate_caller_any();
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, *verifier);
+        let params = AddVerifiedClientsParams {
+            clients: clients
+                .iter()
+                .map(|(address, allowance)| AddVerifierClientParams {
+                    address: *address,
+                    allowance: non_neg(allowance),
+                })
+                .collect(),
+        };
+        let ret = rt.call::<VerifregActor>(
+            Method::AddVerifiedClients as MethodNum,
+            &RawBytes::serialize(params).unwrap(),
+        )?;
+        assert_eq!(RawBytes::default(), ret);
+        rt.verify();
+        Ok(())
+    }
+
+    pub fn add_client_for(
+        &self,
+        rt: &mut MockRuntime,
+        verifier: &Address,
+        client: &Address,
+        allowance: &DataCap,
+    ) -> Result<(), ActorError> {
+        rt.expect_validate_caller_addr(vec![self.root]);
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, self.root);
+        let params = AddVerifiedClientForParams {
+            verifier: *verifier,
+            params: AddVerifierClientParams { address: *client, allowance: non_neg(allowance) },
+        };
+        let ret = rt.call::<VerifregActor>(
+            Method::AddVerifiedClientFor as MethodNum,
+            &RawBytes::serialize(params).unwrap(),
+        )?;
+        assert_eq!(RawBytes::default(), ret);
+        rt.verify();
+        Ok(())
+    }
+
     pub fn assert_client_allowance(&self, rt: &MockRuntime, client: &Address, allowance: &DataCap) {
         let client_id_addr = rt.get_id_address(client).unwrap();
         assert_eq!(*allowance, self.get_client_allowance(rt, &client_id_addr));
@@ -209,6 +596,552 @@ impl Harness {
         Ok(())
     }
 
+    pub fn remove_client_datacap(
+        &self,
+        rt: &mut MockRuntime,
+        client: &Address,
+        amount: &DataCap,
+        verifier1: &Address,
+        verifier2: &Address,
+    ) -> Result<RemoveDataCapReturn, ActorError> {
+        rt.expect_validate_caller_addr(vec![self.root]);
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, self.root);
+        let params = RemoveDataCapParams {
+            verified_client_to_remove: *client,
+            data_cap_amount_to_remove: amount.clone(),
+            verifier_request_1: self.make_remove_request(rt, verifier1, client, amount),
+            verifier_request_2: self.make_remove_request(rt, verifier2, client, amount),
+        };
+        let ret = rt.call::<VerifregActor>(
+            Method::RemoveVerifiedClientDataCap as MethodNum,
+            &RawBytes::serialize(params).unwrap(),
+        )?;
+        rt.verify();
+        Ok(ret.deserialize().unwrap())
+    }
+
+    pub fn batch_remove_client_datacap(
+        &self,
+        rt: &mut MockRuntime,
+        removals: Vec<RemoveDataCapParamsV2>,
+    ) -> Result<BatchRemoveDataCapReturn, ActorError> {
+        rt.expect_validate_caller_addr(vec![self.root]);
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, self.root);
+        let params = BatchRemoveDataCapParams { removals };
+        let ret = rt.call::<VerifregActor>(
+            Method::BatchRemoveDataCap as MethodNum,
+            &RawBytes::serialize(params).unwrap(),
+        )?;
+        rt.verify();
+        Ok(ret.deserialize().unwrap())
+    }
+
+    /// Removes `amounts` from a client in successive steps of one message, each signed by
+    /// both verifiers over the proposal IDs the step before leaves.
+    pub fn stepwise_remove_client_datacap(
+        &self,
+        rt: &mut MockRuntime,
+        client: &Address,
+        amounts: &[DataCap],
+        verifier1: &Address,
+        verifier2: &Address,
+    ) -> Result<StepwiseRemoveDataCapReturn, ActorError> {
+        rt.expect_validate_caller_addr(vec![self.root]);
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, self.root);
+        let id1 = self.get_proposal_id(rt, verifier1, client);
+        let id2 = self.get_proposal_id(rt, verifier2, client);
+        let mut steps = vec![];
+        for (i, amount) in amounts.iter().enumerate() {
+            let requests = [
+                self.sign_remove_request_at(verifier1, client, amount, id1 + i as u64),
+                self.sign_remove_request_at(verifier2, client, amount, id2 + i as u64),
+            ];
+            for request in &requests {
+                rt.expect_verify_signature(ExpectedVerifySig {
+                    sig: request.signature.clone(),
+                    signer: request.verifier,
+                    plaintext: request.signature.bytes.clone(),
+                    result: Ok(()),
+                });
+            }
+            let [request1, request2] = requests;
+            steps.push(RemoveDataCapParamsV2 {
+                verified_client_to_remove: *client,
+                data_cap_amount_to_remove: amount.clone(),
+                verifier_request_1: request1,
+                verifier_request_2: Some(request2),
+                format_version: REMOVE_DATA_CAP_PROPOSAL_FORMAT_V1,
+                expected_client_data_cap: DataCap::zero(),
+                additional_verifier_requests: vec![],
+                reason: None,
+            });
+        }
+        let params = StepwiseRemoveDataCapParams { steps };
+        let ret = rt.call::<VerifregActor>(
+            Method::StepwiseRemoveDataCap as MethodNum,
+            &RawBytes::serialize(params).unwrap(),
+        )?;
+        rt.verify();
+        Ok(ret.deserialize().unwrap())
+    }
+
+    /// Builds V1 removal params signed by two verifiers, expecting both signatures to be
+    /// verified.
+    pub fn make_remove_params(
+        &self,
+        rt: &MockRuntime,
+        client: &Address,
+        amount: &DataCap,
+        verifier1: &Address,
+        verifier2: &Address,
+    ) -> RemoveDataCapParamsV2 {
+        RemoveDataCapParamsV2 {
+            verified_client_to_remove: *client,
+            data_cap_amount_to_remove: amount.clone(),
+            verifier_request_1: self.make_remove_request(rt, verifier1, client, amount),
+            verifier_request_2: Some(self.make_remove_request(rt, verifier2, client, amount)),
+            format_version: REMOVE_DATA_CAP_PROPOSAL_FORMAT_V1,
+            expected_client_data_cap: DataCap::zero(),
+            additional_verifier_requests: vec![],
+            reason: None,
+        }
+    }
+
+    /// Submits `verifier`'s approval of a removal, expecting its signature to be checked. If
+    /// `pending` names the verifiers whose approvals are already waiting, the removal is
+    /// instead expected to execute, checking this signature and then theirs in the order the
+    /// actor reads them back.
+    pub fn submit_removal_approval(
+        &self,
+        rt: &mut MockRuntime,
+        client: &Address,
+        amount: &DataCap,
+        verifier: &Address,
+        pending: &[Address],
+    ) -> Result<SubmitRemovalApprovalReturn, ActorError> {
+        rt.expect_validate_caller_addr(vec![self.root]);
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, self.root);
+        let verifier_request = self.make_remove_request(rt, verifier, client, amount);
+        let mut pending = pending.to_vec();
+        pending.sort_by_key(|verifier| verifier.to_bytes());
+        for pending in &pending {
+            self.make_remove_request(rt, pending, client, amount);
+        }
+        let params = SubmitRemovalApprovalParams {
+            verified_client_to_remove: *client,
+            data_cap_amount_to_remove: non_neg(amount),
+            verifier_request,
+        };
+        let ret = rt.call::<VerifregActor>(
+            Method::SubmitRemovalApproval as MethodNum,
+            &RawBytes::serialize(params).unwrap(),
+        )?;
+        rt.verify();
+        Ok(ret.deserialize().unwrap())
+    }
+
+    /// Removes datacap with a request signed by each of `verifiers`, which may number
+    /// other than two.
+    pub fn remove_client_datacap_by(
+        &self,
+        rt: &mut MockRuntime,
+        client: &Address,
+        amount: &DataCap,
+        verifiers: &[Address],
+    ) -> Result<RemoveDataCapReturn, ActorError> {
+        rt.expect_validate_caller_addr(vec![self.root]);
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, self.root);
+        let mut requests =
+            verifiers.iter().map(|verifier| self.make_remove_request(rt, verifier, client, amount));
+        let params = RemoveDataCapParamsV2 {
+            verified_client_to_remove: *client,
+            data_cap_amount_to_remove: amount.clone(),
+            verifier_request_1: requests.next().unwrap(),
+            verifier_request_2: requests.next(),
+            format_version: REMOVE_DATA_CAP_PROPOSAL_FORMAT_V1,
+            expected_client_data_cap: DataCap::zero(),
+            additional_verifier_requests: requests.collect(),
+            reason: None,
+        };
+        let ret = rt.call::<VerifregActor>(
+            Method::RemoveVerifiedClientDataCapV2 as MethodNum,
+            &RawBytes::serialize(params).unwrap(),
+        )?;
+        rt.verify();
+        Ok(ret.deserialize().unwrap())
+    }
+
+    /// Removes datacap with V2 requests, signed against the client holding
+    /// `expected_balance`.
+    pub fn remove_client_datacap_v2(
+        &self,
+        rt: &mut MockRuntime,
+        client: &Address,
+        amount: &DataCap,
+        expected_balance: &DataCap,
+        verifier1: &Address,
+        verifier2: &Address,
+    ) -> Result<RemoveDataCapReturn, ActorError> {
+        rt.expect_validate_caller_addr(vec![self.root]);
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, self.root);
+        let params = RemoveDataCapParamsV2 {
+            verified_client_to_remove: *client,
+            data_cap_amount_to_remove: amount.clone(),
+            verifier_request_1: self.make_remove_request_v2(
+                rt,
+                verifier1,
+                client,
+                amount,
+                expected_balance,
+            ),
+            verifier_request_2: Some(self.make_remove_request_v2(
+                rt,
+                verifier2,
+                client,
+                amount,
+                expected_balance,
+            )),
+            format_version: REMOVE_DATA_CAP_PROPOSAL_FORMAT_V2,
+            expected_client_data_cap: expected_balance.clone(),
+            additional_verifier_requests: vec![],
+            reason: None,
+        };
+        let ret = rt.call::<VerifregActor>(
+            Method::RemoveVerifiedClientDataCapV2 as MethodNum,
+            &RawBytes::serialize(params).unwrap(),
+        )?;
+        rt.verify();
+        Ok(ret.deserialize().unwrap())
+    }
+
+    /// Removes datacap with V3 requests, which also sign over `reason`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn remove_client_datacap_v3(
+        &self,
+        rt: &mut MockRuntime,
+        client: &Address,
+        amount: &DataCap,
+        expected_balance: &DataCap,
+        verifier1: &Address,
+        verifier2: &Address,
+        reason: Option<&str>,
+    ) -> Result<RemoveDataCapReturn, ActorError> {
+        rt.expect_validate_caller_addr(vec![self.root]);
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, self.root);
+        let params = RemoveDataCapParamsV2 {
+            verified_client_to_remove: *client,
+            data_cap_amount_to_remove: amount.clone(),
+            verifier_request_1: self.make_remove_request_v3(
+                rt,
+                verifier1,
+                client,
+                amount,
+                expected_balance,
+                reason,
+            ),
+            verifier_request_2: Some(self.make_remove_request_v3(
+                rt,
+                verifier2,
+                client,
+                amount,
+                expected_balance,
+                reason,
+            )),
+            format_version: REMOVE_DATA_CAP_PROPOSAL_FORMAT_V3,
+            expected_client_data_cap: expected_balance.clone(),
+            additional_verifier_requests: vec![],
+            reason: reason.map(str::to_owned),
+        };
+        let ret = rt.call::<VerifregActor>(
+            Method::RemoveVerifiedClientDataCapV2 as MethodNum,
+            &RawBytes::serialize(params).unwrap(),
+        )?;
+        rt.verify();
+        Ok(ret.deserialize().unwrap())
+    }
+
+    /// Builds a removal request signed over the verifier's current proposal ID, and
+    /// expects the actor to verify its signature.
+    pub fn make_remove_request(
+        &self,
+        rt: &MockRuntime,
+        verifier: &Address,
+        client: &Address,
+        amount: &DataCap,
+    ) -> RemoveDataCapRequest {
+        let request = self.sign_remove_request(rt, verifier, client, amount);
+        rt.expect_verify_signature(ExpectedVerifySig {
+            sig: request.signature.clone(),
+            signer: *verifier,
+            plaintext: request.signature.bytes.clone(),
+            result: Ok(()),
+        });
+        request
+    }
+
+    /// Like `make_remove_request`, but without expecting the signature to be verified.
+    pub fn sign_remove_request(
+        &self,
+        rt: &MockRuntime,
+        verifier: &Address,
+        client: &Address,
+        amount: &DataCap,
+    ) -> RemoveDataCapRequest {
+        let id = self.get_proposal_id(rt, verifier, client);
+        self.sign_remove_request_at(verifier, client, amount, id)
+    }
+
+    /// Signs a V1 removal proposal with the given proposal ID.
+    pub fn sign_remove_request_at(
+        &self,
+        verifier: &Address,
+        client: &Address,
+        amount: &DataCap,
+        id: u64,
+    ) -> RemoveDataCapRequest {
+        let proposal = RemoveDataCapProposal {
+            verified_client: *client,
+            data_cap_amount: amount.clone(),
+            removal_proposal_id: RemoveDataCapProposalID(id),
+        };
+        let payload = [
+            SIGNATURE_DOMAIN_SEPARATION_REMOVE_DATA_CAP,
+            RawBytes::serialize(proposal).unwrap().bytes(),
+        ]
+        .concat();
+        let signature = Signature { sig_type: SignatureType::Secp256k1, bytes: payload };
+        RemoveDataCapRequest { verifier: *verifier, signature }
+    }
+
+    /// Like `make_remove_request`, but signing a V2 proposal bound to the client's balance.
+    pub fn make_remove_request_v2(
+        &self,
+        rt: &MockRuntime,
+        verifier: &Address,
+        client: &Address,
+        amount: &DataCap,
+        client_balance: &DataCap,
+    ) -> RemoveDataCapRequest {
+        let proposal = RemoveDataCapProposalV2 {
+            verified_client: *client,
+            data_cap_amount: amount.clone(),
+            removal_proposal_id: RemoveDataCapProposalID(
+                self.get_proposal_id(rt, verifier, client),
+            ),
+            client_data_cap: client_balance.clone(),
+        };
+        let payload = [
+            SIGNATURE_DOMAIN_SEPARATION_REMOVE_DATA_CAP,
+            RawBytes::serialize(proposal).unwrap().bytes(),
+        ]
+        .concat();
+        let signature = Signature { sig_type: SignatureType::Secp256k1, bytes: payload.clone() };
+        rt.expect_verify_signature(ExpectedVerifySig {
+            sig: signature.clone(),
+            signer: *verifier,
+            plaintext: payload,
+            result: Ok(()),
+        });
+        RemoveDataCapRequest { verifier: *verifier, signature }
+    }
+
+    /// Like `make_remove_request_v2`, but signing a V3 proposal that also carries `reason`.
+    pub fn make_remove_request_v3(
+        &self,
+        rt: &MockRuntime,
+        verifier: &Address,
+        client: &Address,
+        amount: &DataCap,
+        client_balance: &DataCap,
+        reason: Option<&str>,
+    ) -> RemoveDataCapRequest {
+        let proposal = RemoveDataCapProposalV3 {
+            verified_client: *client,
+            data_cap_amount: amount.clone(),
+            removal_proposal_id: RemoveDataCapProposalID(
+                self.get_proposal_id(rt, verifier, client),
+            ),
+            client_data_cap: client_balance.clone(),
+            reason: reason.map(str::to_owned),
+        };
+        let payload = [
+            SIGNATURE_DOMAIN_SEPARATION_REMOVE_DATA_CAP,
+            RawBytes::serialize(proposal).unwrap().bytes(),
+        ]
+        .concat();
+        let signature = Signature { sig_type: SignatureType::Secp256k1, bytes: payload.clone() };
+        rt.expect_verify_signature(ExpectedVerifySig {
+            sig: signature.clone(),
+            signer: *verifier,
+            plaintext: payload,
+            result: Ok(()),
+        });
+        RemoveDataCapRequest { verifier: *verifier, signature }
+    }
+
+    pub fn revoke_all_client_datacap(
+        &self,
+        rt: &mut MockRuntime,
+        client: &Address,
+        verifier1: &Address,
+        verifier2: &Address,
+    ) -> Result<RemoveDataCapReturn, ActorError> {
+        self.revoke_all_client_datacap_by(rt, client, &[*verifier1, *verifier2])
+    }
+
+    /// Revokes a client's datacap with a request signed by each of `verifiers`, of which
+    /// there must be at least two.
+    pub fn revoke_all_client_datacap_by(
+        &self,
+        rt: &mut MockRuntime,
+        client: &Address,
+        verifiers: &[Address],
+    ) -> Result<RemoveDataCapReturn, ActorError> {
+        rt.expect_validate_caller_addr(vec![self.root]);
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, self.root);
+        let mut requests =
+            verifiers.iter().map(|verifier| self.make_revoke_all_request(rt, verifier, client));
+        let params = RevokeAllDataCapParams {
+            verified_client: *client,
+            verifier_request_1: requests.next().unwrap(),
+            verifier_request_2: requests.next().unwrap(),
+            additional_verifier_requests: requests.collect(),
+        };
+        let ret = rt.call::<VerifregActor>(
+            Method::RevokeAllClientDataCap as MethodNum,
+            &RawBytes::serialize(params).unwrap(),
+        )?;
+        rt.verify();
+        Ok(ret.deserialize().unwrap())
+    }
+
+    /// Like `make_remove_request`, but signing a revocation of the client's whole balance.
+    pub fn make_revoke_all_request(
+        &self,
+        rt: &MockRuntime,
+        verifier: &Address,
+        client: &Address,
+    ) -> RemoveDataCapRequest {
+        let proposal = RevokeAllDataCapProposal {
+            verified_client: *client,
+            removal_proposal_id: RemoveDataCapProposalID(
+                self.get_proposal_id(rt, verifier, client),
+            ),
+        };
+        let payload = [
+            SIGNATURE_DOMAIN_SEPARATION_REVOKE_ALL_DATA_CAP,
+            RawBytes::serialize(proposal).unwrap().bytes(),
+        ]
+        .concat();
+        let signature = Signature { sig_type: SignatureType::Secp256k1, bytes: payload.clone() };
+        rt.expect_verify_signature(ExpectedVerifySig {
+            sig: signature.clone(),
+            signer: *verifier,
+            plaintext: payload,
+            result: Ok(()),
+        });
+        RemoveDataCapRequest { verifier: *verifier, signature }
+    }
+
+    pub fn set_client_datacap(
+        &self,
+        rt: &mut MockRuntime,
+        client: &Address,
+        target: &DataCap,
+        verifier1: &Address,
+        verifier2: &Address,
+    ) -> Result<DataCapDiff, ActorError> {
+        self.set_client_datacap_by(rt, client, target, &[*verifier1, *verifier2])
+    }
+
+    /// Sets a client's datacap with a request signed by each of `verifiers`, of which there
+    /// must be at least two.
+    pub fn set_client_datacap_by(
+        &self,
+        rt: &mut MockRuntime,
+        client: &Address,
+        target: &DataCap,
+        verifiers: &[Address],
+    ) -> Result<DataCapDiff, ActorError> {
+        rt.expect_validate_caller_addr(vec![self.root]);
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, self.root);
+        let mut requests =
+            verifiers.iter().map(|verifier| self.make_set_request(rt, verifier, client, target));
+        let params = SetClientDataCapParams {
+            verified_client: *client,
+            target: non_neg(target),
+            verifier_request_1: requests.next().unwrap(),
+            verifier_request_2: requests.next().unwrap(),
+            additional_verifier_requests: requests.collect(),
+        };
+        let ret = rt.call::<VerifregActor>(
+            Method::SetClientDataCap as MethodNum,
+            &RawBytes::serialize(params).unwrap(),
+        )?;
+        rt.verify();
+        Ok(ret.deserialize().unwrap())
+    }
+
+    /// Like `make_remove_request`, but signing a new absolute datacap for the client.
+    pub fn make_set_request(
+        &self,
+        rt: &MockRuntime,
+        verifier: &Address,
+        client: &Address,
+        target: &DataCap,
+    ) -> RemoveDataCapRequest {
+        let proposal = SetClientDataCapProposal {
+            verified_client: *client,
+            target: target.clone(),
+            removal_proposal_id: RemoveDataCapProposalID(
+                self.get_proposal_id(rt, verifier, client),
+            ),
+        };
+        let payload = [
+            SIGNATURE_DOMAIN_SEPARATION_SET_CLIENT_DATA_CAP,
+            RawBytes::serialize(proposal).unwrap().bytes(),
+        ]
+        .concat();
+        let signature = Signature { sig_type: SignatureType::Secp256k1, bytes: payload.clone() };
+        rt.expect_verify_signature(ExpectedVerifySig {
+            sig: signature.clone(),
+            signer: *verifier,
+            plaintext: payload,
+            result: Ok(()),
+        });
+        RemoveDataCapRequest { verifier: *verifier, signature }
+    }
+
+    pub fn import_proposal_ids(
+        &self,
+        rt: &mut MockRuntime,
+        proposal_ids: Vec<(Address, Address, u64)>,
+    ) -> Result<(), ActorError> {
+        rt.expect_validate_caller_addr(vec![self.root]);
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, self.root);
+        let ret = rt.call::<VerifregActor>(
+            Method::ImportProposalIds as MethodNum,
+            &RawBytes::serialize(ImportProposalIdsParams { proposal_ids }).unwrap(),
+        )?;
+        assert_eq!(RawBytes::default(), ret);
+        rt.verify();
+        Ok(())
+    }
+
+    pub fn get_proposal_id(&self, rt: &MockRuntime, verifier: &Address, client: &Address) -> u64 {
+        let state: State = rt.get_state();
+        let proposal_ids = make_map_with_root_and_bitwidth::<_, RemoveDataCapProposalID>(
+            &state.remove_data_cap_proposal_ids,
+            &rt.store,
+            HAMT_BIT_WIDTH,
+        )
+        .unwrap();
+        proposal_ids
+            .get(&VerifierClientKey::new(*verifier, *client).to_bytes())
+            .unwrap()
+            .map_or(0, |id| id.0)
+    }
+
     pub fn check_state(&self, rt: &MockRuntime) {
         let (_, acc) = check_state_invariants(&rt.get_state(), rt.store());
         acc.assert_empty();
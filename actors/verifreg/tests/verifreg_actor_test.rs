@@ -72,14 +72,254 @@ mod construction {
     }
 }
 
+mod state {
+    use cid::multihash::Code;
+    use fil_actors_runtime::make_empty_map;
+    use fil_actors_runtime::test_utils::make_builtin;
+    use fvm_ipld_blockstore::MemoryBlockstore;
+    use fvm_ipld_encoding::{from_slice, to_vec, CborStore};
+    use fvm_shared::address::Address;
+    use fvm_shared::HAMT_BIT_WIDTH;
+
+    use fil_actor_verifreg::{DataCap, State, STATE_VERSION};
+
+    use crate::*;
+    use harness::*;
+    use util::*;
+
+    #[test]
+    fn new_state_has_current_version() {
+        let store = MemoryBlockstore::new();
+        let st = State::new(&store, *ROOT_ADDR, DataCap::from(1 << 20)).unwrap();
+        assert_eq!(STATE_VERSION, st.version);
+
+        let decoded: State = from_slice(&to_vec(&st).unwrap()).unwrap();
+        assert_eq!(STATE_VERSION, decoded.version);
+    }
+
+    #[test]
+    fn load_validated_state() {
+        let (h, mut rt) = new_harness();
+        h.add_verifier(&mut rt, &VERIFIER, &verifier_allowance(&rt)).unwrap();
+        let root = rt.store.put_cbor(&rt.get_state::<State>(), Code::Blake2b256).unwrap();
+
+        let st = State::load_validated(&rt.store, &root).unwrap();
+        assert_eq!(h.root, st.root_key);
+    }
+
+    #[test]
+    fn load_validated_rejects_corrupt_state() {
+        let (_, rt) = new_harness();
+        let missing = make_builtin(b"missing");
+        let err = State::load_validated(&rt.store, &missing).unwrap_err();
+        assert!(err.to_string().contains("not found"), "{}", err);
+
+        // State whose verifiers root points at a block that isn't there.
+        let mut st: State = rt.get_state();
+        st.verifiers = missing;
+        let root = rt.store.put_cbor(&st, Code::Blake2b256).unwrap();
+        let err = State::load_validated(&rt.store, &root).unwrap_err();
+        assert!(err.to_string().contains("failed to load verifiers"), "{}", err);
+    }
+
+    #[test]
+    fn walk_verifiers_in_pages() {
+        let (h, mut rt) = new_harness();
+        let allowance = verifier_allowance(&rt);
+        let mut expected: Vec<_> = (0..5).map(|i| Address::new_id(201 + i)).collect();
+        for verifier in &expected {
+            h.add_verifier(&mut rt, verifier, &allowance).unwrap();
+        }
+
+        let st: State = rt.get_state();
+        let mut seen = Vec::new();
+        let mut cursor = None;
+        let mut pages = 0;
+        loop {
+            let (page, next) =
+                st.for_each_verifier_limited(&rt.store, 2, cursor.as_deref()).unwrap();
+            pages += 1;
+            assert!(page.len() <= 2);
+            for (verifier, cap) in page {
+                assert_eq!(allowance, cap);
+                seen.push(verifier);
+            }
+            match next {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+        assert_eq!(3, pages);
+
+        seen.sort_by_key(Address::to_bytes);
+        expected.sort_by_key(Address::to_bytes);
+        assert_eq!(expected, seen);
+        assert!(st.for_each_verifier_limited(&rt.store, 0, None).is_err());
+    }
+
+    #[test]
+    fn walk_verifiers_rejects_unknown_cursor() {
+        let (h, mut rt) = new_harness();
+        let allowance = verifier_allowance(&rt);
+        h.add_verifier(&mut rt, &VERIFIER, &allowance).unwrap();
+
+        let st: State = rt.get_state();
+        let unknown = VERIFIER2.to_bytes();
+        assert!(st.for_each_verifier_limited(&rt.store, 2, Some(&unknown)).is_err());
+
+        // The last verifier is a valid cursor that ends the walk.
+        let last = VERIFIER.to_bytes();
+        let (page, next) = st.for_each_verifier_limited(&rt.store, 2, Some(&last)).unwrap();
+        assert!(page.is_empty());
+        assert_eq!(None, next);
+    }
+
+    #[test]
+    fn verifiers_with_at_least_threshold() {
+        let (h, mut rt) = new_harness();
+        let base = verifier_allowance(&rt);
+        for (i, verifier) in [*VERIFIER, *VERIFIER2, Address::new_id(203)].iter().enumerate() {
+            h.add_verifier(&mut rt, verifier, &(&base + i as u64)).unwrap();
+        }
+
+        let st: State = rt.get_state();
+        let at_least = |threshold: DataCap| -> Vec<Address> {
+            let found = st.verifiers_with_at_least(&rt.store, &threshold).unwrap();
+            found.into_iter().map(|(verifier, _)| verifier).collect()
+        };
+        assert_eq!(vec![*VERIFIER, *VERIFIER2, Address::new_id(203)], at_least(base.clone()));
+        assert_eq!(vec![*VERIFIER2, Address::new_id(203)], at_least(&base + 1));
+        assert_eq!(vec![Address::new_id(203)], at_least(&base + 2));
+        assert!(at_least(&base + 3).is_empty());
+
+        let found = st.verifiers_with_at_least(&rt.store, &(&base + 1)).unwrap();
+        assert_eq!(vec![(*VERIFIER2, &base + 1), (Address::new_id(203), &base + 2)], found);
+    }
+
+    #[test]
+    fn list_proposal_ids() {
+        let (h, mut rt) = new_harness();
+        let verifier3 = Address::new_id(203);
+        let allowance = verifier_allowance(&rt);
+        let client_allowance = client_allowance(&rt);
+        h.add_verifier_and_client(&mut rt, &VERIFIER, &CLIENT, &allowance, &client_allowance);
+        h.add_verifier_and_client(&mut rt, &VERIFIER2, &CLIENT2, &allowance, &client_allowance);
+        h.add_verifier(&mut rt, &verifier3, &allowance).unwrap();
+        let st: State = rt.get_state();
+        assert!(st.list_proposal_ids(&rt.store).unwrap().is_empty());
+
+        let amount = DataCap::from(1);
+        h.remove_client_datacap(&mut rt, &CLIENT, &amount, &VERIFIER, &VERIFIER2).unwrap();
+        h.remove_client_datacap(&mut rt, &CLIENT, &amount, &VERIFIER, &VERIFIER2).unwrap();
+        h.remove_client_datacap(&mut rt, &CLIENT2, &amount, &VERIFIER2, &verifier3).unwrap();
+
+        let st: State = rt.get_state();
+        assert_eq!(
+            vec![
+                (*VERIFIER, *CLIENT, 2),
+                (*VERIFIER2, *CLIENT, 2),
+                (*VERIFIER2, *CLIENT2, 1),
+                (verifier3, *CLIENT2, 1),
+            ],
+            st.list_proposal_ids(&rt.store).unwrap()
+        );
+    }
+
+    #[test]
+    fn rebuild_verifiers_after_churn() {
+        let (h, mut rt) = new_harness();
+        let allowance = verifier_allowance(&rt);
+        let verifiers: Vec<_> = (0..20).map(|i| Address::new_id(201 + i)).collect();
+        for verifier in &verifiers {
+            h.add_verifier(&mut rt, verifier, &allowance).unwrap();
+        }
+        for verifier in verifiers.iter().step_by(2) {
+            h.remove_verifier(&mut rt, verifier).unwrap();
+        }
+
+        let mut st: State = rt.get_state();
+        let old_root = st.verifiers;
+        let before = st.export_verifiers(&rt.store).unwrap();
+        let root = st.rebuild_verifiers(&rt.store).unwrap();
+        assert_eq!(old_root, root);
+        assert_eq!(root, st.verifiers);
+        assert_eq!(before, st.export_verifiers(&rt.store).unwrap());
+        assert_eq!(10, before.len());
+        assert!(before.iter().all(|(_, cap)| *cap == allowance));
+
+        rt.replace_state(&st);
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn export_verifiers_is_sorted() {
+        let verifiers = vec![
+            Address::new_id(1 << 20),
+            Address::new_id(201),
+            Address::new_id(7),
+            Address::new_id(128),
+            Address::new_id(202),
+        ];
+
+        let mut exports = Vec::new();
+        for order in [verifiers.clone(), verifiers.iter().rev().cloned().collect()] {
+            let (h, mut rt) = new_harness();
+            let allowance = verifier_allowance(&rt);
+            for verifier in &order {
+                h.add_verifier(&mut rt, verifier, &allowance).unwrap();
+            }
+            let st: State = rt.get_state();
+            let exported = st.export_verifiers(&rt.store).unwrap();
+            let keys: Vec<_> = exported.iter().map(|(addr, _)| addr.to_bytes()).collect();
+            let mut sorted = keys.clone();
+            sorted.sort();
+            assert_eq!(sorted, keys);
+            exports.push(exported);
+        }
+        assert_eq!(exports[0], exports[1]);
+        assert_eq!(verifiers.len(), exports[0].len());
+    }
+
+    #[test]
+    fn unversioned_state_is_rejected() {
+        let store = MemoryBlockstore::new();
+        let empty_map = make_empty_map::<_, ()>(&store, HAMT_BIT_WIDTH).flush().unwrap();
+        // State as encoded before the version field was introduced.
+        let old = to_vec(&(*ROOT_ADDR, empty_map, empty_map, empty_map)).unwrap();
+        assert!(from_slice::<State>(&old).is_err());
+    }
+
+    #[test]
+    fn version_1_state_is_rejected() {
+        let store = MemoryBlockstore::new();
+        let empty_map = make_empty_map::<_, ()>(&store, HAMT_BIT_WIDTH).flush().unwrap();
+        let old = to_vec(&(*ROOT_ADDR, empty_map, empty_map, empty_map, 1u32)).unwrap();
+        assert!(from_slice::<State>(&old).is_err());
+    }
+
+    #[test]
+    fn load_validated_rejects_other_versions() {
+        let (_, rt) = new_harness();
+        let mut st: State = rt.get_state();
+        st.version = STATE_VERSION - 1;
+        let root = rt.store.put_cbor(&st, Code::Blake2b256).unwrap();
+        let err = State::load_validated(&rt.store, &root).unwrap_err();
+        assert!(err.to_string().contains("has version 1, expected 2"), "{}", err);
+    }
+}
+
 mod verifiers {
     use fvm_ipld_encoding::RawBytes;
     use fvm_shared::address::{Address, BLS_PUB_LEN};
+    use fvm_shared::bigint::bigint_ser::BigIntSer;
     use fvm_shared::econ::TokenAmount;
     use fvm_shared::error::ExitCode;
     use fvm_shared::{MethodNum, METHOD_SEND};
 
-    use fil_actor_verifreg::{Actor as VerifregActor, AddVerifierParams, Method};
+    use fil_actor_verifreg::{
+        Actor as VerifregActor, AddVerifierParams, DataCap, Method, ScaleAllVerifiersParams,
+        SetVerifierFrozenParams, State, MAX_DATACAP,
+    };
     use fil_actors_runtime::test_utils::*;
 
     use crate::*;
@@ -91,8 +331,10 @@ mod verifiers {
         let (h, mut rt) = new_harness();
         rt.expect_validate_caller_addr(vec![h.root]);
         rt.set_caller(*VERIFREG_ACTOR_CODE_ID, Address::new_id(501));
-        let params =
-            AddVerifierParams { address: Address::new_id(201), allowance: verifier_allowance(&rt) };
+        let params = AddVerifierParams {
+            address: Address::new_id(201),
+            allowance: non_neg(&verifier_allowance(&rt)),
+        };
         expect_abort(
             ExitCode::USR_FORBIDDEN,
             rt.call::<VerifregActor>(
@@ -223,513 +465,2474 @@ mod verifiers {
         h.remove_verifier(&mut rt, &VERIFIER).unwrap();
         h.check_state(&rt);
     }
-}
 
-mod clients {
-    use fvm_ipld_encoding::RawBytes;
-    use fvm_shared::address::{Address, BLS_PUB_LEN};
-    use fvm_shared::econ::TokenAmount;
-    use fvm_shared::error::ExitCode;
-    use fvm_shared::{MethodNum, METHOD_SEND};
+    #[test]
+    fn total_verifier_allowance() {
+        let (h, mut rt) = new_harness();
+        assert_eq!(DataCap::from(0), h.total_verifier_allowance(&mut rt));
 
-    use fil_actor_verifreg::{Actor as VerifregActor, AddVerifierClientParams, DataCap, Method};
-    use fil_actors_runtime::test_utils::*;
+        let allowance = verifier_allowance(&rt);
+        let allowance2 = allowance.clone() * 2u64;
+        let allowance3 = allowance.clone() + 7;
+        h.add_verifier(&mut rt, &VERIFIER, &allowance).unwrap();
+        h.add_verifier(&mut rt, &VERIFIER2, &allowance2).unwrap();
+        h.add_verifier(&mut rt, &Address::new_id(203), &allowance3).unwrap();
+        assert_eq!(
+            allowance.clone() + allowance2 + allowance3,
+            h.total_verifier_allowance(&mut rt)
+        );
 
-    use crate::*;
-    use harness::*;
-    use util::*;
+        // Allowance granted to a client no longer counts towards the total.
+        let client_allowance = client_allowance(&rt);
+        h.add_client(&mut rt, &VERIFIER, &CLIENT, &client_allowance, &client_allowance).unwrap();
+        assert_eq!(
+            allowance.clone() * 4u64 + 7 - client_allowance,
+            h.total_verifier_allowance(&mut rt)
+        );
+        h.check_state(&rt);
+    }
 
     #[test]
-    fn many_verifiers_and_clients() {
+    fn burn_verifier_allowance() {
         let (h, mut rt) = new_harness();
-        // Each verifier has enough allowance for two clients.
-        let allowance_client = client_allowance(&rt);
-        let allowance_verifier = allowance_client.clone() + allowance_client.clone();
-        h.add_verifier(&mut rt, &VERIFIER, &allowance_verifier).unwrap();
-        h.add_verifier(&mut rt, &VERIFIER2, &allowance_verifier).unwrap();
+        let allowance = verifier_allowance(&rt);
+        h.add_verifier(&mut rt, &VERIFIER, &allowance).unwrap();
+        h.add_verifier(&mut rt, &VERIFIER2, &allowance).unwrap();
+
+        h.burn_verifier_allowance(&mut rt, &VERIFIER).unwrap();
+        let st: State = rt.get_state();
+        assert_eq!(allowance, st.total_burned_allowance);
+        h.assert_verifier_allowance(&rt, &VERIFIER2, &allowance);
+
+        // Burning an already-empty verifier leaves the total unchanged.
+        h.burn_verifier_allowance(&mut rt, &VERIFIER).unwrap();
+        h.burn_verifier_allowance(&mut rt, &VERIFIER2).unwrap();
+        let st: State = rt.get_state();
+        assert_eq!(allowance * 2, st.total_burned_allowance);
+        h.check_state(&rt);
+    }
 
-        h.add_client(&mut rt, &VERIFIER, &CLIENT, &allowance_client, &allowance_client).unwrap();
-        h.add_client(&mut rt, &VERIFIER, &CLIENT2, &allowance_client, &allowance_client).unwrap();
+    #[test]
+    fn burn_verifier_allowance_requires_root_caller() {
+        let (h, mut rt) = new_harness();
+        h.add_verifier(&mut rt, &VERIFIER, &verifier_allowance(&rt)).unwrap();
+        rt.expect_validate_caller_addr(vec![h.root]);
+        rt.set_caller(*VERIFREG_ACTOR_CODE_ID, Address::new_id(501));
+        expect_abort(
+            ExitCode::USR_FORBIDDEN,
+            rt.call::<VerifregActor>(
+                Method::BurnVerifierAllowance as MethodNum,
+                &RawBytes::serialize(*VERIFIER).unwrap(),
+            ),
+        );
+        h.check_state(&rt);
+    }
 
-        h.add_client(&mut rt, &VERIFIER2, &CLIENT3, &allowance_client, &allowance_client).unwrap();
-        h.add_client(&mut rt, &VERIFIER2, &CLIENT4, &allowance_client, &allowance_client).unwrap();
+    #[test]
+    fn burn_verifier_allowance_requires_verifier() {
+        let (h, mut rt) = new_harness();
+        expect_abort(ExitCode::USR_NOT_FOUND, h.burn_verifier_allowance(&mut rt, &VERIFIER));
+        h.check_state(&rt);
+    }
 
-        // all clients should exist and verifiers should have no more allowance left
-        h.assert_client_allowance(&rt, &CLIENT, &allowance_client);
-        h.assert_client_allowance(&rt, &CLIENT2, &allowance_client);
-        h.assert_client_allowance(&rt, &CLIENT3, &allowance_client);
-        h.assert_client_allowance(&rt, &CLIENT4, &allowance_client);
-        h.assert_verifier_allowance(&rt, &VERIFIER, &DataCap::from(0));
-        h.assert_verifier_allowance(&rt, &VERIFIER2, &DataCap::from(0));
+    #[test]
+    fn decrease_verifier_allowance() {
+        let (h, mut rt) = new_harness();
+        let allowance = verifier_allowance(&rt);
+        h.add_verifier(&mut rt, &VERIFIER, &allowance).unwrap();
+
+        let new_cap =
+            h.decrease_verifier_allowance(&mut rt, &VERIFIER, &DataCap::from(42)).unwrap();
+        assert_eq!(allowance.clone() - 42, new_cap);
+
+        // Decreasing by the entire remaining allowance leaves the verifier with zero.
+        let new_cap = h.decrease_verifier_allowance(&mut rt, &VERIFIER, &new_cap).unwrap();
+        assert_eq!(DataCap::from(0), new_cap);
         h.check_state(&rt);
     }
 
     #[test]
-    fn verifier_allowance_exhausted() {
+    fn decrease_verifier_allowance_rejects_over_decrease() {
         let (h, mut rt) = new_harness();
-        let allowance = client_allowance(&rt);
-        // Verifier only has allowance for one client.
+        let allowance = verifier_allowance(&rt);
         h.add_verifier(&mut rt, &VERIFIER, &allowance).unwrap();
 
-        h.add_client(&mut rt, &VERIFIER, &CLIENT, &allowance, &allowance).unwrap();
         expect_abort(
             ExitCode::USR_ILLEGAL_ARGUMENT,
-            h.add_client(&mut rt, &VERIFIER, &CLIENT2, &allowance, &allowance),
+            h.decrease_verifier_allowance(&mut rt, &VERIFIER, &(allowance.clone() + 1)),
         );
+        rt.reset();
 
-        // One client should exist and verifier should have no more allowance left.
-        h.assert_client_allowance(&rt, &CLIENT, &allowance);
-        h.assert_verifier_allowance(&rt, &VERIFIER, &DataCap::from(0));
+        // A negative amount can't be decoded as parameters at all.
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, h.root);
+        let params = RawBytes::serialize((*VERIFIER, BigIntSer(&DataCap::from(-1)))).unwrap();
+        expect_abort(
+            ExitCode::USR_SERIALIZATION,
+            rt.call::<VerifregActor>(Method::DecreaseVerifierAllowance as MethodNum, &params),
+        );
+        h.assert_verifier_allowance(&rt, &VERIFIER, &allowance);
         h.check_state(&rt);
     }
 
     #[test]
-    fn resolves_client_address() {
+    fn transfer_allowance() {
         let (h, mut rt) = new_harness();
-        let allowance_verifier = verifier_allowance(&rt);
-        let allowance_client = client_allowance(&rt);
-
-        let client_pubkey = Address::new_bls(&[7u8; BLS_PUB_LEN]).unwrap();
-        rt.id_addresses.insert(client_pubkey, *CLIENT);
-
-        h.add_verifier(&mut rt, &VERIFIER, &allowance_verifier).unwrap();
-        h.add_client(&mut rt, &VERIFIER, &client_pubkey, &allowance_client, &allowance_client)
-            .unwrap();
+        let allowance = verifier_allowance(&rt);
+        h.add_verifier(&mut rt, &VERIFIER, &allowance).unwrap();
+        h.add_verifier(&mut rt, &VERIFIER2, &allowance).unwrap();
 
-        // Adding another verified client with the same ID address increments
-        // the data cap which has already been granted.
-        h.add_verifier(&mut rt, &VERIFIER, &allowance_verifier).unwrap();
-        let expected_allowance = allowance_client.clone() + allowance_client.clone();
-        h.add_client(&mut rt, &VERIFIER, &CLIENT, &allowance_client, &expected_allowance).unwrap();
+        let amount = DataCap::from(40);
+        h.transfer_allowance(&mut rt, &VERIFIER, &VERIFIER2, &amount).unwrap();
+        h.assert_verifier_allowance(&rt, &VERIFIER, &(&allowance - &amount));
+        h.assert_verifier_allowance(&rt, &VERIFIER2, &(&allowance + &amount));
         h.check_state(&rt);
     }
 
     #[test]
-    fn minimum_allowance_ok() {
+    fn import_verifiers() {
         let (h, mut rt) = new_harness();
-        let allowance_verifier = verifier_allowance(&rt);
-        h.add_verifier(&mut rt, &VERIFIER, &allowance_verifier).unwrap();
-
-        let allowance = rt.policy.minimum_verified_deal_size.clone();
-        h.add_client(&mut rt, &VERIFIER, &CLIENT, &allowance, &allowance).unwrap();
+        let allowance = verifier_allowance(&rt);
+        let verifier3 = Address::new_id(203);
+        let imports = vec![
+            (*VERIFIER, allowance.clone()),
+            (*VERIFIER2, &allowance * 2),
+            (verifier3, &allowance * 3),
+        ];
+        h.import_verifiers(&mut rt, &imports).unwrap();
+
+        let st: State = rt.get_state();
+        assert_eq!(3, st.verifier_count(&rt.store).unwrap());
         h.check_state(&rt);
     }
 
     #[test]
-    fn rejects_unresolved_address() {
+    fn import_verifiers_rejects_duplicates() {
         let (h, mut rt) = new_harness();
-        let allowance_verifier = verifier_allowance(&rt);
-        let allowance_client = client_allowance(&rt);
-        h.add_verifier(&mut rt, &VERIFIER, &allowance_verifier).unwrap();
-
-        let client = Address::new_bls(&[7u8; BLS_PUB_LEN]).unwrap();
-        // Expect runtime to attempt to create the actor, but don't add it to the mock's
-        // address resolution table.
-        rt.expect_send(
-            client,
-            METHOD_SEND,
-            RawBytes::default(),
-            TokenAmount::default(),
-            RawBytes::default(),
-            ExitCode::OK,
+        let allowance = verifier_allowance(&rt);
+        expect_abort(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            h.import_verifiers(
+                &mut rt,
+                &[(*VERIFIER, allowance.clone()), (*VERIFIER, allowance.clone())],
+            ),
         );
+        rt.reset();
 
+        // An existing verifier also counts as a duplicate, and nothing is imported.
+        h.add_verifier(&mut rt, &VERIFIER, &allowance).unwrap();
         expect_abort(
-            ExitCode::USR_ILLEGAL_STATE,
-            h.add_client(&mut rt, &VERIFIER, &client, &allowance_client, &allowance_client),
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            h.import_verifiers(&mut rt, &[(*VERIFIER2, allowance.clone()), (*VERIFIER, allowance)]),
         );
+        rt.reset();
+        h.assert_verifier_removed(&rt, &VERIFIER2);
         h.check_state(&rt);
     }
 
     #[test]
-    fn rejects_allowance_below_minimum() {
+    fn frozen_verifier_cannot_grant() {
         let (h, mut rt) = new_harness();
         let allowance_verifier = verifier_allowance(&rt);
+        let allowance_client = client_allowance(&rt);
         h.add_verifier(&mut rt, &VERIFIER, &allowance_verifier).unwrap();
 
-        let allowance = rt.policy.minimum_verified_deal_size.clone() - 1;
+        h.set_verifier_frozen(&mut rt, &VERIFIER, true).unwrap();
         expect_abort(
-            ExitCode::USR_ILLEGAL_ARGUMENT,
-            h.add_client(&mut rt, &VERIFIER, &CLIENT, &allowance, &allowance),
+            ExitCode::USR_FORBIDDEN,
+            h.add_client(&mut rt, &VERIFIER, &CLIENT, &allowance_client, &allowance_client),
         );
+        rt.reset();
+        h.assert_verifier_allowance(&rt, &VERIFIER, &allowance_verifier);
+
+        h.set_verifier_frozen(&mut rt, &VERIFIER, false).unwrap();
+        h.add_client(&mut rt, &VERIFIER, &CLIENT, &allowance_client, &allowance_client).unwrap();
         h.check_state(&rt);
     }
 
     #[test]
-    fn rejects_non_verifier_caller() {
+    fn set_verifier_frozen_validates() {
         let (h, mut rt) = new_harness();
-        let allowance_verifier = verifier_allowance(&rt);
-        let allowance_client = client_allowance(&rt);
-        h.add_verifier(&mut rt, &VERIFIER, &allowance_verifier).unwrap();
+        let allowance = verifier_allowance(&rt);
+        expect_abort(ExitCode::USR_NOT_FOUND, h.set_verifier_frozen(&mut rt, &VERIFIER, true));
+        rt.reset();
 
-        let caller = Address::new_id(209);
-        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, caller);
-        rt.expect_validate_caller_any();
-        let params = AddVerifierClientParams { address: *CLIENT, allowance: allowance_client };
+        h.add_verifier(&mut rt, &VERIFIER, &allowance).unwrap();
+        rt.expect_validate_caller_addr(vec![h.root]);
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, *VERIFIER);
         expect_abort(
-            ExitCode::USR_NOT_FOUND,
+            ExitCode::USR_FORBIDDEN,
             rt.call::<VerifregActor>(
-                Method::AddVerifiedClient as MethodNum,
-                &RawBytes::serialize(params).unwrap(),
+                Method::SetVerifierFrozen as MethodNum,
+                &RawBytes::serialize(SetVerifierFrozenParams { verifier: *VERIFIER, frozen: true })
+                    .unwrap(),
             ),
         );
+        rt.reset();
+        let st: State = rt.get_state();
+        assert!(!st.is_verifier_frozen(&rt.store, &VERIFIER).unwrap());
+
+        // Removing a frozen verifier clears its flag.
+        h.set_verifier_frozen(&mut rt, &VERIFIER, true).unwrap();
+        h.remove_verifier(&mut rt, &VERIFIER).unwrap();
+        let st: State = rt.get_state();
+        assert!(!st.is_verifier_frozen(&rt.store, &VERIFIER).unwrap());
         h.check_state(&rt);
     }
 
     #[test]
-    fn rejects_allowance_greater_than_verifier_cap() {
+    fn swap_verifier_allowances() {
         let (h, mut rt) = new_harness();
-        let allowance_verifier = verifier_allowance(&rt);
-        h.add_verifier(&mut rt, &VERIFIER, &allowance_verifier).unwrap();
+        let allowance = verifier_allowance(&rt);
+        let allowance2 = &allowance + 40;
+        h.add_verifier(&mut rt, &VERIFIER, &allowance).unwrap();
+        h.add_verifier(&mut rt, &VERIFIER2, &allowance2).unwrap();
 
-        let allowance = allowance_verifier.clone() + 1;
+        h.swap_verifier_allowances(&mut rt, &VERIFIER, &VERIFIER2).unwrap();
+        h.assert_verifier_allowance(&rt, &VERIFIER, &allowance2);
+        h.assert_verifier_allowance(&rt, &VERIFIER2, &allowance);
+
+        // Both must be verifiers, and distinct.
+        expect_abort(
+            ExitCode::USR_NOT_FOUND,
+            h.swap_verifier_allowances(&mut rt, &VERIFIER, &CLIENT),
+        );
+        rt.reset();
         expect_abort(
             ExitCode::USR_ILLEGAL_ARGUMENT,
-            h.add_client(&mut rt, &VERIFIER, &h.root, &allowance, &allowance),
+            h.swap_verifier_allowances(&mut rt, &VERIFIER, &VERIFIER),
         );
+        rt.reset();
+        h.assert_verifier_allowance(&rt, &VERIFIER, &allowance2);
         h.check_state(&rt);
     }
 
     #[test]
-    fn rejects_root_as_client() {
+    fn transfer_allowance_capped_at_max() {
         let (h, mut rt) = new_harness();
-        let allowance_verifier = verifier_allowance(&rt);
-        let allowance_client = client_allowance(&rt);
-        h.add_verifier(&mut rt, &VERIFIER, &allowance_verifier).unwrap();
+        let allowance = verifier_allowance(&rt);
+        h.add_verifier(&mut rt, &VERIFIER, &allowance).unwrap();
+        h.add_verifier(&mut rt, &VERIFIER2, &(MAX_DATACAP.clone() - 1)).unwrap();
+
         expect_abort(
             ExitCode::USR_ILLEGAL_ARGUMENT,
-            h.add_client(&mut rt, &VERIFIER, &h.root, &allowance_client, &allowance_client),
+            h.transfer_allowance(&mut rt, &VERIFIER, &VERIFIER2, &DataCap::from(2)),
         );
+        rt.reset();
+        h.transfer_allowance(&mut rt, &VERIFIER, &VERIFIER2, &DataCap::from(1)).unwrap();
+        h.assert_verifier_allowance(&rt, &VERIFIER2, &MAX_DATACAP);
         h.check_state(&rt);
     }
 
     #[test]
-    fn rejects_verifier_as_client() {
+    fn transfer_allowance_rejects_insufficient_allowance() {
         let (h, mut rt) = new_harness();
-        let allowance_verifier = verifier_allowance(&rt);
-        let allowance_client = client_allowance(&rt);
-        h.add_verifier(&mut rt, &VERIFIER, &allowance_verifier).unwrap();
+        let allowance = verifier_allowance(&rt);
+        h.add_verifier(&mut rt, &VERIFIER, &allowance).unwrap();
+        h.add_verifier(&mut rt, &VERIFIER2, &allowance).unwrap();
+
         expect_abort(
             ExitCode::USR_ILLEGAL_ARGUMENT,
-            h.add_client(&mut rt, &VERIFIER, &VERIFIER, &allowance_client, &allowance_client),
+            h.transfer_allowance(&mut rt, &VERIFIER, &VERIFIER2, &(&allowance + 1)),
         );
-
-        h.add_verifier(&mut rt, &VERIFIER2, &allowance_verifier).unwrap();
+        rt.reset();
         expect_abort(
             ExitCode::USR_ILLEGAL_ARGUMENT,
-            h.add_client(&mut rt, &VERIFIER, &VERIFIER2, &allowance_client, &allowance_client),
+            h.transfer_allowance(&mut rt, &VERIFIER, &VERIFIER, &DataCap::from(1)),
         );
-
+        rt.reset();
+        expect_abort(
+            ExitCode::USR_NOT_FOUND,
+            h.transfer_allowance(&mut rt, &VERIFIER, &Address::new_id(299), &DataCap::from(1)),
+        );
+        rt.reset();
+        h.assert_verifier_allowance(&rt, &VERIFIER, &allowance);
+        h.assert_verifier_allowance(&rt, &VERIFIER2, &allowance);
         h.check_state(&rt);
     }
-}
 
-mod datacap {
-    use fvm_ipld_encoding::RawBytes;
-    use fvm_shared::address::Address;
-    use fvm_shared::error::ExitCode;
-    use fvm_shared::MethodNum;
+    #[test]
+    fn scale_all_verifiers() {
+        let (h, mut rt) = new_harness();
+        let allowance = verifier_allowance(&rt);
+        let allowance2 = &allowance + 7;
+        h.add_verifier_and_client(&mut rt, &VERIFIER, &CLIENT, &allowance, &client_allowance(&rt));
+        h.add_verifier(&mut rt, &VERIFIER2, &allowance2).unwrap();
+        let remaining = h.get_verifier_allowance(&rt, &VERIFIER);
+
+        h.scale_all_verifiers(&mut rt, 2, 1).unwrap();
+        h.assert_verifier_allowance(&rt, &VERIFIER, &(&remaining * 2u64));
+        h.assert_verifier_allowance(&rt, &VERIFIER2, &(&allowance2 * 2u64));
+        // Clients are untouched.
+        h.assert_client_allowance(&rt, &CLIENT, &client_allowance(&rt));
+
+        // Scaling down rounds towards zero.
+        h.scale_all_verifiers(&mut rt, 1, 4).unwrap();
+        h.assert_verifier_allowance(&rt, &VERIFIER, &(&remaining / 2u64));
+        h.assert_verifier_allowance(&rt, &VERIFIER2, &((&allowance2 * 2u64) / 4u64));
+        h.check_state(&rt);
+    }
 
-    use fil_actor_verifreg::{Actor as VerifregActor, Method, RestoreBytesParams, UseBytesParams};
-    use fil_actors_runtime::test_utils::*;
-    use fil_actors_runtime::{STORAGE_MARKET_ACTOR_ADDR, STORAGE_POWER_ACTOR_ADDR};
+    #[test]
+    fn scale_all_verifiers_rejects_overflow() {
+        let (h, mut rt) = new_harness();
+        let allowance = verifier_allowance(&rt);
+        let large = &*MAX_DATACAP / 2u64 + 1u64;
+        h.add_verifier(&mut rt, &VERIFIER, &allowance).unwrap();
+        h.add_verifier(&mut rt, &VERIFIER2, &large).unwrap();
+
+        expect_abort(ExitCode::USR_ILLEGAL_ARGUMENT, h.scale_all_verifiers(&mut rt, 2, 1));
+        rt.reset();
+        h.assert_verifier_allowance(&rt, &VERIFIER, &allowance);
+        h.assert_verifier_allowance(&rt, &VERIFIER2, &large);
+
+        // Reaching exactly the maximum is allowed.
+        let max = &*MAX_DATACAP / 2u64;
+        h.remove_verifier(&mut rt, &VERIFIER2).unwrap();
+        h.add_verifier(&mut rt, &VERIFIER2, &max).unwrap();
+        h.scale_all_verifiers(&mut rt, 2, 1).unwrap();
+        h.assert_verifier_allowance(&rt, &VERIFIER2, &MAX_DATACAP);
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn scale_all_verifiers_rejects_invalid() {
+        let (h, mut rt) = new_harness();
+        let allowance = verifier_allowance(&rt);
+        h.add_verifier(&mut rt, &VERIFIER, &allowance).unwrap();
+
+        for (numer, denom) in [(1, 0), (0, 1)] {
+            expect_abort(
+                ExitCode::USR_ILLEGAL_ARGUMENT,
+                h.scale_all_verifiers(&mut rt, numer, denom),
+            );
+            rt.reset();
+        }
+
+        rt.expect_validate_caller_addr(vec![h.root]);
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, *VERIFIER);
+        expect_abort(
+            ExitCode::USR_FORBIDDEN,
+            rt.call::<VerifregActor>(
+                Method::ScaleAllVerifiers as MethodNum,
+                &RawBytes::serialize(ScaleAllVerifiersParams { numer: 2, denom: 1 }).unwrap(),
+            ),
+        );
+        h.assert_verifier_allowance(&rt, &VERIFIER, &allowance);
+        h.check_state(&rt);
+    }
+}
+
+mod clients {
+    use fvm_ipld_encoding::RawBytes;
+    use fvm_shared::address::{Address, BLS_PUB_LEN};
+    use fvm_shared::econ::TokenAmount;
+    use fvm_shared::error::ExitCode;
+    use fvm_shared::{MethodNum, METHOD_SEND};
+
+    use fil_actor_verifreg::{
+        Actor as VerifregActor, AddVerifiedClientForParams, AddVerifiedClientsParams,
+        AddVerifierClientParams, AddressRole, DataCap, Method,
+        MAX_ADD_VERIFIED_CLIENTS_PARAMS_SIZE, MAX_DATACAP,
+    };
+    use fil_actors_runtime::test_utils::*;
 
     use crate::*;
     use harness::*;
     use util::*;
 
     #[test]
-    fn consume_multiple_clients() {
+    fn many_verifiers_and_clients() {
         let (h, mut rt) = new_harness();
-        let allowance = rt.policy.minimum_verified_deal_size.clone() * 10;
+        // Each verifier has enough allowance for two clients.
+        let allowance_client = client_allowance(&rt);
+        let allowance_verifier = allowance_client.clone() + allowance_client.clone();
+        h.add_verifier(&mut rt, &VERIFIER, &allowance_verifier).unwrap();
+        h.add_verifier(&mut rt, &VERIFIER2, &allowance_verifier).unwrap();
 
-        let ca1 = rt.policy.minimum_verified_deal_size.clone() * 3;
-        h.add_verifier_and_client(&mut rt, &VERIFIER, &CLIENT, &allowance, &ca1);
-        let ca2 = rt.policy.minimum_verified_deal_size.clone() * 2;
-        h.add_verifier_and_client(&mut rt, &VERIFIER, &CLIENT2, &allowance, &ca2); // FIXME redundant verifier
-        let ca3 = rt.policy.minimum_verified_deal_size.clone() + 1;
-        h.add_verifier_and_client(&mut rt, &VERIFIER, &CLIENT3, &allowance, &ca3);
+        h.add_client(&mut rt, &VERIFIER, &CLIENT, &allowance_client, &allowance_client).unwrap();
+        h.add_client(&mut rt, &VERIFIER, &CLIENT2, &allowance_client, &allowance_client).unwrap();
 
-        let deal_size = rt.policy.minimum_verified_deal_size.clone();
-        h.use_bytes(&mut rt, &CLIENT, &deal_size).unwrap();
-        h.assert_client_allowance(&rt, &CLIENT, &(ca1.clone() - &deal_size));
+        h.add_client(&mut rt, &VERIFIER2, &CLIENT3, &allowance_client, &allowance_client).unwrap();
+        h.add_client(&mut rt, &VERIFIER2, &CLIENT4, &allowance_client, &allowance_client).unwrap();
 
-        h.use_bytes(&mut rt, &CLIENT2, &deal_size).unwrap();
-        h.assert_client_allowance(&rt, &CLIENT2, &(ca2 - &deal_size));
+        // all clients should exist and verifiers should have no more allowance left
+        h.assert_client_allowance(&rt, &CLIENT, &allowance_client);
+        h.assert_client_allowance(&rt, &CLIENT2, &allowance_client);
+        h.assert_client_allowance(&rt, &CLIENT3, &allowance_client);
+        h.assert_client_allowance(&rt, &CLIENT4, &allowance_client);
+        h.assert_verifier_allowance(&rt, &VERIFIER, &DataCap::from(0));
+        h.assert_verifier_allowance(&rt, &VERIFIER2, &DataCap::from(0));
+        h.check_state(&rt);
+    }
 
-        // Client 3 had less than minimum balance remaining.
-        h.use_bytes(&mut rt, &CLIENT3, &deal_size).unwrap();
-        h.assert_client_removed(&rt, &CLIENT3);
+    #[test]
+    fn verifier_utilization_tracks_grants() {
+        let (h, mut rt) = new_harness();
+        let allowance_client = client_allowance(&rt);
+        let allowance_verifier = allowance_client.clone() * 3;
+        h.add_verifier(&mut rt, &VERIFIER, &allowance_verifier).unwrap();
 
-        // Client 1 uses more bytes.
-        h.use_bytes(&mut rt, &CLIENT, &deal_size).unwrap();
-        h.assert_client_allowance(&rt, &CLIENT, &(ca1.clone() - &deal_size - &deal_size));
+        let utilization = h.get_verifier_utilization(&mut rt, &VERIFIER).unwrap();
+        assert_eq!(allowance_verifier, utilization.remaining);
+        assert_eq!(DataCap::from(0), utilization.granted);
 
-        // Client 2 uses more bytes, exhausting allocation
-        h.use_bytes(&mut rt, &CLIENT2, &deal_size).unwrap();
-        h.assert_client_removed(&rt, &CLIENT2);
+        h.add_client(&mut rt, &VERIFIER, &CLIENT, &allowance_client, &allowance_client).unwrap();
+        let utilization = h.get_verifier_utilization(&mut rt, &VERIFIER).unwrap();
+        assert_eq!(allowance_verifier.clone() - &allowance_client, utilization.remaining);
+        assert_eq!(allowance_client, utilization.granted);
+
+        h.add_client(&mut rt, &VERIFIER, &CLIENT2, &allowance_client, &allowance_client).unwrap();
+        let utilization = h.get_verifier_utilization(&mut rt, &VERIFIER).unwrap();
+        let granted = allowance_client.clone() + &allowance_client;
+        assert_eq!(allowance_verifier - &granted, utilization.remaining);
+        assert_eq!(granted, utilization.granted);
+
+        expect_abort(ExitCode::USR_NOT_FOUND, h.get_verifier_utilization(&mut rt, &VERIFIER2));
         h.check_state(&rt);
     }
 
     #[test]
-    fn consume_then_fail_exhausted() {
+    fn client_total_granted_accumulates() {
         let (h, mut rt) = new_harness();
-        let ve_allowance = rt.policy.minimum_verified_deal_size.clone() * 10;
-        let cl_allowance = rt.policy.minimum_verified_deal_size.clone() * 2;
-        h.add_verifier_and_client(&mut rt, &VERIFIER, &CLIENT, &ve_allowance, &cl_allowance);
+        let allowance_client = client_allowance(&rt);
+        h.add_verifier(&mut rt, &VERIFIER, &allowance_client).unwrap();
+        h.add_verifier(&mut rt, &VERIFIER2, &allowance_client).unwrap();
+        assert_eq!(DataCap::from(0), h.get_client_total_granted(&mut rt, &CLIENT));
 
-        // Use some allowance.
-        let deal_size = rt.policy.minimum_verified_deal_size.clone();
-        h.use_bytes(&mut rt, &CLIENT, &deal_size).unwrap();
+        h.add_client(&mut rt, &VERIFIER, &CLIENT, &allowance_client, &allowance_client).unwrap();
+        assert_eq!(allowance_client, h.get_client_total_granted(&mut rt, &CLIENT));
 
-        // Attempt to use more than remaining.
-        let deal_size = rt.policy.minimum_verified_deal_size.clone() + 2;
-        expect_abort(ExitCode::USR_ILLEGAL_ARGUMENT, h.use_bytes(&mut rt, &CLIENT, &deal_size));
-        h.check_state(&rt)
+        let total = allowance_client.clone() + &allowance_client;
+        h.add_client(&mut rt, &VERIFIER2, &CLIENT, &allowance_client, &total).unwrap();
+        assert_eq!(total, h.get_client_total_granted(&mut rt, &CLIENT));
+        assert_eq!(DataCap::from(0), h.get_client_total_granted(&mut rt, &CLIENT2));
+        h.check_state(&rt);
     }
 
     #[test]
-    fn consume_resolves_client_address() {
+    fn client_granters_lists_distinct_verifiers() {
         let (h, mut rt) = new_harness();
-        let allowance = rt.policy.minimum_verified_deal_size.clone();
+        let allowance_client = client_allowance(&rt);
+        let allowance_verifier = allowance_client.clone() * 2;
+        h.add_verifier(&mut rt, &VERIFIER, &allowance_verifier).unwrap();
+        h.add_verifier(&mut rt, &VERIFIER2, &allowance_verifier).unwrap();
+        assert!(h.get_client_granters(&mut rt, &CLIENT).is_empty());
 
-        h.add_verifier_and_client(&mut rt, &VERIFIER, &CLIENT, &allowance, &allowance);
+        h.add_client(&mut rt, &VERIFIER2, &CLIENT, &allowance_client, &allowance_client).unwrap();
+        let total = allowance_client.clone() * 2;
+        h.add_client(&mut rt, &VERIFIER, &CLIENT, &allowance_client, &total).unwrap();
+        // A repeat grant doesn't list the verifier twice.
+        let total = allowance_client.clone() * 3;
+        h.add_client(&mut rt, &VERIFIER2, &CLIENT, &allowance_client, &total).unwrap();
 
-        let client_pubkey = Address::new_secp256k1(&[3u8; 65]).unwrap();
+        assert_eq!(vec![*VERIFIER2, *VERIFIER], h.get_client_granters(&mut rt, &CLIENT));
+        assert!(h.get_client_granters(&mut rt, &CLIENT2).is_empty());
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn verifier_allowance_exhausted() {
+        let (h, mut rt) = new_harness();
+        let allowance = client_allowance(&rt);
+        // Verifier only has allowance for one client.
+        h.add_verifier(&mut rt, &VERIFIER, &allowance).unwrap();
+
+        h.add_client(&mut rt, &VERIFIER, &CLIENT, &allowance, &allowance).unwrap();
+        expect_abort(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            h.add_client(&mut rt, &VERIFIER, &CLIENT2, &allowance, &allowance),
+        );
+
+        // One client should exist and verifier should have no more allowance left.
+        h.assert_client_allowance(&rt, &CLIENT, &allowance);
+        h.assert_verifier_allowance(&rt, &VERIFIER, &DataCap::from(0));
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn resolves_client_address() {
+        let (h, mut rt) = new_harness();
+        let allowance_verifier = verifier_allowance(&rt);
+        let allowance_client = client_allowance(&rt);
+
+        let client_pubkey = Address::new_bls(&[7u8; BLS_PUB_LEN]).unwrap();
         rt.id_addresses.insert(client_pubkey, *CLIENT);
-        h.use_bytes(&mut rt, &client_pubkey, &allowance).unwrap();
-        h.check_state(&rt)
+
+        h.add_verifier(&mut rt, &VERIFIER, &allowance_verifier).unwrap();
+        h.add_client(&mut rt, &VERIFIER, &client_pubkey, &allowance_client, &allowance_client)
+            .unwrap();
+
+        // Adding another verified client with the same ID address increments
+        // the data cap which has already been granted.
+        h.add_verifier(&mut rt, &VERIFIER, &allowance_verifier).unwrap();
+        let expected_allowance = allowance_client.clone() + allowance_client.clone();
+        h.add_client(&mut rt, &VERIFIER, &CLIENT, &allowance_client, &expected_allowance).unwrap();
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn minimum_allowance_ok() {
+        let (h, mut rt) = new_harness();
+        let allowance_verifier = verifier_allowance(&rt);
+        h.add_verifier(&mut rt, &VERIFIER, &allowance_verifier).unwrap();
+
+        let allowance = rt.policy.minimum_verified_deal_size.clone();
+        h.add_client(&mut rt, &VERIFIER, &CLIENT, &allowance, &allowance).unwrap();
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn rejects_unresolved_address() {
+        let (h, mut rt) = new_harness();
+        let allowance_verifier = verifier_allowance(&rt);
+        let allowance_client = client_allowance(&rt);
+        h.add_verifier(&mut rt, &VERIFIER, &allowance_verifier).unwrap();
+
+        let client = Address::new_bls(&[7u8; BLS_PUB_LEN]).unwrap();
+        // Expect runtime to attempt to create the actor, but don't add it to the mock's
+        // address resolution table.
+        rt.expect_send(
+            client,
+            METHOD_SEND,
+            RawBytes::default(),
+            TokenAmount::default(),
+            RawBytes::default(),
+            ExitCode::OK,
+        );
+
+        expect_abort(
+            ExitCode::USR_ILLEGAL_STATE,
+            h.add_client(&mut rt, &VERIFIER, &client, &allowance_client, &allowance_client),
+        );
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn rejects_allowance_below_minimum() {
+        let (h, mut rt) = new_harness();
+        let allowance_verifier = verifier_allowance(&rt);
+        h.add_verifier(&mut rt, &VERIFIER, &allowance_verifier).unwrap();
+
+        let allowance = rt.policy.minimum_verified_deal_size.clone() - 1;
+        expect_abort(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            h.add_client(&mut rt, &VERIFIER, &CLIENT, &allowance, &allowance),
+        );
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn rejects_allowance_above_maximum() {
+        let (h, mut rt) = new_harness();
+        let allowance_verifier = MAX_DATACAP.clone() * 2u64;
+        h.add_verifier(&mut rt, &VERIFIER, &allowance_verifier).unwrap();
+
+        let allowance = MAX_DATACAP.clone() + 1;
+        expect_abort(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            h.add_client(&mut rt, &VERIFIER, &CLIENT, &allowance, &allowance),
+        );
+        h.assert_verifier_allowance(&rt, &VERIFIER, &allowance_verifier);
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn rejects_allowance_below_raised_minimum() {
+        let (h, mut rt) = new_harness();
+        let allowance_verifier = verifier_allowance(&rt) * 2;
+        h.add_verifier(&mut rt, &VERIFIER, &allowance_verifier).unwrap();
+
+        let minimum = rt.policy.minimum_verified_deal_size.clone() + 10;
+        h.set_minimum_allocation_size(&mut rt, &minimum).unwrap();
+
+        // The policy minimum is no longer enough.
+        let allowance = rt.policy.minimum_verified_deal_size.clone();
+        expect_abort(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            h.add_client(&mut rt, &VERIFIER, &CLIENT, &allowance, &allowance),
+        );
+
+        h.add_client(&mut rt, &VERIFIER, &CLIENT, &minimum, &minimum).unwrap();
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn set_minimum_allocation_size_requires_root_caller() {
+        let (h, mut rt) = new_harness();
+        let minimum = rt.policy.minimum_verified_deal_size.clone() + 10;
+
+        rt.expect_validate_caller_addr(vec![h.root]);
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, *VERIFIER);
+        expect_abort(
+            ExitCode::USR_FORBIDDEN,
+            rt.call::<VerifregActor>(
+                Method::SetMinimumAllocationSize as MethodNum,
+                &RawBytes::serialize(non_neg(&minimum)).unwrap(),
+            ),
+        );
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn client_datacap_capped_at_max() {
+        let (h, mut rt) = new_harness();
+        let allowance_verifier = MAX_DATACAP.clone() * 2u64;
+        h.add_verifier(&mut rt, &VERIFIER, &allowance_verifier).unwrap();
+
+        let minimum = rt.policy.minimum_verified_deal_size.clone();
+        let first = MAX_DATACAP.clone() - &minimum;
+        h.add_client(&mut rt, &VERIFIER, &CLIENT, &first, &first).unwrap();
+        // Topping up to exactly the maximum is allowed.
+        h.add_client(&mut rt, &VERIFIER, &CLIENT, &minimum, &MAX_DATACAP).unwrap();
+
+        expect_abort(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            h.add_client(&mut rt, &VERIFIER, &CLIENT, &minimum, &minimum),
+        );
+        h.assert_client_allowance(&rt, &CLIENT, &MAX_DATACAP);
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn add_clients_in_batch() {
+        let (h, mut rt) = new_harness();
+        let allowance_verifier = verifier_allowance(&rt) * 3u64;
+        h.add_verifier(&mut rt, &VERIFIER, &allowance_verifier).unwrap();
+
+        let clients = vec![
+            (*CLIENT, client_allowance(&rt)),
+            (*CLIENT2, client_allowance(&rt) + 1),
+            (*CLIENT3, rt.policy.minimum_verified_deal_size.clone()),
+        ];
+        h.add_clients(&mut rt, &VERIFIER, &clients).unwrap();
+
+        let mut granted = DataCap::from(0);
+        for (client, allowance) in &clients {
+            h.assert_client_allowance(&rt, client, allowance);
+            granted += allowance;
+        }
+        h.assert_verifier_allowance(&rt, &VERIFIER, &(allowance_verifier - granted));
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn add_clients_rejects_batch_over_allowance() {
+        let (h, mut rt) = new_harness();
+        let allowance_client = client_allowance(&rt);
+        let allowance_verifier = allowance_client.clone() * 2u64;
+        h.add_verifier(&mut rt, &VERIFIER, &allowance_verifier).unwrap();
+
+        // Each grant fits on its own, but the batch as a whole does not.
+        let clients: Vec<_> =
+            [*CLIENT, *CLIENT2, *CLIENT3].iter().map(|c| (*c, allowance_client.clone())).collect();
+        expect_abort(ExitCode::USR_ILLEGAL_ARGUMENT, h.add_clients(&mut rt, &VERIFIER, &clients));
+        h.assert_verifier_allowance(&rt, &VERIFIER, &allowance_verifier);
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn add_clients_rejects_oversized_batch() {
+        let (h, mut rt) = new_harness();
+        let allowance_client = rt.policy.minimum_verified_deal_size.clone();
+        let allowance_verifier = verifier_allowance(&rt);
+        h.add_verifier(&mut rt, &VERIFIER, &allowance_verifier).unwrap();
+
+        let clients: Vec<_> =
+            (0..8000).map(|i| (Address::new_id(1000 + i), allowance_client.clone())).collect();
+        let params = AddVerifiedClientsParams {
+            clients: clients
+                .iter()
+                .map(|(address, allowance)| AddVerifierClientParams {
+                    address: *address,
+                    allowance: non_neg(allowance),
+                })
+                .collect(),
+        };
+        let size = RawBytes::serialize(&params).unwrap().bytes().len();
+        assert_eq!(size, params.serialized_size());
+        assert!(size > MAX_ADD_VERIFIED_CLIENTS_PARAMS_SIZE);
+
+        expect_abort(ExitCode::USR_ILLEGAL_ARGUMENT, h.add_clients(&mut rt, &VERIFIER, &clients));
+        h.assert_verifier_allowance(&rt, &VERIFIER, &allowance_verifier);
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn rejects_non_verifier_caller() {
+        let (h, mut rt) = new_harness();
+        let allowance_verifier = verifier_allowance(&rt);
+        let allowance_client = client_allowance(&rt);
+        h.add_verifier(&mut rt, &VERIFIER, &allowance_verifier).unwrap();
+
+        let caller = Address::new_id(209);
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, caller);
+        rt.expect_validate_caller_any();
+        let params =
+            AddVerifierClientParams { address: *CLIENT, allowance: non_neg(&allowance_client) };
+        expect_abort(
+            ExitCode::USR_NOT_FOUND,
+            rt.call::<VerifregActor>(
+                Method::AddVerifiedClient as MethodNum,
+                &RawBytes::serialize(params).unwrap(),
+            ),
+        );
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn rejects_allowance_greater_than_verifier_cap() {
+        let (h, mut rt) = new_harness();
+        let allowance_verifier = verifier_allowance(&rt);
+        h.add_verifier(&mut rt, &VERIFIER, &allowance_verifier).unwrap();
+
+        let allowance = allowance_verifier.clone() + 1;
+        expect_abort(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            h.add_client(&mut rt, &VERIFIER, &h.root, &allowance, &allowance),
+        );
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn rejects_root_as_client() {
+        let (h, mut rt) = new_harness();
+        let allowance_verifier = verifier_allowance(&rt);
+        let allowance_client = client_allowance(&rt);
+        h.add_verifier(&mut rt, &VERIFIER, &allowance_verifier).unwrap();
+        expect_abort(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            h.add_client(&mut rt, &VERIFIER, &h.root, &allowance_client, &allowance_client),
+        );
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn rejects_verifier_as_client() {
+        let (h, mut rt) = new_harness();
+        let allowance_verifier = verifier_allowance(&rt);
+        let allowance_client = client_allowance(&rt);
+        h.add_verifier(&mut rt, &VERIFIER, &allowance_verifier).unwrap();
+        expect_abort(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            h.add_client(&mut rt, &VERIFIER, &VERIFIER, &allowance_client, &allowance_client),
+        );
+
+        h.add_verifier(&mut rt, &VERIFIER2, &allowance_verifier).unwrap();
+        expect_abort(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            h.add_client(&mut rt, &VERIFIER, &VERIFIER2, &allowance_client, &allowance_client),
+        );
+
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn address_role() {
+        let (h, mut rt) = new_harness();
+        let allowance_verifier = verifier_allowance(&rt);
+        let allowance_client = client_allowance(&rt);
+        h.add_verifier(&mut rt, &VERIFIER, &allowance_verifier).unwrap();
+        h.add_client(&mut rt, &VERIFIER, &CLIENT, &allowance_client, &allowance_client).unwrap();
+
+        assert_eq!(
+            AddressRole::Verifier(allowance_verifier - &allowance_client),
+            h.get_address_role(&mut rt, &VERIFIER)
+        );
+        assert_eq!(AddressRole::Client(allowance_client), h.get_address_role(&mut rt, &CLIENT));
+        assert_eq!(AddressRole::None, h.get_address_role(&mut rt, &CLIENT2));
+        // An address unknown to the runtime has no role either.
+        let unknown = Address::new_bls(&[7u8; BLS_PUB_LEN]).unwrap();
+        assert_eq!(AddressRole::None, h.get_address_role(&mut rt, &unknown));
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn add_client_for_named_verifier() {
+        let (h, mut rt) = new_harness();
+        let allowance_verifier = verifier_allowance(&rt);
+        let allowance_client = client_allowance(&rt);
+        h.add_verifier(&mut rt, &VERIFIER, &allowance_verifier).unwrap();
+        h.add_verifier(&mut rt, &VERIFIER2, &allowance_verifier).unwrap();
+
+        h.add_client_for(&mut rt, &VERIFIER2, &CLIENT, &allowance_client).unwrap();
+        h.assert_client_allowance(&rt, &CLIENT, &allowance_client);
+        h.assert_verifier_allowance(&rt, &VERIFIER, &allowance_verifier);
+        h.assert_verifier_allowance(&rt, &VERIFIER2, &(&allowance_verifier - &allowance_client));
+
+        // The named verifier's remaining allowance is insufficient for another grant.
+        expect_abort(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            h.add_client_for(&mut rt, &VERIFIER2, &CLIENT2, &allowance_client),
+        );
+        rt.reset();
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn add_client_for_requires_root_caller() {
+        let (h, mut rt) = new_harness();
+        let allowance_client = client_allowance(&rt);
+        h.add_verifier(&mut rt, &VERIFIER, &verifier_allowance(&rt)).unwrap();
+
+        rt.expect_validate_caller_addr(vec![h.root]);
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, *VERIFIER);
+        let params = AddVerifiedClientForParams {
+            verifier: *VERIFIER,
+            params: AddVerifierClientParams {
+                address: *CLIENT,
+                allowance: non_neg(&allowance_client),
+            },
+        };
+        expect_abort(
+            ExitCode::USR_FORBIDDEN,
+            rt.call::<VerifregActor>(
+                Method::AddVerifiedClientFor as MethodNum,
+                &RawBytes::serialize(params).unwrap(),
+            ),
+        );
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn add_client_for_requires_verifier() {
+        let (h, mut rt) = new_harness();
+        expect_abort(
+            ExitCode::USR_NOT_FOUND,
+            h.add_client_for(&mut rt, &VERIFIER, &CLIENT, &client_allowance(&rt)),
+        );
+        rt.reset();
+        h.check_state(&rt);
+    }
+}
+
+mod datacap {
+    use fvm_ipld_encoding::RawBytes;
+    use fvm_shared::address::Address;
+    use fvm_shared::error::ExitCode;
+    use fvm_shared::MethodNum;
+
+    use fil_actor_verifreg::{Actor as VerifregActor, Method, RestoreBytesParams, UseBytesParams};
+    use fil_actors_runtime::test_utils::*;
+    use fil_actors_runtime::{STORAGE_MARKET_ACTOR_ADDR, STORAGE_POWER_ACTOR_ADDR};
+
+    use crate::*;
+    use harness::*;
+    use util::*;
+
+    #[test]
+    fn consume_multiple_clients() {
+        let (h, mut rt) = new_harness();
+        let allowance = rt.policy.minimum_verified_deal_size.clone() * 10;
+
+        let ca1 = rt.policy.minimum_verified_deal_size.clone() * 3;
+        h.add_verifier_and_client(&mut rt, &VERIFIER, &CLIENT, &allowance, &ca1);
+        let ca2 = rt.policy.minimum_verified_deal_size.clone() * 2;
+        h.add_verifier_and_client(&mut rt, &VERIFIER, &CLIENT2, &allowance, &ca2); // FIXME redundant verifier
+        let ca3 = rt.policy.minimum_verified_deal_size.clone() + 1;
+        h.add_verifier_and_client(&mut rt, &VERIFIER, &CLIENT3, &allowance, &ca3);
+
+        let deal_size = rt.policy.minimum_verified_deal_size.clone();
+        h.use_bytes(&mut rt, &CLIENT, &deal_size).unwrap();
+        h.assert_client_allowance(&rt, &CLIENT, &(ca1.clone() - &deal_size));
+
+        h.use_bytes(&mut rt, &CLIENT2, &deal_size).unwrap();
+        h.assert_client_allowance(&rt, &CLIENT2, &(ca2 - &deal_size));
+
+        // Client 3 had less than minimum balance remaining.
+        h.use_bytes(&mut rt, &CLIENT3, &deal_size).unwrap();
+        h.assert_client_removed(&rt, &CLIENT3);
+
+        // Client 1 uses more bytes.
+        h.use_bytes(&mut rt, &CLIENT, &deal_size).unwrap();
+        h.assert_client_allowance(&rt, &CLIENT, &(ca1.clone() - &deal_size - &deal_size));
+
+        // Client 2 uses more bytes, exhausting allocation
+        h.use_bytes(&mut rt, &CLIENT2, &deal_size).unwrap();
+        h.assert_client_removed(&rt, &CLIENT2);
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn consume_then_fail_exhausted() {
+        let (h, mut rt) = new_harness();
+        let ve_allowance = rt.policy.minimum_verified_deal_size.clone() * 10;
+        let cl_allowance = rt.policy.minimum_verified_deal_size.clone() * 2;
+        h.add_verifier_and_client(&mut rt, &VERIFIER, &CLIENT, &ve_allowance, &cl_allowance);
+
+        // Use some allowance.
+        let deal_size = rt.policy.minimum_verified_deal_size.clone();
+        h.use_bytes(&mut rt, &CLIENT, &deal_size).unwrap();
+
+        // Attempt to use more than remaining.
+        let deal_size = rt.policy.minimum_verified_deal_size.clone() + 2;
+        expect_abort(ExitCode::USR_ILLEGAL_ARGUMENT, h.use_bytes(&mut rt, &CLIENT, &deal_size));
+        h.check_state(&rt)
+    }
+
+    #[test]
+    fn consume_resolves_client_address() {
+        let (h, mut rt) = new_harness();
+        let allowance = rt.policy.minimum_verified_deal_size.clone();
+
+        h.add_verifier_and_client(&mut rt, &VERIFIER, &CLIENT, &allowance, &allowance);
+
+        let client_pubkey = Address::new_secp256k1(&[3u8; 65]).unwrap();
+        rt.id_addresses.insert(client_pubkey, *CLIENT);
+        h.use_bytes(&mut rt, &client_pubkey, &allowance).unwrap();
+        h.check_state(&rt)
+    }
+
+    #[test]
+    fn consume_then_fail_removed() {
+        let (h, mut rt) = new_harness();
+        let allowance = rt.policy.minimum_verified_deal_size.clone();
+        h.add_verifier_and_client(&mut rt, &VERIFIER, &CLIENT, &allowance, &allowance);
+
+        // Use full allowance.
+        h.use_bytes(&mut rt, &CLIENT, &allowance).unwrap();
+        // Fail to use any more because client was removed.
+        expect_abort(ExitCode::USR_NOT_FOUND, h.use_bytes(&mut rt, &CLIENT, &allowance));
+        h.check_state(&rt)
+    }
+
+    #[test]
+    fn consume_requires_market_actor_caller() {
+        let (h, mut rt) = new_harness();
+        rt.expect_validate_caller_addr(vec![*STORAGE_MARKET_ACTOR_ADDR]);
+        rt.set_caller(*POWER_ACTOR_CODE_ID, *STORAGE_POWER_ACTOR_ADDR);
+        let params = UseBytesParams {
+            address: *CLIENT,
+            deal_size: rt.policy.minimum_verified_deal_size.clone(),
+        };
+        expect_abort(
+            ExitCode::USR_FORBIDDEN,
+            rt.call::<VerifregActor>(
+                Method::UseBytes as MethodNum,
+                &RawBytes::serialize(params).unwrap(),
+            ),
+        );
+        h.check_state(&rt)
+    }
+
+    #[test]
+    fn consume_requires_minimum_deal_size() {
+        let (h, mut rt) = new_harness();
+        let allowance_verifier = verifier_allowance(&rt);
+        let allowance_client = client_allowance(&rt);
+        h.add_verifier_and_client(
+            &mut rt,
+            &VERIFIER,
+            &CLIENT,
+            &allowance_verifier,
+            &allowance_client,
+        );
+
+        let deal_size = rt.policy.minimum_verified_deal_size.clone() - 1;
+        expect_abort(ExitCode::USR_ILLEGAL_ARGUMENT, h.use_bytes(&mut rt, &CLIENT, &deal_size));
+        h.check_state(&rt)
+    }
+
+    #[test]
+    fn consume_requires_client_exists() {
+        let (h, mut rt) = new_harness();
+        let min_deal_size = rt.policy.minimum_verified_deal_size.clone();
+        expect_abort(ExitCode::USR_NOT_FOUND, h.use_bytes(&mut rt, &CLIENT, &min_deal_size));
+        h.check_state(&rt)
+    }
+
+    #[test]
+    fn consume_requires_deal_size_below_allowance() {
+        let (h, mut rt) = new_harness();
+        let allowance_verifier = verifier_allowance(&rt);
+        let allowance_client = client_allowance(&rt);
+        h.add_verifier_and_client(
+            &mut rt,
+            &VERIFIER,
+            &CLIENT,
+            &allowance_verifier,
+            &allowance_client,
+        );
+
+        let deal_size = allowance_client.clone() + 1;
+        expect_abort(ExitCode::USR_ILLEGAL_ARGUMENT, h.use_bytes(&mut rt, &CLIENT, &deal_size));
+        h.check_state(&rt)
+    }
+
+    #[test]
+    fn restore_multiple_clients() {
+        let (h, mut rt) = new_harness();
+        let allowance = rt.policy.minimum_verified_deal_size.clone() * 10;
+
+        let ca1 = rt.policy.minimum_verified_deal_size.clone() * 3;
+        h.add_verifier_and_client(&mut rt, &VERIFIER, &CLIENT, &allowance, &ca1);
+        let ca2 = rt.policy.minimum_verified_deal_size.clone() * 2;
+        h.add_client(&mut rt, &VERIFIER, &CLIENT2, &ca2, &ca2).unwrap();
+        let ca3 = rt.policy.minimum_verified_deal_size.clone() + 1;
+        h.add_client(&mut rt, &VERIFIER, &CLIENT3, &ca3, &ca3).unwrap();
+
+        let deal_size = rt.policy.minimum_verified_deal_size.clone();
+        h.restore_bytes(&mut rt, &CLIENT, &deal_size).unwrap();
+        h.assert_client_allowance(&rt, &CLIENT, &(ca1.clone() + &deal_size));
+
+        h.restore_bytes(&mut rt, &CLIENT2, &deal_size).unwrap();
+        h.assert_client_allowance(&rt, &CLIENT2, &(ca2.clone() + &deal_size));
+
+        h.restore_bytes(&mut rt, &CLIENT3, &deal_size).unwrap();
+        h.assert_client_allowance(&rt, &CLIENT3, &(ca3.clone() + &deal_size));
+
+        // Clients 1 and 2 now use bytes.
+        h.use_bytes(&mut rt, &CLIENT, &deal_size).unwrap();
+        h.assert_client_allowance(&rt, &CLIENT, &ca1);
+
+        h.use_bytes(&mut rt, &CLIENT2, &deal_size).unwrap();
+        h.assert_client_allowance(&rt, &CLIENT2, &ca2);
+
+        // Restore bytes back to all clients
+        h.restore_bytes(&mut rt, &CLIENT, &deal_size).unwrap();
+        h.assert_client_allowance(&rt, &CLIENT, &(ca1.clone() + &deal_size));
+
+        h.restore_bytes(&mut rt, &CLIENT2, &deal_size).unwrap();
+        h.assert_client_allowance(&rt, &CLIENT2, &(ca2.clone() + &deal_size));
+
+        h.restore_bytes(&mut rt, &CLIENT3, &deal_size).unwrap();
+        h.assert_client_allowance(&rt, &CLIENT3, &(ca3.clone() + &deal_size + &deal_size));
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn restore_after_reducing_client_cap() {
+        let (h, mut rt) = new_harness();
+        let allowance = rt.policy.minimum_verified_deal_size.clone() * 2;
+        h.add_verifier_and_client(&mut rt, &VERIFIER, &CLIENT, &allowance, &allowance);
+
+        // Use half allowance.
+        let deal_size = rt.policy.minimum_verified_deal_size.clone();
+        h.use_bytes(&mut rt, &CLIENT, &deal_size).unwrap();
+        h.assert_client_allowance(&rt, &CLIENT, &rt.policy.minimum_verified_deal_size);
+
+        // Restore it.
+        h.restore_bytes(&mut rt, &CLIENT, &deal_size).unwrap();
+        h.assert_client_allowance(&rt, &CLIENT, &allowance);
+        h.check_state(&rt)
+    }
+
+    #[test]
+    fn restore_resolves_client_address() {
+        let (h, mut rt) = new_harness();
+        let allowance = rt.policy.minimum_verified_deal_size.clone() * 2;
+        h.add_verifier_and_client(&mut rt, &VERIFIER, &CLIENT, &allowance, &allowance);
+
+        // Use half allowance.
+        let deal_size = rt.policy.minimum_verified_deal_size.clone();
+        h.use_bytes(&mut rt, &CLIENT, &deal_size).unwrap();
+        h.assert_client_allowance(&rt, &CLIENT, &rt.policy.minimum_verified_deal_size);
+
+        let client_pubkey = Address::new_secp256k1(&[3u8; 65]).unwrap();
+        rt.id_addresses.insert(client_pubkey, *CLIENT);
+
+        // Restore to pubkey address.
+        h.restore_bytes(&mut rt, &client_pubkey, &deal_size).unwrap();
+        h.assert_client_allowance(&rt, &CLIENT, &allowance);
+        h.check_state(&rt)
+    }
+
+    #[test]
+    fn restore_after_removing_client() {
+        let (h, mut rt) = new_harness();
+        let allowance = rt.policy.minimum_verified_deal_size.clone() + 1;
+        h.add_verifier_and_client(&mut rt, &VERIFIER, &CLIENT, &allowance, &allowance);
+
+        // Use allowance.
+        let deal_size = rt.policy.minimum_verified_deal_size.clone();
+        h.use_bytes(&mut rt, &CLIENT, &deal_size).unwrap();
+        h.assert_client_removed(&rt, &CLIENT);
+
+        // Restore it. Client has only the restored bytes (lost the +1 in original allowance).
+        h.restore_bytes(&mut rt, &CLIENT, &deal_size).unwrap();
+        h.assert_client_allowance(&rt, &CLIENT, &deal_size);
+        h.check_state(&rt)
+    }
+
+    #[test]
+    fn restore_requires_market_actor_caller() {
+        let (h, mut rt) = new_harness();
+        rt.expect_validate_caller_addr(vec![*STORAGE_MARKET_ACTOR_ADDR]);
+        rt.set_caller(*POWER_ACTOR_CODE_ID, *STORAGE_POWER_ACTOR_ADDR);
+        let params = RestoreBytesParams {
+            address: *CLIENT,
+            deal_size: rt.policy.minimum_verified_deal_size.clone(),
+        };
+        expect_abort(
+            ExitCode::USR_FORBIDDEN,
+            rt.call::<VerifregActor>(
+                Method::RestoreBytes as MethodNum,
+                &RawBytes::serialize(params).unwrap(),
+            ),
+        );
+        h.check_state(&rt)
+    }
+
+    #[test]
+    fn restore_requires_minimum_deal_size() {
+        let (h, mut rt) = new_harness();
+        let allowance_verifier = verifier_allowance(&rt);
+        let allowance_client = client_allowance(&rt);
+        h.add_verifier_and_client(
+            &mut rt,
+            &VERIFIER,
+            &CLIENT,
+            &allowance_verifier,
+            &allowance_client,
+        );
+
+        let deal_size = rt.policy.minimum_verified_deal_size.clone() - 1;
+        expect_abort(ExitCode::USR_ILLEGAL_ARGUMENT, h.restore_bytes(&mut rt, &CLIENT, &deal_size));
+        h.check_state(&rt)
+    }
+
+    #[test]
+    fn restore_rejects_root() {
+        let (h, mut rt) = new_harness();
+        let deal_size = rt.policy.minimum_verified_deal_size.clone();
+        expect_abort(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            h.restore_bytes(&mut rt, &ROOT_ADDR, &deal_size),
+        );
+        h.check_state(&rt)
+    }
+
+    #[test]
+    fn restore_rejects_verifier() {
+        let (h, mut rt) = new_harness();
+        let allowance = verifier_allowance(&rt);
+        h.add_verifier(&mut rt, &VERIFIER, &allowance).unwrap();
+        let deal_size = rt.policy.minimum_verified_deal_size.clone();
+        expect_abort(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            h.restore_bytes(&mut rt, &VERIFIER, &deal_size),
+        );
+        h.check_state(&rt)
+    }
+}
+
+mod removal {
+    use fvm_ipld_encoding::RawBytes;
+    use fvm_shared::address::{Address, BLS_PUB_LEN};
+    use fvm_shared::crypto::signature::{Signature, SignatureType};
+    use fvm_shared::error::ExitCode;
+    use fvm_shared::{MethodNum, HAMT_BIT_WIDTH};
+
+    use fil_actor_verifreg::{
+        assert_datacap_eq, verify_remove_proposal_signature, Actor as VerifregActor, AddrPairKey,
+        DataCap, DataCapDirection, DataCapExt, GetClientProposalIdsReturn, GetRecentRemovalsReturn,
+        Method, RemovalLogEntry, RemoveDataCapParams, RemoveDataCapParamsV2, RemoveDataCapProposal,
+        RemoveDataCapProposalID, RemoveDataCapRequest, RevokeAllDataCapParams,
+        SetClientDataCapParams, State, StepwiseRemoveDataCapParams, SubmitRemovalApprovalParams,
+        VerifierClientKey, DEFAULT_REQUIRED_REMOVAL_APPROVERS, MAX_REMOVAL_REASON_LEN,
+        REMOVE_DATA_CAP_PROPOSAL_FORMAT_V1, REMOVE_DATA_CAP_PROPOSAL_FORMAT_V2,
+        REMOVE_DATA_CAP_PROPOSAL_FORMAT_V3, SIGNATURE_DOMAIN_SEPARATION_REMOVE_DATA_CAP,
+    };
+    use fil_actors_runtime::make_map_with_root_and_bitwidth;
+    use fil_actors_runtime::test_utils::*;
+
+    use crate::*;
+    use harness::*;
+    use util::*;
+
+    #[test]
+    fn addr_pair_key_round_trip() {
+        let bls = Address::new_bls(&[7u8; BLS_PUB_LEN]).unwrap();
+        let secp = Address::new_secp256k1(&[3u8; 65]).unwrap();
+        for (first, second) in [
+            (*VERIFIER, *CLIENT),
+            (Address::new_id(1 << 40), Address::new_id(0)),
+            (bls, *CLIENT),
+            (*VERIFIER, secp),
+        ] {
+            let key = AddrPairKey::new(first, second).to_bytes();
+            let decoded = AddrPairKey::from_bytes(&key).unwrap();
+            assert_eq!(first, decoded.first);
+            assert_eq!(second, decoded.second);
+        }
+        assert!(AddrPairKey::from_bytes(&[]).is_err());
+        assert!(AddrPairKey::from_bytes(&VERIFIER.to_bytes()).is_err());
+    }
+
+    #[test]
+    fn verifier_client_key_is_role_ordered() {
+        let key = VerifierClientKey::new(*VERIFIER, *CLIENT);
+        assert_eq!(AddrPairKey::new(*VERIFIER, *CLIENT).to_bytes(), key.to_bytes());
+        assert_ne!(VerifierClientKey::new(*CLIENT, *VERIFIER).to_bytes(), key.to_bytes());
+
+        let decoded = VerifierClientKey::from_bytes(&key.to_bytes()).unwrap();
+        assert_eq!(*VERIFIER, decoded.verifier);
+        assert_eq!(*CLIENT, decoded.client);
+    }
+
+    #[test]
+    fn client_proposal_ids() {
+        let (h, mut rt) = new_harness();
+        let verifier3 = Address::new_id(203);
+        let allowance = verifier_allowance(&rt);
+        let client_allowance = client_allowance(&rt);
+        h.add_verifier_and_client(&mut rt, &VERIFIER, &CLIENT, &allowance, &client_allowance);
+        h.add_verifier_and_client(&mut rt, &VERIFIER2, &CLIENT2, &allowance, &client_allowance);
+        h.add_verifier(&mut rt, &verifier3, &allowance).unwrap();
+        assert!(get_client_proposal_ids(&mut rt, &CLIENT).is_empty());
+
+        let amount = DataCap::from(1);
+        h.remove_client_datacap(&mut rt, &CLIENT, &amount, &VERIFIER, &VERIFIER2).unwrap();
+        h.remove_client_datacap(&mut rt, &CLIENT, &amount, &VERIFIER, &VERIFIER2).unwrap();
+        h.remove_client_datacap(&mut rt, &CLIENT2, &amount, &VERIFIER2, &verifier3).unwrap();
+
+        let mut ids = get_client_proposal_ids(&mut rt, &CLIENT);
+        ids.sort_by_key(|(verifier, _)| verifier.to_bytes());
+        assert_eq!(vec![(*VERIFIER, 2), (*VERIFIER2, 2)], ids);
+
+        let mut ids = get_client_proposal_ids(&mut rt, &CLIENT2);
+        ids.sort_by_key(|(verifier, _)| verifier.to_bytes());
+        assert_eq!(vec![(*VERIFIER2, 1), (verifier3, 1)], ids);
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn verifier_client_status() {
+        let (h, mut rt) = new_harness();
+        let allowance = verifier_allowance(&rt);
+        let client_allowance = client_allowance(&rt);
+        h.add_verifier_and_client(&mut rt, &VERIFIER, &CLIENT, &allowance, &client_allowance);
+        h.add_verifier(&mut rt, &VERIFIER2, &allowance).unwrap();
+
+        let amount = DataCap::from(1);
+        h.remove_client_datacap(&mut rt, &CLIENT, &amount, &VERIFIER, &VERIFIER2).unwrap();
+
+        let status = h.get_verifier_client_status(&mut rt, &VERIFIER, &CLIENT).unwrap();
+        assert_eq!(&allowance - &client_allowance, status.verifier_cap);
+        assert_eq!(1, status.proposal_id);
+        assert_eq!(&client_allowance - &amount, status.client_balance);
+
+        // A client with no datacap reports a zero balance.
+        let status = h.get_verifier_client_status(&mut rt, &VERIFIER2, &CLIENT2).unwrap();
+        assert_eq!(allowance, status.verifier_cap);
+        assert_eq!(0, status.proposal_id);
+        assert_eq!(DataCap::from(0), status.client_balance);
+
+        expect_abort(
+            ExitCode::USR_NOT_FOUND,
+            h.get_verifier_client_status(&mut rt, &CLIENT, &CLIENT),
+        );
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn prepare_removal_returns_current_proposal_ids() {
+        let (h, mut rt) = new_harness();
+        let verifier3 = Address::new_id(203);
+        let allowance = verifier_allowance(&rt);
+        let client_allowance = client_allowance(&rt);
+        h.add_verifier_and_client(&mut rt, &VERIFIER, &CLIENT, &allowance, &client_allowance);
+        h.add_verifier(&mut rt, &VERIFIER2, &allowance).unwrap();
+        h.add_verifier(&mut rt, &verifier3, &allowance).unwrap();
+
+        let amount = DataCap::from(1);
+        let ret = h.prepare_removal(&mut rt, &VERIFIER, &VERIFIER2, &CLIENT, &amount).unwrap();
+        assert_eq!(RemoveDataCapProposalID(0), ret.verifier1_proposal_id);
+        assert_eq!(RemoveDataCapProposalID(0), ret.verifier2_proposal_id);
+        assert!(!ret.deletes_client);
+
+        h.remove_client_datacap(&mut rt, &CLIENT, &amount, &VERIFIER, &VERIFIER2).unwrap();
+        let ret = h.prepare_removal(&mut rt, &VERIFIER, &verifier3, &CLIENT, &amount).unwrap();
+        let st: State = rt.get_state();
+        assert_eq!(
+            st.proposal_id(&rt.store, &VERIFIER, &CLIENT).unwrap(),
+            ret.verifier1_proposal_id
+        );
+        assert_eq!(
+            st.proposal_id(&rt.store, &verifier3, &CLIENT).unwrap(),
+            ret.verifier2_proposal_id
+        );
+        assert_eq!(RemoveDataCapProposalID(1), ret.verifier1_proposal_id);
+        assert_eq!(RemoveDataCapProposalID(0), ret.verifier2_proposal_id);
+
+        // Removing the whole remaining balance deletes the client.
+        let remaining = &client_allowance - &amount;
+        let ret = h.prepare_removal(&mut rt, &VERIFIER, &verifier3, &CLIENT, &remaining).unwrap();
+        assert!(ret.deletes_client);
+
+        expect_abort(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            h.prepare_removal(&mut rt, &VERIFIER, &VERIFIER, &CLIENT, &amount),
+        );
+        rt.reset();
+        expect_abort(
+            ExitCode::USR_NOT_FOUND,
+            h.prepare_removal(&mut rt, &VERIFIER, &VERIFIER2, &CLIENT2, &amount),
+        );
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn remove_datacap_with_current_format_version() {
+        let (h, mut rt) = new_harness();
+        let allowance = verifier_allowance(&rt);
+        let client_allowance = client_allowance(&rt);
+        h.add_verifier_and_client(&mut rt, &VERIFIER, &CLIENT, &allowance, &client_allowance);
+        h.add_verifier(&mut rt, &VERIFIER2, &allowance).unwrap();
+
+        let amount = DataCap::from(1);
+        let ret =
+            h.remove_client_datacap(&mut rt, &CLIENT, &amount, &VERIFIER, &VERIFIER2).unwrap();
+        assert_datacap_eq!(amount, ret.data_cap_removed);
+        assert_eq!(client_allowance.diff(&(&client_allowance - 1)), ret.data_cap_change);
+        assert_eq!(DataCapDirection::Decrease, ret.data_cap_change.direction);
+        h.assert_client_allowance(&rt, &CLIENT, &(client_allowance - 1));
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn remove_datacap_with_single_approver() {
+        let (h, mut rt) = new_harness();
+        let allowance = verifier_allowance(&rt);
+        let client_allowance = client_allowance(&rt);
+        h.add_verifier_and_client(&mut rt, &VERIFIER, &CLIENT, &allowance, &client_allowance);
+        let amount = DataCap::from(1);
+        expect_abort(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            h.remove_client_datacap_by(&mut rt, &CLIENT, &amount, &[*VERIFIER]),
+        );
+        rt.reset();
+
+        h.set_required_removal_approvers(&mut rt, 1).unwrap();
+        rt.set_epoch(100);
+        h.remove_client_datacap_by(&mut rt, &CLIENT, &amount, &[*VERIFIER]).unwrap();
+        h.assert_client_allowance(&rt, &CLIENT, &(&client_allowance - &amount));
+        assert_eq!(1, h.get_proposal_id(&rt, &VERIFIER, &CLIENT));
+        // The lone approver is the only one logged.
+        assert_eq!(
+            vec![RemovalLogEntry {
+                client: *CLIENT,
+                amount,
+                approvers: vec![*VERIFIER],
+                epoch: 100,
+                reason: None,
+            }],
+            get_recent_removals(&mut rt, 10)
+        );
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn remove_datacap_with_three_approvers() {
+        let (h, mut rt) = new_harness();
+        let verifier3 = Address::new_id(203);
+        let allowance = verifier_allowance(&rt);
+        let client_allowance = client_allowance(&rt);
+        h.add_verifier_and_client(&mut rt, &VERIFIER, &CLIENT, &allowance, &client_allowance);
+        h.add_verifier(&mut rt, &VERIFIER2, &allowance).unwrap();
+        h.add_verifier(&mut rt, &verifier3, &allowance).unwrap();
+        h.set_required_removal_approvers(&mut rt, 3).unwrap();
+
+        let amount = DataCap::from(1);
+        expect_abort(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            h.remove_client_datacap(&mut rt, &CLIENT, &amount, &VERIFIER, &VERIFIER2),
+        );
+        rt.reset();
+        // Three requests from only two distinct verifiers aren't enough either.
+        expect_abort(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            h.remove_client_datacap_by(
+                &mut rt,
+                &CLIENT,
+                &amount,
+                &[*VERIFIER, *VERIFIER2, *VERIFIER],
+            ),
+        );
+        rt.reset();
+        h.assert_client_allowance(&rt, &CLIENT, &client_allowance);
+
+        h.remove_client_datacap_by(&mut rt, &CLIENT, &amount, &[*VERIFIER, *VERIFIER2, verifier3])
+            .unwrap();
+        h.assert_client_allowance(&rt, &CLIENT, &(&client_allowance - &amount));
+        assert_eq!(
+            vec![*VERIFIER, *VERIFIER2, verifier3],
+            get_recent_removals(&mut rt, 10)[0].approvers
+        );
+        for verifier in [*VERIFIER, *VERIFIER2, verifier3] {
+            assert_eq!(1, h.get_proposal_id(&rt, &verifier, &CLIENT));
+        }
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn set_required_removal_approvers_validates() {
+        let (h, mut rt) = new_harness();
+        expect_abort(ExitCode::USR_ILLEGAL_ARGUMENT, h.set_required_removal_approvers(&mut rt, 0));
+        rt.reset();
+
+        rt.expect_validate_caller_addr(vec![h.root]);
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, *VERIFIER);
+        expect_abort(
+            ExitCode::USR_FORBIDDEN,
+            rt.call::<VerifregActor>(
+                Method::SetRequiredRemovalApprovers as MethodNum,
+                &RawBytes::serialize(1u64).unwrap(),
+            ),
+        );
+        rt.reset();
+        let st: State = rt.get_state();
+        assert_eq!(DEFAULT_REQUIRED_REMOVAL_APPROVERS, st.required_removal_approvers);
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn remove_datacap_bound_to_client_balance() {
+        let (h, mut rt) = new_harness();
+        let allowance = verifier_allowance(&rt);
+        let client_allowance = client_allowance(&rt);
+        h.add_verifier_and_client(&mut rt, &VERIFIER, &CLIENT, &allowance, &client_allowance);
+        h.add_verifier(&mut rt, &VERIFIER2, &allowance).unwrap();
+
+        let amount = DataCap::from(1);
+        let ret = h
+            .remove_client_datacap_v2(
+                &mut rt,
+                &CLIENT,
+                &amount,
+                &client_allowance,
+                &VERIFIER,
+                &VERIFIER2,
+            )
+            .unwrap();
+        assert_eq!(amount, ret.data_cap_removed);
+        h.assert_client_allowance(&rt, &CLIENT, &(&client_allowance - 1));
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn remove_datacap_rejects_stale_client_balance() {
+        let (h, mut rt) = new_harness();
+        let allowance = verifier_allowance(&rt) * 2u64;
+        let client_allowance = client_allowance(&rt);
+        h.add_verifier_and_client(&mut rt, &VERIFIER, &CLIENT, &allowance, &client_allowance);
+        h.add_verifier(&mut rt, &VERIFIER2, &allowance).unwrap();
+
+        // The verifiers sign against the current balance, which then grows before the
+        // removal is submitted.
+        let signed_balance = client_allowance.clone();
+        let live_balance = &client_allowance * 2u64;
+        h.add_client(&mut rt, &VERIFIER, &CLIENT, &client_allowance, &live_balance).unwrap();
+
+        expect_abort(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            h.remove_client_datacap_v2(
+                &mut rt,
+                &CLIENT,
+                &DataCap::from(1),
+                &signed_balance,
+                &VERIFIER,
+                &VERIFIER2,
+            ),
+        );
+        rt.reset();
+        h.assert_client_allowance(&rt, &CLIENT, &live_balance);
+        assert_eq!(0, h.get_proposal_id(&rt, &VERIFIER, &CLIENT));
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn frozen_verifier_cannot_approve_removal() {
+        let (h, mut rt) = new_harness();
+        let allowance = verifier_allowance(&rt);
+        let client_allowance = client_allowance(&rt);
+        h.add_verifier_and_client(&mut rt, &VERIFIER, &CLIENT, &allowance, &client_allowance);
+        h.add_verifier(&mut rt, &VERIFIER2, &allowance).unwrap();
+        h.set_verifier_frozen(&mut rt, &VERIFIER2, true).unwrap();
+        assert!(!h.can_jointly_remove(&mut rt, &VERIFIER, &VERIFIER2));
+
+        let amount = DataCap::from(1);
+        expect_abort(
+            ExitCode::USR_FORBIDDEN,
+            h.remove_client_datacap(&mut rt, &CLIENT, &amount, &VERIFIER, &VERIFIER2),
+        );
+        rt.reset();
+        h.assert_client_allowance(&rt, &CLIENT, &client_allowance);
+
+        h.set_verifier_frozen(&mut rt, &VERIFIER2, false).unwrap();
+        h.remove_client_datacap(&mut rt, &CLIENT, &amount, &VERIFIER, &VERIFIER2).unwrap();
+        h.assert_client_allowance(&rt, &CLIENT, &(&client_allowance - &amount));
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn verify_remove_proposal_signature_offline() {
+        let (h, mut rt) = new_harness();
+        let amount = DataCap::from(1);
+        let proposal = RemoveDataCapProposal {
+            verified_client: *CLIENT,
+            data_cap_amount: amount.clone(),
+            removal_proposal_id: RemoveDataCapProposalID(0),
+        };
+        let payload = [
+            SIGNATURE_DOMAIN_SEPARATION_REMOVE_DATA_CAP,
+            RawBytes::serialize(&proposal).unwrap().bytes(),
+        ]
+        .concat();
+        let request = h.sign_remove_request(&rt, &VERIFIER, &CLIENT, &amount);
+        assert_eq!(payload, request.signature.bytes);
+
+        rt.expect_verify_signature(ExpectedVerifySig {
+            sig: request.signature.clone(),
+            signer: *VERIFIER,
+            plaintext: payload.clone(),
+            result: Ok(()),
+        });
+        verify_remove_proposal_signature(&rt, &VERIFIER, &proposal, &request.signature).unwrap();
+
+        let mut tampered = request.signature;
+        tampered.bytes[0] ^= 1;
+        rt.expect_verify_signature(ExpectedVerifySig {
+            sig: tampered.clone(),
+            signer: *VERIFIER,
+            plaintext: payload,
+            result: Err(anyhow::anyhow!("invalid signature")),
+        });
+        expect_abort(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            verify_remove_proposal_signature(&rt, &VERIFIER, &proposal, &tampered),
+        );
+        rt.verify();
+    }
+
+    #[test]
+    fn removal_executes_on_second_approval() {
+        let (h, mut rt) = new_harness();
+        let allowance = verifier_allowance(&rt);
+        let client_allowance = client_allowance(&rt);
+        h.add_verifier_and_client(&mut rt, &VERIFIER, &CLIENT, &allowance, &client_allowance);
+        h.add_verifier(&mut rt, &VERIFIER2, &allowance).unwrap();
+
+        let amount = DataCap::from(2);
+        let ret = h.submit_removal_approval(&mut rt, &CLIENT, &amount, &VERIFIER, &[]).unwrap();
+        assert_eq!(None, ret.removal);
+        h.assert_client_allowance(&rt, &CLIENT, &client_allowance);
+
+        // An approval of a different amount conflicts with the pending one.
+        expect_abort(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            h.submit_removal_approval(&mut rt, &CLIENT, &DataCap::from(1), &VERIFIER2, &[]),
+        );
+        rt.reset();
+        h.assert_client_allowance(&rt, &CLIENT, &client_allowance);
+
+        let ret =
+            h.submit_removal_approval(&mut rt, &CLIENT, &amount, &VERIFIER2, &[*VERIFIER]).unwrap();
+        let removal = ret.removal.unwrap();
+        assert_eq!(*CLIENT, removal.verified_client);
+        assert_eq!(amount, removal.data_cap_removed);
+        h.assert_client_allowance(&rt, &CLIENT, &(&client_allowance - &amount));
+        assert_eq!(1, h.get_proposal_id(&rt, &VERIFIER, &CLIENT));
+        assert_eq!(1, h.get_proposal_id(&rt, &VERIFIER2, &CLIENT));
+
+        // The executed approval is cleared, so the next one waits again.
+        let ret = h.submit_removal_approval(&mut rt, &CLIENT, &amount, &VERIFIER2, &[]).unwrap();
+        assert_eq!(None, ret.removal);
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn removal_approval_with_bad_signature_is_rejected() {
+        let (h, mut rt) = new_harness();
+        let allowance = verifier_allowance(&rt);
+        let client_allowance = client_allowance(&rt);
+        h.add_verifier_and_client(&mut rt, &VERIFIER, &CLIENT, &allowance, &client_allowance);
+        h.add_verifier(&mut rt, &VERIFIER2, &allowance).unwrap();
+
+        let amount = DataCap::from(1);
+        let request = h.sign_remove_request(&rt, &VERIFIER, &CLIENT, &amount);
+        let signature = Signature { sig_type: SignatureType::Secp256k1, bytes: b"bad".to_vec() };
+        rt.expect_verify_signature(ExpectedVerifySig {
+            sig: signature.clone(),
+            signer: *VERIFIER,
+            plaintext: request.signature.bytes,
+            result: Err(anyhow::anyhow!("invalid signature")),
+        });
+        rt.expect_validate_caller_addr(vec![h.root]);
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, h.root);
+        let params = SubmitRemovalApprovalParams {
+            verified_client_to_remove: *CLIENT,
+            data_cap_amount_to_remove: non_neg(&amount),
+            verifier_request: RemoveDataCapRequest { verifier: *VERIFIER, signature },
+        };
+        expect_abort(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            rt.call::<VerifregActor>(
+                Method::SubmitRemovalApproval as MethodNum,
+                &RawBytes::serialize(params).unwrap(),
+            ),
+        );
+        rt.reset();
+
+        // Nothing was stored, so the next approval waits rather than completing a removal.
+        let ret = h.submit_removal_approval(&mut rt, &CLIENT, &amount, &VERIFIER2, &[]).unwrap();
+        assert_eq!(None, ret.removal);
+        let ret =
+            h.submit_removal_approval(&mut rt, &CLIENT, &amount, &VERIFIER, &[*VERIFIER2]).unwrap();
+        assert_eq!(amount, ret.removal.unwrap().data_cap_removed);
+        h.assert_client_allowance(&rt, &CLIENT, &(&client_allowance - &amount));
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn stale_removal_approval_is_replaced() {
+        let (h, mut rt) = new_harness();
+        let allowance = verifier_allowance(&rt);
+        let client_allowance = client_allowance(&rt);
+        h.add_verifier_and_client(&mut rt, &VERIFIER, &CLIENT, &allowance, &client_allowance);
+        h.add_verifier(&mut rt, &VERIFIER2, &allowance).unwrap();
+
+        let amount = DataCap::from(1);
+        h.submit_removal_approval(&mut rt, &CLIENT, &amount, &VERIFIER, &[]).unwrap();
+        h.remove_verifier(&mut rt, &VERIFIER).unwrap();
+
+        // The removed verifier's approval can no longer execute, so this one replaces it.
+        let ret = h.submit_removal_approval(&mut rt, &CLIENT, &amount, &VERIFIER2, &[]).unwrap();
+        assert_eq!(None, ret.removal);
+        h.assert_client_allowance(&rt, &CLIENT, &client_allowance);
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn removal_approval_executes_alone_with_one_approver() {
+        let (h, mut rt) = new_harness();
+        let allowance = verifier_allowance(&rt);
+        let client_allowance = client_allowance(&rt);
+        h.add_verifier_and_client(&mut rt, &VERIFIER, &CLIENT, &allowance, &client_allowance);
+        h.set_required_removal_approvers(&mut rt, 1).unwrap();
+
+        let amount = DataCap::from(1);
+        let ret = h.submit_removal_approval(&mut rt, &CLIENT, &amount, &VERIFIER, &[]).unwrap();
+        assert_eq!(amount, ret.removal.unwrap().data_cap_removed);
+        h.assert_client_allowance(&rt, &CLIENT, &(&client_allowance - &amount));
+        assert_eq!(vec![*VERIFIER], get_recent_removals(&mut rt, 10)[0].approvers);
+        assert_eq!(1, h.get_proposal_id(&rt, &VERIFIER, &CLIENT));
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn removal_approval_executes_on_third_approval() {
+        let (h, mut rt) = new_harness();
+        let verifier3 = Address::new_id(203);
+        let allowance = verifier_allowance(&rt);
+        let client_allowance = client_allowance(&rt);
+        h.add_verifier_and_client(&mut rt, &VERIFIER, &CLIENT, &allowance, &client_allowance);
+        h.add_verifier(&mut rt, &VERIFIER2, &allowance).unwrap();
+        h.add_verifier(&mut rt, &verifier3, &allowance).unwrap();
+        h.set_required_removal_approvers(&mut rt, 3).unwrap();
+
+        let amount = DataCap::from(1);
+        let ret = h.submit_removal_approval(&mut rt, &CLIENT, &amount, &VERIFIER, &[]).unwrap();
+        assert_eq!(None, ret.removal);
+        // Approving again doesn't count as a second verifier.
+        let ret = h.submit_removal_approval(&mut rt, &CLIENT, &amount, &VERIFIER, &[]).unwrap();
+        assert_eq!(None, ret.removal);
+        let ret = h.submit_removal_approval(&mut rt, &CLIENT, &amount, &VERIFIER2, &[]).unwrap();
+        assert_eq!(None, ret.removal);
+        h.assert_client_allowance(&rt, &CLIENT, &client_allowance);
+
+        let ret = h
+            .submit_removal_approval(
+                &mut rt,
+                &CLIENT,
+                &amount,
+                &verifier3,
+                &[*VERIFIER, *VERIFIER2],
+            )
+            .unwrap();
+        assert_eq!(amount, ret.removal.unwrap().data_cap_removed);
+        h.assert_client_allowance(&rt, &CLIENT, &(&client_allowance - &amount));
+        assert_eq!(
+            vec![verifier3, *VERIFIER, *VERIFIER2],
+            get_recent_removals(&mut rt, 10)[0].approvers
+        );
+        for verifier in [*VERIFIER, *VERIFIER2, verifier3] {
+            assert_eq!(1, h.get_proposal_id(&rt, &verifier, &CLIENT));
+        }
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn batch_remove_datacap() {
+        let (h, mut rt) = new_harness();
+        let allowance = verifier_allowance(&rt);
+        let client_allowance = client_allowance(&rt);
+        h.add_verifier_and_client(&mut rt, &VERIFIER, &CLIENT, &allowance, &client_allowance);
+        h.add_verifier_and_client(&mut rt, &VERIFIER2, &CLIENT2, &allowance, &client_allowance);
+
+        let amount1 = DataCap::from(3);
+        let amount2 = DataCap::from(5);
+        let removals = vec![
+            h.make_remove_params(&rt, &CLIENT, &amount1, &VERIFIER, &VERIFIER2),
+            h.make_remove_params(&rt, &CLIENT2, &amount2, &VERIFIER, &VERIFIER2),
+        ];
+        let ret = h.batch_remove_client_datacap(&mut rt, removals).unwrap();
+        assert_eq!(
+            vec![(*CLIENT, amount1.clone()), (*CLIENT2, amount2.clone())],
+            ret.results
+                .into_iter()
+                .map(|r| (r.verified_client, r.data_cap_removed))
+                .collect::<Vec<_>>()
+        );
+        h.assert_client_allowance(&rt, &CLIENT, &(&client_allowance - &amount1));
+        h.assert_client_allowance(&rt, &CLIENT2, &(&client_allowance - &amount2));
+        for client in [*CLIENT, *CLIENT2] {
+            assert_eq!(1, h.get_proposal_id(&rt, &VERIFIER, &client));
+            assert_eq!(1, h.get_proposal_id(&rt, &VERIFIER2, &client));
+        }
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn stepwise_remove_datacap() {
+        let (h, mut rt) = new_harness();
+        let allowance = verifier_allowance(&rt);
+        let client_allowance = client_allowance(&rt);
+        h.add_verifier_and_client(&mut rt, &VERIFIER, &CLIENT, &allowance, &client_allowance);
+        h.add_verifier(&mut rt, &VERIFIER2, &allowance).unwrap();
+        h.remove_client_datacap(&mut rt, &CLIENT, &DataCap::from(1), &VERIFIER, &VERIFIER2)
+            .unwrap();
+
+        let amounts = [DataCap::from(3), DataCap::from(5)];
+        let ret = h
+            .stepwise_remove_client_datacap(&mut rt, &CLIENT, &amounts, &VERIFIER, &VERIFIER2)
+            .unwrap();
+        assert_eq!(
+            amounts.to_vec(),
+            ret.results.into_iter().map(|r| r.data_cap_removed).collect::<Vec<_>>()
+        );
+        h.assert_client_allowance(&rt, &CLIENT, &(&client_allowance - DataCap::from(9)));
+        assert_eq!(3, h.get_proposal_id(&rt, &VERIFIER, &CLIENT));
+        assert_eq!(3, h.get_proposal_id(&rt, &VERIFIER2, &CLIENT));
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn stepwise_remove_datacap_rejects_mixed_clients() {
+        let (h, mut rt) = new_harness();
+        let allowance = verifier_allowance(&rt);
+        let client_allowance = client_allowance(&rt);
+        h.add_verifier_and_client(&mut rt, &VERIFIER, &CLIENT, &allowance, &client_allowance);
+        h.add_verifier_and_client(&mut rt, &VERIFIER2, &CLIENT2, &allowance, &client_allowance);
+
+        let amount = DataCap::from(3);
+        let steps = vec![
+            h.make_remove_params(&rt, &CLIENT, &amount, &VERIFIER, &VERIFIER2),
+            h.make_remove_params(&rt, &CLIENT2, &amount, &VERIFIER, &VERIFIER2),
+        ];
+        rt.expect_validate_caller_addr(vec![h.root]);
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, h.root);
+        expect_abort(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            rt.call::<VerifregActor>(
+                Method::StepwiseRemoveDataCap as MethodNum,
+                &RawBytes::serialize(StepwiseRemoveDataCapParams { steps }).unwrap(),
+            ),
+        );
+        rt.reset();
+        h.assert_client_allowance(&rt, &CLIENT, &client_allowance);
+        h.assert_client_allowance(&rt, &CLIENT2, &client_allowance);
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn batch_remove_datacap_is_atomic() {
+        let (h, mut rt) = new_harness();
+        let allowance = verifier_allowance(&rt);
+        let client_allowance = client_allowance(&rt);
+        h.add_verifier_and_client(&mut rt, &VERIFIER, &CLIENT, &allowance, &client_allowance);
+        h.add_verifier_and_client(&mut rt, &VERIFIER2, &CLIENT2, &allowance, &client_allowance);
+
+        let amount = DataCap::from(3);
+        let valid = h.make_remove_params(&rt, &CLIENT, &amount, &VERIFIER, &VERIFIER2);
+        // The second removal carries a bad signature from the second verifier.
+        let proposal = RemoveDataCapProposal {
+            verified_client: *CLIENT2,
+            data_cap_amount: amount.clone(),
+            removal_proposal_id: RemoveDataCapProposalID(0),
+        };
+        let payload = [
+            SIGNATURE_DOMAIN_SEPARATION_REMOVE_DATA_CAP,
+            RawBytes::serialize(proposal).unwrap().bytes(),
+        ]
+        .concat();
+        let verifier_request_1 = h.make_remove_request(&rt, &VERIFIER, &CLIENT2, &amount);
+        let signature = Signature { sig_type: SignatureType::Secp256k1, bytes: b"bad".to_vec() };
+        rt.expect_verify_signature(ExpectedVerifySig {
+            sig: signature.clone(),
+            signer: *VERIFIER2,
+            plaintext: payload,
+            result: Err(anyhow::anyhow!("invalid signature")),
+        });
+        let invalid = RemoveDataCapParamsV2 {
+            verified_client_to_remove: *CLIENT2,
+            data_cap_amount_to_remove: amount.clone(),
+            verifier_request_1,
+            verifier_request_2: Some(RemoveDataCapRequest { verifier: *VERIFIER2, signature }),
+            format_version: REMOVE_DATA_CAP_PROPOSAL_FORMAT_V1,
+            expected_client_data_cap: DataCap::from(0),
+            additional_verifier_requests: vec![],
+            reason: None,
+        };
+        expect_abort(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            h.batch_remove_client_datacap(&mut rt, vec![valid, invalid]),
+        );
+        rt.reset();
+
+        // The valid first removal was not applied either.
+        h.assert_client_allowance(&rt, &CLIENT, &client_allowance);
+        h.assert_client_allowance(&rt, &CLIENT2, &client_allowance);
+        assert_eq!(0, h.get_proposal_id(&rt, &VERIFIER, &CLIENT));
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn remove_datacap_rejects_negative_amount() {
+        let (h, mut rt) = new_harness();
+        let allowance = verifier_allowance(&rt);
+        let client_allowance = client_allowance(&rt);
+        h.add_verifier_and_client(&mut rt, &VERIFIER, &CLIENT, &allowance, &client_allowance);
+        h.add_verifier(&mut rt, &VERIFIER2, &allowance).unwrap();
+
+        expect_abort(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            h.remove_client_datacap(&mut rt, &CLIENT, &DataCap::from(-1), &VERIFIER, &VERIFIER2),
+        );
+        rt.reset();
+        h.assert_client_allowance(&rt, &CLIENT, &client_allowance);
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn total_removed_accumulates() {
+        let (h, mut rt) = new_harness();
+        let allowance = verifier_allowance(&rt);
+        let client_allowance = client_allowance(&rt);
+        h.add_verifier_and_client(&mut rt, &VERIFIER, &CLIENT, &allowance, &client_allowance);
+        h.add_verifier(&mut rt, &VERIFIER2, &allowance).unwrap();
+        assert_eq!(DataCap::from(0), h.get_total_removed(&mut rt));
+
+        let first = DataCap::from(3);
+        let second = DataCap::from(5);
+        h.remove_client_datacap(&mut rt, &CLIENT, &first, &VERIFIER, &VERIFIER2).unwrap();
+        assert_eq!(first, h.get_total_removed(&mut rt));
+        h.remove_client_datacap(&mut rt, &CLIENT, &second, &VERIFIER, &VERIFIER2).unwrap();
+        assert_eq!(first + second, h.get_total_removed(&mut rt));
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn remove_datacap_with_reason() {
+        let (h, mut rt) = new_harness();
+        let allowance = verifier_allowance(&rt);
+        let client_allowance = client_allowance(&rt);
+        h.add_verifier_and_client(&mut rt, &VERIFIER, &CLIENT, &allowance, &client_allowance);
+        h.add_verifier(&mut rt, &VERIFIER2, &allowance).unwrap();
+
+        let amount = DataCap::from(1);
+        let reason = "client failed to retrieve data";
+        rt.set_epoch(100);
+        h.remove_client_datacap_v3(
+            &mut rt,
+            &CLIENT,
+            &amount,
+            &client_allowance,
+            &VERIFIER,
+            &VERIFIER2,
+            Some(reason),
+        )
+        .unwrap();
+        h.assert_client_allowance(&rt, &CLIENT, &(&client_allowance - &amount));
+        assert_eq!(
+            vec![RemovalLogEntry {
+                client: *CLIENT,
+                amount,
+                approvers: vec![*VERIFIER, *VERIFIER2],
+                epoch: 100,
+                reason: Some(reason.to_string()),
+            }],
+            get_recent_removals(&mut rt, 10)
+        );
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn remove_datacap_rejects_invalid_reason() {
+        let (h, mut rt) = new_harness();
+        let allowance = verifier_allowance(&rt);
+        let client_allowance = client_allowance(&rt);
+        h.add_verifier_and_client(&mut rt, &VERIFIER, &CLIENT, &allowance, &client_allowance);
+        h.add_verifier(&mut rt, &VERIFIER2, &allowance).unwrap();
+
+        // A reason sent with an earlier format isn't covered by the signatures.
+        let amount = DataCap::from(1);
+        let params = RemoveDataCapParamsV2 {
+            verified_client_to_remove: *CLIENT,
+            data_cap_amount_to_remove: amount.clone(),
+            verifier_request_1: h.make_remove_request_v2(
+                &rt,
+                &VERIFIER,
+                &CLIENT,
+                &amount,
+                &client_allowance,
+            ),
+            verifier_request_2: Some(h.make_remove_request_v2(
+                &rt,
+                &VERIFIER2,
+                &CLIENT,
+                &amount,
+                &client_allowance,
+            )),
+            format_version: REMOVE_DATA_CAP_PROPOSAL_FORMAT_V2,
+            expected_client_data_cap: client_allowance.clone(),
+            additional_verifier_requests: vec![],
+            reason: Some("unsigned".to_string()),
+        };
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, h.root);
+        expect_abort(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            rt.call::<VerifregActor>(
+                Method::RemoveVerifiedClientDataCapV2 as MethodNum,
+                &RawBytes::serialize(params).unwrap(),
+            ),
+        );
+        rt.reset();
+
+        let too_long = "x".repeat(MAX_REMOVAL_REASON_LEN + 1);
+        expect_abort(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            h.remove_client_datacap_v3(
+                &mut rt,
+                &CLIENT,
+                &amount,
+                &client_allowance,
+                &VERIFIER,
+                &VERIFIER2,
+                Some(&too_long),
+            ),
+        );
+        rt.reset();
+        h.assert_client_allowance(&rt, &CLIENT, &client_allowance);
+        assert_eq!(0, h.get_proposal_id(&rt, &VERIFIER, &CLIENT));
+        h.check_state(&rt);
     }
 
     #[test]
-    fn consume_then_fail_removed() {
-        let (h, mut rt) = new_harness();
-        let allowance = rt.policy.minimum_verified_deal_size.clone();
-        h.add_verifier_and_client(&mut rt, &VERIFIER, &CLIENT, &allowance, &allowance);
-
-        // Use full allowance.
-        h.use_bytes(&mut rt, &CLIENT, &allowance).unwrap();
-        // Fail to use any more because client was removed.
-        expect_abort(ExitCode::USR_NOT_FOUND, h.use_bytes(&mut rt, &CLIENT, &allowance));
-        h.check_state(&rt)
+    fn remove_datacap_params_keep_original_encoding() {
+        // RemoveVerifiedClientDataCap still takes a four element tuple; later options are
+        // only accepted by RemoveVerifiedClientDataCapV2.
+        let request = RemoveDataCapRequest {
+            verifier: *VERIFIER,
+            signature: Signature { sig_type: SignatureType::Secp256k1, bytes: vec![] },
+        };
+        let params = RemoveDataCapParams {
+            verified_client_to_remove: *CLIENT,
+            data_cap_amount_to_remove: DataCap::from(1),
+            verifier_request_1: request.clone(),
+            verifier_request_2: request,
+        };
+        assert_eq!(0x84, RawBytes::serialize(params).unwrap().bytes()[0]);
     }
 
     #[test]
-    fn consume_requires_market_actor_caller() {
+    fn remove_datacap_rejects_unknown_format_version() {
         let (h, mut rt) = new_harness();
-        rt.expect_validate_caller_addr(vec![*STORAGE_MARKET_ACTOR_ADDR]);
-        rt.set_caller(*POWER_ACTOR_CODE_ID, *STORAGE_POWER_ACTOR_ADDR);
-        let params = UseBytesParams {
-            address: *CLIENT,
-            deal_size: rt.policy.minimum_verified_deal_size.clone(),
+        let allowance = verifier_allowance(&rt);
+        let client_allowance = client_allowance(&rt);
+        h.add_verifier_and_client(&mut rt, &VERIFIER, &CLIENT, &allowance, &client_allowance);
+        h.add_verifier(&mut rt, &VERIFIER2, &allowance).unwrap();
+
+        let amount = DataCap::from(1);
+        let params = RemoveDataCapParamsV2 {
+            verified_client_to_remove: *CLIENT,
+            data_cap_amount_to_remove: amount.clone(),
+            verifier_request_1: h.make_remove_request(&rt, &VERIFIER, &CLIENT, &amount),
+            verifier_request_2: Some(h.make_remove_request(&rt, &VERIFIER2, &CLIENT, &amount)),
+            format_version: REMOVE_DATA_CAP_PROPOSAL_FORMAT_V3 + 1,
+            expected_client_data_cap: client_allowance.clone(),
+            additional_verifier_requests: vec![],
+            reason: None,
         };
+        rt.expect_validate_caller_addr(vec![h.root]);
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, h.root);
         expect_abort(
-            ExitCode::USR_FORBIDDEN,
+            ExitCode::USR_ILLEGAL_ARGUMENT,
             rt.call::<VerifregActor>(
-                Method::UseBytes as MethodNum,
+                Method::RemoveVerifiedClientDataCapV2 as MethodNum,
                 &RawBytes::serialize(params).unwrap(),
             ),
         );
-        h.check_state(&rt)
+        rt.reset();
+        h.assert_client_allowance(&rt, &CLIENT, &client_allowance);
+        assert_eq!(0, h.get_proposal_id(&rt, &VERIFIER, &CLIENT));
+        h.check_state(&rt);
     }
 
     #[test]
-    fn consume_requires_minimum_deal_size() {
+    fn remove_datacap_rejects_proposal_for_other_client() {
         let (h, mut rt) = new_harness();
-        let allowance_verifier = verifier_allowance(&rt);
-        let allowance_client = client_allowance(&rt);
-        h.add_verifier_and_client(
-            &mut rt,
-            &VERIFIER,
-            &CLIENT,
-            &allowance_verifier,
-            &allowance_client,
+        let allowance = verifier_allowance(&rt);
+        let client_allowance = client_allowance(&rt);
+        h.add_verifier_and_client(&mut rt, &VERIFIER, &CLIENT, &allowance, &client_allowance);
+        h.add_verifier_and_client(&mut rt, &VERIFIER2, &CLIENT2, &allowance, &client_allowance);
+
+        let amount = DataCap::from(1);
+        let payload = |client: &Address| {
+            let proposal = RemoveDataCapProposal {
+                verified_client: *client,
+                data_cap_amount: amount.clone(),
+                removal_proposal_id: RemoveDataCapProposalID(0),
+            };
+            [
+                SIGNATURE_DOMAIN_SEPARATION_REMOVE_DATA_CAP,
+                RawBytes::serialize(proposal).unwrap().bytes(),
+            ]
+            .concat()
+        };
+        let verifier_request_1 = h.make_remove_request(&rt, &VERIFIER, &CLIENT, &amount);
+        // The second verifier signed a proposal naming a different client, which can't
+        // verify against the proposal for the client being removed.
+        let signature = Signature { sig_type: SignatureType::Secp256k1, bytes: payload(&CLIENT2) };
+        rt.expect_verify_signature(ExpectedVerifySig {
+            sig: signature.clone(),
+            signer: *VERIFIER2,
+            plaintext: payload(&CLIENT),
+            result: Err(anyhow::anyhow!("signature is over a different proposal")),
+        });
+        let params = RemoveDataCapParamsV2 {
+            verified_client_to_remove: *CLIENT,
+            data_cap_amount_to_remove: amount.clone(),
+            verifier_request_1,
+            verifier_request_2: Some(RemoveDataCapRequest { verifier: *VERIFIER2, signature }),
+            format_version: REMOVE_DATA_CAP_PROPOSAL_FORMAT_V1,
+            expected_client_data_cap: client_allowance.clone(),
+            additional_verifier_requests: vec![],
+            reason: None,
+        };
+        rt.expect_validate_caller_addr(vec![h.root]);
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, h.root);
+        expect_abort(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            rt.call::<VerifregActor>(
+                Method::RemoveVerifiedClientDataCapV2 as MethodNum,
+                &RawBytes::serialize(params).unwrap(),
+            ),
         );
-
-        let deal_size = rt.policy.minimum_verified_deal_size.clone() - 1;
-        expect_abort(ExitCode::USR_ILLEGAL_ARGUMENT, h.use_bytes(&mut rt, &CLIENT, &deal_size));
-        h.check_state(&rt)
-    }
-
-    #[test]
-    fn consume_requires_client_exists() {
-        let (h, mut rt) = new_harness();
-        let min_deal_size = rt.policy.minimum_verified_deal_size.clone();
-        expect_abort(ExitCode::USR_NOT_FOUND, h.use_bytes(&mut rt, &CLIENT, &min_deal_size));
-        h.check_state(&rt)
+        rt.reset();
+        h.assert_client_allowance(&rt, &CLIENT, &client_allowance);
+        h.assert_client_allowance(&rt, &CLIENT2, &client_allowance);
+        assert_eq!(0, h.get_proposal_id(&rt, &VERIFIER2, &CLIENT));
+        h.check_state(&rt);
     }
 
     #[test]
-    fn consume_requires_deal_size_below_allowance() {
+    fn revoke_all_datacap() {
         let (h, mut rt) = new_harness();
-        let allowance_verifier = verifier_allowance(&rt);
-        let allowance_client = client_allowance(&rt);
-        h.add_verifier_and_client(
-            &mut rt,
-            &VERIFIER,
-            &CLIENT,
-            &allowance_verifier,
-            &allowance_client,
-        );
+        let allowance = verifier_allowance(&rt);
+        let client_allowance = client_allowance(&rt);
+        h.add_verifier_and_client(&mut rt, &VERIFIER, &CLIENT, &allowance, &client_allowance);
+        h.add_verifier(&mut rt, &VERIFIER2, &allowance).unwrap();
+        // Change the balance so that it no longer matches anything the caller knows.
+        h.remove_client_datacap(&mut rt, &CLIENT, &DataCap::from(7), &VERIFIER, &VERIFIER2)
+            .unwrap();
+        let balance = client_allowance - 7;
 
-        let deal_size = allowance_client.clone() + 1;
-        expect_abort(ExitCode::USR_ILLEGAL_ARGUMENT, h.use_bytes(&mut rt, &CLIENT, &deal_size));
-        h.check_state(&rt)
+        let ret = h.revoke_all_client_datacap(&mut rt, &CLIENT, &VERIFIER, &VERIFIER2).unwrap();
+        assert_eq!(*CLIENT, ret.verified_client);
+        assert_eq!(balance, ret.data_cap_removed);
+        assert_eq!(balance.diff(&DataCap::from(0)), ret.data_cap_change);
+        h.assert_client_removed(&rt, &CLIENT);
+        assert_eq!(2, h.get_proposal_id(&rt, &VERIFIER, &CLIENT));
+        assert_eq!(2, h.get_proposal_id(&rt, &VERIFIER2, &CLIENT));
+        h.check_state(&rt);
     }
 
     #[test]
-    fn restore_multiple_clients() {
+    fn revoke_all_datacap_with_three_approvers() {
         let (h, mut rt) = new_harness();
-        let allowance = rt.policy.minimum_verified_deal_size.clone() * 10;
-
-        let ca1 = rt.policy.minimum_verified_deal_size.clone() * 3;
-        h.add_verifier_and_client(&mut rt, &VERIFIER, &CLIENT, &allowance, &ca1);
-        let ca2 = rt.policy.minimum_verified_deal_size.clone() * 2;
-        h.add_client(&mut rt, &VERIFIER, &CLIENT2, &ca2, &ca2).unwrap();
-        let ca3 = rt.policy.minimum_verified_deal_size.clone() + 1;
-        h.add_client(&mut rt, &VERIFIER, &CLIENT3, &ca3, &ca3).unwrap();
-
-        let deal_size = rt.policy.minimum_verified_deal_size.clone();
-        h.restore_bytes(&mut rt, &CLIENT, &deal_size).unwrap();
-        h.assert_client_allowance(&rt, &CLIENT, &(ca1.clone() + &deal_size));
-
-        h.restore_bytes(&mut rt, &CLIENT2, &deal_size).unwrap();
-        h.assert_client_allowance(&rt, &CLIENT2, &(ca2.clone() + &deal_size));
-
-        h.restore_bytes(&mut rt, &CLIENT3, &deal_size).unwrap();
-        h.assert_client_allowance(&rt, &CLIENT3, &(ca3.clone() + &deal_size));
-
-        // Clients 1 and 2 now use bytes.
-        h.use_bytes(&mut rt, &CLIENT, &deal_size).unwrap();
-        h.assert_client_allowance(&rt, &CLIENT, &ca1);
-
-        h.use_bytes(&mut rt, &CLIENT2, &deal_size).unwrap();
-        h.assert_client_allowance(&rt, &CLIENT2, &ca2);
-
-        // Restore bytes back to all clients
-        h.restore_bytes(&mut rt, &CLIENT, &deal_size).unwrap();
-        h.assert_client_allowance(&rt, &CLIENT, &(ca1.clone() + &deal_size));
+        let verifier3 = Address::new_id(203);
+        let allowance = verifier_allowance(&rt);
+        let client_allowance = client_allowance(&rt);
+        h.add_verifier_and_client(&mut rt, &VERIFIER, &CLIENT, &allowance, &client_allowance);
+        h.add_verifier(&mut rt, &VERIFIER2, &allowance).unwrap();
+        h.add_verifier(&mut rt, &verifier3, &allowance).unwrap();
+        h.set_required_removal_approvers(&mut rt, 3).unwrap();
 
-        h.restore_bytes(&mut rt, &CLIENT2, &deal_size).unwrap();
-        h.assert_client_allowance(&rt, &CLIENT2, &(ca2.clone() + &deal_size));
+        expect_abort(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            h.revoke_all_client_datacap(&mut rt, &CLIENT, &VERIFIER, &VERIFIER2),
+        );
+        rt.reset();
+        expect_abort(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            h.revoke_all_client_datacap_by(&mut rt, &CLIENT, &[*VERIFIER, *VERIFIER2, *VERIFIER]),
+        );
+        rt.reset();
+        h.assert_client_allowance(&rt, &CLIENT, &client_allowance);
 
-        h.restore_bytes(&mut rt, &CLIENT3, &deal_size).unwrap();
-        h.assert_client_allowance(&rt, &CLIENT3, &(ca3.clone() + &deal_size + &deal_size));
+        let ret = h
+            .revoke_all_client_datacap_by(&mut rt, &CLIENT, &[*VERIFIER, *VERIFIER2, verifier3])
+            .unwrap();
+        assert_eq!(client_allowance, ret.data_cap_removed);
+        h.assert_client_removed(&rt, &CLIENT);
+        for verifier in [*VERIFIER, *VERIFIER2, verifier3] {
+            assert_eq!(1, h.get_proposal_id(&rt, &verifier, &CLIENT));
+        }
         h.check_state(&rt);
     }
 
     #[test]
-    fn restore_after_reducing_client_cap() {
+    fn revoke_all_requires_verified_client() {
         let (h, mut rt) = new_harness();
-        let allowance = rt.policy.minimum_verified_deal_size.clone() * 2;
-        h.add_verifier_and_client(&mut rt, &VERIFIER, &CLIENT, &allowance, &allowance);
-
-        // Use half allowance.
-        let deal_size = rt.policy.minimum_verified_deal_size.clone();
-        h.use_bytes(&mut rt, &CLIENT, &deal_size).unwrap();
-        h.assert_client_allowance(&rt, &CLIENT, &rt.policy.minimum_verified_deal_size);
+        let allowance = verifier_allowance(&rt);
+        h.add_verifier(&mut rt, &VERIFIER, &allowance).unwrap();
+        h.add_verifier(&mut rt, &VERIFIER2, &allowance).unwrap();
 
-        // Restore it.
-        h.restore_bytes(&mut rt, &CLIENT, &deal_size).unwrap();
-        h.assert_client_allowance(&rt, &CLIENT, &allowance);
-        h.check_state(&rt)
+        rt.expect_validate_caller_addr(vec![h.root]);
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, h.root);
+        let params = RevokeAllDataCapParams {
+            verified_client: *CLIENT,
+            verifier_request_1: RemoveDataCapRequest {
+                verifier: *VERIFIER,
+                signature: Signature { sig_type: SignatureType::Secp256k1, bytes: vec![] },
+            },
+            verifier_request_2: RemoveDataCapRequest {
+                verifier: *VERIFIER2,
+                signature: Signature { sig_type: SignatureType::Secp256k1, bytes: vec![] },
+            },
+            additional_verifier_requests: vec![],
+        };
+        expect_abort(
+            ExitCode::USR_NOT_FOUND,
+            rt.call::<VerifregActor>(
+                Method::RevokeAllClientDataCap as MethodNum,
+                &RawBytes::serialize(params).unwrap(),
+            ),
+        );
+        h.check_state(&rt);
     }
 
     #[test]
-    fn restore_resolves_client_address() {
+    fn set_client_datacap_to_target() {
         let (h, mut rt) = new_harness();
-        let allowance = rt.policy.minimum_verified_deal_size.clone() * 2;
-        h.add_verifier_and_client(&mut rt, &VERIFIER, &CLIENT, &allowance, &allowance);
-
-        // Use half allowance.
-        let deal_size = rt.policy.minimum_verified_deal_size.clone();
-        h.use_bytes(&mut rt, &CLIENT, &deal_size).unwrap();
-        h.assert_client_allowance(&rt, &CLIENT, &rt.policy.minimum_verified_deal_size);
-
-        let client_pubkey = Address::new_secp256k1(&[3u8; 65]).unwrap();
-        rt.id_addresses.insert(client_pubkey, *CLIENT);
+        let allowance = verifier_allowance(&rt);
+        let client_allowance = client_allowance(&rt);
+        h.add_verifier_and_client(&mut rt, &VERIFIER, &CLIENT, &allowance, &client_allowance);
+        h.add_verifier(&mut rt, &VERIFIER2, &allowance).unwrap();
+
+        let raised = &client_allowance * 3u64;
+        let change =
+            h.set_client_datacap(&mut rt, &CLIENT, &raised, &VERIFIER, &VERIFIER2).unwrap();
+        assert_eq!(client_allowance.diff(&raised), change);
+        assert_eq!(DataCapDirection::Increase, change.direction);
+        h.assert_client_allowance(&rt, &CLIENT, &raised);
+        assert!(get_recent_removals(&mut rt, 10).is_empty());
+
+        rt.set_epoch(100);
+        let lowered = &client_allowance / 2u64;
+        let change =
+            h.set_client_datacap(&mut rt, &CLIENT, &lowered, &VERIFIER2, &VERIFIER).unwrap();
+        assert_eq!(raised.diff(&lowered), change);
+        assert_eq!(DataCapDirection::Decrease, change.direction);
+        h.assert_client_allowance(&rt, &CLIENT, &lowered);
+        assert_eq!(
+            vec![RemovalLogEntry {
+                client: *CLIENT,
+                amount: &raised - &lowered,
+                approvers: vec![*VERIFIER2, *VERIFIER],
+                epoch: 100,
+                reason: None,
+            }],
+            get_recent_removals(&mut rt, 10)
+        );
+        assert_eq!(2, h.get_proposal_id(&rt, &VERIFIER, &CLIENT));
 
-        // Restore to pubkey address.
-        h.restore_bytes(&mut rt, &client_pubkey, &deal_size).unwrap();
-        h.assert_client_allowance(&rt, &CLIENT, &allowance);
-        h.check_state(&rt)
+        // A zero target removes the client.
+        h.set_client_datacap(&mut rt, &CLIENT, &DataCap::from(0), &VERIFIER, &VERIFIER2).unwrap();
+        h.assert_client_removed(&rt, &CLIENT);
+        h.check_state(&rt);
     }
 
     #[test]
-    fn restore_after_removing_client() {
+    fn set_client_datacap_with_three_approvers() {
         let (h, mut rt) = new_harness();
-        let allowance = rt.policy.minimum_verified_deal_size.clone() + 1;
-        h.add_verifier_and_client(&mut rt, &VERIFIER, &CLIENT, &allowance, &allowance);
+        let verifier3 = Address::new_id(203);
+        let allowance = verifier_allowance(&rt);
+        let client_allowance = client_allowance(&rt);
+        h.add_verifier_and_client(&mut rt, &VERIFIER, &CLIENT, &allowance, &client_allowance);
+        h.add_verifier(&mut rt, &VERIFIER2, &allowance).unwrap();
+        h.add_verifier(&mut rt, &verifier3, &allowance).unwrap();
+        h.set_required_removal_approvers(&mut rt, 3).unwrap();
 
-        // Use allowance.
-        let deal_size = rt.policy.minimum_verified_deal_size.clone();
-        h.use_bytes(&mut rt, &CLIENT, &deal_size).unwrap();
-        h.assert_client_removed(&rt, &CLIENT);
+        let target = &client_allowance / 2u64;
+        expect_abort(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            h.set_client_datacap(&mut rt, &CLIENT, &target, &VERIFIER, &VERIFIER2),
+        );
+        rt.reset();
+        expect_abort(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            h.set_client_datacap_by(&mut rt, &CLIENT, &target, &[*VERIFIER, *VERIFIER2, *VERIFIER]),
+        );
+        rt.reset();
+        h.assert_client_allowance(&rt, &CLIENT, &client_allowance);
 
-        // Restore it. Client has only the restored bytes (lost the +1 in original allowance).
-        h.restore_bytes(&mut rt, &CLIENT, &deal_size).unwrap();
-        h.assert_client_allowance(&rt, &CLIENT, &deal_size);
-        h.check_state(&rt)
+        h.set_client_datacap_by(&mut rt, &CLIENT, &target, &[*VERIFIER, *VERIFIER2, verifier3])
+            .unwrap();
+        h.assert_client_allowance(&rt, &CLIENT, &target);
+        for verifier in [*VERIFIER, *VERIFIER2, verifier3] {
+            assert_eq!(1, h.get_proposal_id(&rt, &verifier, &CLIENT));
+        }
+        h.check_state(&rt);
     }
 
     #[test]
-    fn restore_requires_market_actor_caller() {
+    fn set_client_datacap_requires_verified_client() {
         let (h, mut rt) = new_harness();
-        rt.expect_validate_caller_addr(vec![*STORAGE_MARKET_ACTOR_ADDR]);
-        rt.set_caller(*POWER_ACTOR_CODE_ID, *STORAGE_POWER_ACTOR_ADDR);
-        let params = RestoreBytesParams {
-            address: *CLIENT,
-            deal_size: rt.policy.minimum_verified_deal_size.clone(),
+        let allowance = verifier_allowance(&rt);
+        h.add_verifier(&mut rt, &VERIFIER, &allowance).unwrap();
+        h.add_verifier(&mut rt, &VERIFIER2, &allowance).unwrap();
+
+        let target = client_allowance(&rt);
+        rt.expect_validate_caller_addr(vec![h.root]);
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, h.root);
+        let params = SetClientDataCapParams {
+            verified_client: *CLIENT,
+            target: non_neg(&target),
+            verifier_request_1: RemoveDataCapRequest {
+                verifier: *VERIFIER,
+                signature: Signature { sig_type: SignatureType::Secp256k1, bytes: vec![] },
+            },
+            verifier_request_2: RemoveDataCapRequest {
+                verifier: *VERIFIER2,
+                signature: Signature { sig_type: SignatureType::Secp256k1, bytes: vec![] },
+            },
+            additional_verifier_requests: vec![],
         };
         expect_abort(
-            ExitCode::USR_FORBIDDEN,
+            ExitCode::USR_NOT_FOUND,
             rt.call::<VerifregActor>(
-                Method::RestoreBytes as MethodNum,
+                Method::SetClientDataCap as MethodNum,
                 &RawBytes::serialize(params).unwrap(),
             ),
         );
-        h.check_state(&rt)
+        rt.reset();
+        h.assert_client_removed(&rt, &CLIENT);
+        h.check_state(&rt);
     }
 
     #[test]
-    fn restore_requires_minimum_deal_size() {
+    fn can_jointly_remove() {
         let (h, mut rt) = new_harness();
-        let allowance_verifier = verifier_allowance(&rt);
-        let allowance_client = client_allowance(&rt);
-        h.add_verifier_and_client(
-            &mut rt,
-            &VERIFIER,
-            &CLIENT,
-            &allowance_verifier,
-            &allowance_client,
-        );
+        let allowance = verifier_allowance(&rt);
+        let client_allowance = client_allowance(&rt);
+        h.add_verifier_and_client(&mut rt, &VERIFIER, &CLIENT, &allowance, &client_allowance);
+        assert!(!h.can_jointly_remove(&mut rt, &VERIFIER, &VERIFIER2));
+        h.add_verifier(&mut rt, &VERIFIER2, &allowance).unwrap();
+
+        assert!(h.can_jointly_remove(&mut rt, &VERIFIER, &VERIFIER2));
+        assert!(h.can_jointly_remove(&mut rt, &VERIFIER2, &VERIFIER));
+        assert!(!h.can_jointly_remove(&mut rt, &VERIFIER, &VERIFIER));
+        assert!(!h.can_jointly_remove(&mut rt, &VERIFIER, &CLIENT));
+        let unknown = Address::new_bls(&[9u8; BLS_PUB_LEN]).unwrap();
+        assert!(!h.can_jointly_remove(&mut rt, &unknown, &VERIFIER2));
+        h.check_state(&rt);
+    }
 
-        let deal_size = rt.policy.minimum_verified_deal_size.clone() - 1;
-        expect_abort(ExitCode::USR_ILLEGAL_ARGUMENT, h.restore_bytes(&mut rt, &CLIENT, &deal_size));
-        h.check_state(&rt)
+    #[test]
+    fn normalize_proposal_ids() {
+        let (h, mut rt) = new_harness();
+        let verifier_pubkey = Address::new_bls(&[7u8; BLS_PUB_LEN]).unwrap();
+        let client_pubkey = Address::new_secp256k1(&[3u8; 65]).unwrap();
+        let unknown_pubkey = Address::new_bls(&[9u8; BLS_PUB_LEN]).unwrap();
+        rt.add_id_address(verifier_pubkey, *VERIFIER);
+        rt.add_id_address(client_pubkey, *CLIENT2);
+
+        // Seed entries keyed by pubkey addresses, one of which collides with an
+        // existing ID-keyed entry holding a lower ID.
+        let mut st: State = rt.get_state();
+        let mut proposal_ids = make_map_with_root_and_bitwidth::<_, RemoveDataCapProposalID>(
+            &st.remove_data_cap_proposal_ids,
+            &rt.store,
+            HAMT_BIT_WIDTH,
+        )
+        .unwrap();
+        for (verifier, client, id) in [
+            (verifier_pubkey, *CLIENT, 3),
+            (*VERIFIER2, client_pubkey, 5),
+            (*VERIFIER2, *CLIENT2, 2),
+            (unknown_pubkey, *CLIENT, 7),
+        ] {
+            let key = VerifierClientKey::new(verifier, client).to_bytes();
+            proposal_ids.set(key.into(), RemoveDataCapProposalID(id)).unwrap();
+        }
+        st.remove_data_cap_proposal_ids = proposal_ids.flush().unwrap();
+        rt.replace_state(&st);
+
+        rt.expect_validate_caller_addr(vec![h.root]);
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, h.root);
+        let ret = rt
+            .call::<VerifregActor>(Method::NormalizeProposalIds as MethodNum, &RawBytes::default())
+            .unwrap();
+        assert_eq!(RawBytes::default(), ret);
+        rt.verify();
+
+        assert_eq!(3, h.get_proposal_id(&rt, &VERIFIER, &CLIENT));
+        assert_eq!(5, h.get_proposal_id(&rt, &VERIFIER2, &CLIENT2));
+        assert_eq!(0, h.get_proposal_id(&rt, &verifier_pubkey, &CLIENT));
+        assert_eq!(0, h.get_proposal_id(&rt, &VERIFIER2, &client_pubkey));
+        // An address with no ID mapping is left as it was.
+        assert_eq!(7, h.get_proposal_id(&rt, &unknown_pubkey, &CLIENT));
+        h.check_state(&rt);
     }
 
     #[test]
-    fn restore_rejects_root() {
+    fn export_and_import_proposal_ids() {
         let (h, mut rt) = new_harness();
-        let deal_size = rt.policy.minimum_verified_deal_size.clone();
+        let allowance = verifier_allowance(&rt);
+        let client_allowance = client_allowance(&rt);
+        h.add_verifier_and_client(&mut rt, &VERIFIER, &CLIENT, &allowance, &client_allowance);
+        h.add_verifier_and_client(&mut rt, &VERIFIER2, &CLIENT2, &allowance, &client_allowance);
+        let amount = DataCap::from(1);
+        h.remove_client_datacap(&mut rt, &CLIENT, &amount, &VERIFIER, &VERIFIER2).unwrap();
+        h.remove_client_datacap(&mut rt, &CLIENT, &amount, &VERIFIER2, &VERIFIER).unwrap();
+        h.remove_client_datacap(&mut rt, &CLIENT2, &amount, &VERIFIER, &VERIFIER2).unwrap();
+
+        let st: State = rt.get_state();
+        let exported = st.list_proposal_ids(&rt.store).unwrap();
+        assert_eq!(4, exported.len());
+
+        // Restore into a fresh registry.
+        let (h2, mut rt2) = new_harness();
+        h2.import_proposal_ids(&mut rt2, exported.clone()).unwrap();
+        let st2: State = rt2.get_state();
+        assert_eq!(exported, st2.list_proposal_ids(&rt2.store).unwrap());
+        assert_eq!(2, h2.get_proposal_id(&rt2, &VERIFIER, &CLIENT));
+
+        // Importing a lower ID never rewinds a pair.
+        h2.import_proposal_ids(&mut rt2, vec![(*VERIFIER, *CLIENT, 1)]).unwrap();
+        assert_eq!(2, h2.get_proposal_id(&rt2, &VERIFIER, &CLIENT));
+        h2.check_state(&rt2);
+    }
+
+    #[test]
+    fn import_proposal_ids_requires_id_addresses() {
+        let (h, mut rt) = new_harness();
+        let client = Address::new_bls(&[7u8; BLS_PUB_LEN]).unwrap();
         expect_abort(
             ExitCode::USR_ILLEGAL_ARGUMENT,
-            h.restore_bytes(&mut rt, &ROOT_ADDR, &deal_size),
+            h.import_proposal_ids(&mut rt, vec![(*VERIFIER, client, 3)]),
         );
-        h.check_state(&rt)
+        rt.reset();
+        let st: State = rt.get_state();
+        assert!(st.list_proposal_ids(&rt.store).unwrap().is_empty());
+        h.check_state(&rt);
     }
 
     #[test]
-    fn restore_rejects_verifier() {
+    fn removals_are_logged() {
         let (h, mut rt) = new_harness();
         let allowance = verifier_allowance(&rt);
-        h.add_verifier(&mut rt, &VERIFIER, &allowance).unwrap();
-        let deal_size = rt.policy.minimum_verified_deal_size.clone();
-        expect_abort(
-            ExitCode::USR_ILLEGAL_ARGUMENT,
-            h.restore_bytes(&mut rt, &VERIFIER, &deal_size),
+        let client_allowance = client_allowance(&rt);
+        h.add_verifier_and_client(&mut rt, &VERIFIER, &CLIENT, &allowance, &client_allowance);
+        h.add_verifier(&mut rt, &VERIFIER2, &allowance).unwrap();
+        assert!(get_recent_removals(&mut rt, 10).is_empty());
+
+        rt.set_epoch(100);
+        h.remove_client_datacap(&mut rt, &CLIENT, &DataCap::from(5), &VERIFIER, &VERIFIER2)
+            .unwrap();
+        // Removing more than remains clamps to the client's remaining cap.
+        rt.set_epoch(200);
+        let remaining = &client_allowance - 5;
+        h.remove_client_datacap(&mut rt, &CLIENT, &client_allowance, &VERIFIER2, &VERIFIER)
+            .unwrap();
+
+        let entries = get_recent_removals(&mut rt, 10);
+        assert_eq!(
+            vec![
+                RemovalLogEntry {
+                    client: *CLIENT,
+                    amount: DataCap::from(5),
+                    approvers: vec![*VERIFIER, *VERIFIER2],
+                    epoch: 100,
+                    reason: None,
+                },
+                RemovalLogEntry {
+                    client: *CLIENT,
+                    amount: remaining,
+                    approvers: vec![*VERIFIER2, *VERIFIER],
+                    epoch: 200,
+                    reason: None,
+                },
+            ],
+            entries
         );
-        h.check_state(&rt)
+        assert_eq!(entries[1..].to_vec(), get_recent_removals(&mut rt, 1));
+        h.check_state(&rt);
+    }
+
+    fn get_recent_removals(rt: &mut MockRuntime, count: u64) -> Vec<RemovalLogEntry> {
+        rt.expect_validate_caller_any();
+        let ret: GetRecentRemovalsReturn = rt
+            .call::<VerifregActor>(
+                Method::GetRecentRemovals as MethodNum,
+                &RawBytes::serialize(count).unwrap(),
+            )
+            .unwrap()
+            .deserialize()
+            .unwrap();
+        rt.verify();
+        ret.entries
+    }
+
+    #[test]
+    fn proposal_id_count() {
+        let (h, mut rt) = new_harness();
+        let verifier3 = Address::new_id(203);
+        let allowance = verifier_allowance(&rt);
+        let client_allowance = client_allowance(&rt);
+        h.add_verifier_and_client(&mut rt, &VERIFIER, &CLIENT, &allowance, &client_allowance);
+        h.add_verifier_and_client(&mut rt, &VERIFIER2, &CLIENT2, &allowance, &client_allowance);
+        h.add_verifier(&mut rt, &verifier3, &allowance).unwrap();
+
+        let st: State = rt.get_state();
+        assert_eq!(3, st.verifier_count(&rt.store).unwrap());
+        assert_eq!(0, st.proposal_id_count(&rt.store).unwrap());
+
+        let amount = DataCap::from(1);
+        h.remove_client_datacap(&mut rt, &CLIENT, &amount, &VERIFIER, &VERIFIER2).unwrap();
+        // Repeating a removal with the same verifiers reuses their entries.
+        h.remove_client_datacap(&mut rt, &CLIENT, &amount, &VERIFIER, &VERIFIER2).unwrap();
+        h.remove_client_datacap(&mut rt, &CLIENT, &amount, &VERIFIER, &verifier3).unwrap();
+        h.remove_client_datacap(&mut rt, &CLIENT2, &amount, &VERIFIER2, &verifier3).unwrap();
+
+        let st: State = rt.get_state();
+        assert_eq!(5, st.proposal_id_count(&rt.store).unwrap());
+        assert_eq!(3, st.verifier_count(&rt.store).unwrap());
+        h.check_state(&rt);
+    }
+
+    fn get_client_proposal_ids(rt: &mut MockRuntime, client: &Address) -> Vec<(Address, u64)> {
+        rt.expect_validate_caller_any();
+        let ret: GetClientProposalIdsReturn = rt
+            .call::<VerifregActor>(
+                Method::GetClientProposalIds as MethodNum,
+                &RawBytes::serialize(client).unwrap(),
+            )
+            .unwrap()
+            .deserialize()
+            .unwrap();
+        rt.verify();
+        ret.proposal_ids
     }
 }
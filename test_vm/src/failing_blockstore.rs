@@ -0,0 +1,47 @@
+use std::cell::Cell;
+
+use anyhow::{anyhow, Result};
+use cid::Cid;
+use fvm_ipld_blockstore::{Blockstore, MemoryBlockstore};
+
+/// A memory blockstore that fails every operation after a fixed number have succeeded.
+///
+/// Used to exercise the error paths of code that reads or writes state.
+#[derive(Debug, Default)]
+pub struct FailingBlockstore {
+    inner: MemoryBlockstore,
+    remaining: Cell<usize>,
+}
+
+impl FailingBlockstore {
+    /// Creates a blockstore allowing `ops` gets and puts before failing.
+    pub fn new(ops: usize) -> Self {
+        FailingBlockstore { inner: MemoryBlockstore::new(), remaining: Cell::new(ops) }
+    }
+
+    /// Allows `ops` more gets and puts before failing, replacing any remaining allowance.
+    pub fn fail_after(&self, ops: usize) {
+        self.remaining.set(ops);
+    }
+
+    fn consume(&self) -> Result<()> {
+        let remaining = self.remaining.get();
+        if remaining == 0 {
+            return Err(anyhow!("blockstore failure injected"));
+        }
+        self.remaining.set(remaining - 1);
+        Ok(())
+    }
+}
+
+impl Blockstore for FailingBlockstore {
+    fn get(&self, k: &Cid) -> Result<Option<Vec<u8>>> {
+        self.consume()?;
+        self.inner.get(k)
+    }
+
+    fn put_keyed(&self, k: &Cid, block: &[u8]) -> Result<()> {
+        self.consume()?;
+        self.inner.put_keyed(k, block)
+    }
+}
@@ -52,11 +52,12 @@ use num_traits::Signed;
 use regex::Regex;
 use serde::ser;
 use std::cell::{RefCell, RefMut};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::error::Error;
 use std::fmt;
 use std::ops::Add;
 
+pub mod failing_blockstore;
 pub mod util;
 
 pub struct VM<'bs> {
@@ -230,7 +231,14 @@ impl<'bs> VM<'bs> {
         let root_msig_addr = msig_ctor_ret.id_address;
         assert_eq!(TEST_VERIFREG_ROOT_ADDR, root_msig_addr);
         // verifreg
-        let verifreg_head = v.put_store(&VerifRegState::new(&v.store, root_msig_addr).unwrap());
+        let verifreg_head = v.put_store(
+            &VerifRegState::new(
+                &v.store,
+                root_msig_addr,
+                Policy::default().minimum_verified_deal_size,
+            )
+            .unwrap(),
+        );
         v.set_actor(
             *VERIFIED_REGISTRY_ACTOR_ADDR,
             actor(*VERIFREG_ACTOR_CODE_ID, verifreg_head, 0, TokenAmount::zero()),
@@ -336,6 +344,31 @@ impl<'bs> VM<'bs> {
         self.actors_dirty.replace(true);
     }
 
+    /// Returns every actor in the state tree, including changes not yet checkpointed.
+    /// Intended for inspecting state while debugging a test.
+    ///
+    /// The map is keyed by actor ID rather than `Address`, which has no ordering and so
+    /// can't key a `BTreeMap`. Every key in the tree is an ID address, so
+    /// `Address::new_id` recovers it.
+    pub fn dump_state(&self) -> BTreeMap<ActorID, Actor> {
+        let actors = Hamt::<&'bs MemoryBlockstore, Actor, BytesKey, Sha256>::load(
+            &self.state_root.borrow(),
+            self.store,
+        )
+        .unwrap();
+        let mut state = BTreeMap::new();
+        actors
+            .for_each(|key, act| {
+                state.insert(Address::from_bytes(key)?.id()?, act.clone());
+                Ok(())
+            })
+            .unwrap();
+        for (addr, act) in self.actors_cache.borrow().iter() {
+            state.insert(addr.id().unwrap(), act.clone());
+        }
+        state
+    }
+
     pub fn checkpoint(&self) -> Cid {
         // persist cache on top of latest checkpoint and clear
         let mut actors = Hamt::<&'bs MemoryBlockstore, Actor, BytesKey, Sha256>::load(
@@ -384,29 +417,46 @@ impl<'bs> VM<'bs> {
         method: MethodNum,
         params: C,
     ) -> Result<MessageResult, TestVMError> {
-        let from_id = self.normalize_address(&from).unwrap();
-        let mut a = self.get_actor(from_id).unwrap();
+        self.apply_message_with_origin(from, from, to, value, method, params)
+    }
+
+    /// Applies a message sent by `origin` whose immediate caller, as seen by the receiving
+    /// actor, is `caller` instead. The origin's call sequence is bumped and it seeds any
+    /// new actor addresses, as for a message it had sent itself.
+    pub fn apply_message_with_origin<C: Cbor>(
+        &self,
+        origin: Address,
+        caller: Address,
+        to: Address,
+        value: TokenAmount,
+        method: MethodNum,
+        params: C,
+    ) -> Result<MessageResult, TestVMError> {
+        let params = serialize(&params, "params for apply message").map_err(|e| {
+            vm_err(&format!(
+                "failed to serialize params of type {}: {}",
+                std::any::type_name::<C>(),
+                e
+            ))
+        })?;
+        let origin_id = self.normalize_address(&origin).unwrap();
+        let mut a = self.get_actor(origin_id).unwrap();
         let call_seq = a.call_seq_num;
         a.call_seq_num = call_seq + 1;
-        self.set_actor(from_id, a);
+        self.set_actor(origin_id, a);
+        let caller_id = self.normalize_address(&caller).unwrap();
 
         let prior_root = self.checkpoint();
 
         // big.Mul(big.NewInt(1e9), big.NewInt(1e18))
         // make top level context with internal context
         let top = TopCtx {
-            originator_stable_addr: from,
+            originator_stable_addr: origin,
             _originator_call_seq: call_seq,
             new_actor_addr_count: RefCell::new(0),
             circ_supply: TokenAmount::from(1e9 as u128 * 1e18 as u128),
         };
-        let msg = InternalMessage {
-            from: from_id,
-            to,
-            value,
-            method,
-            params: serialize(&params, "params for apply message").unwrap(),
-        };
+        let msg = InternalMessage { from: caller_id, to, value, method, params };
         let mut new_ctx = InvocationCtx {
             v: self,
             top,
@@ -438,6 +488,12 @@ impl<'bs> VM<'bs> {
         self.invocations.take()
     }
 
+    /// Renders the recorded invocation trees without consuming them, one call per line
+    /// indented by call depth.
+    pub fn format_invocations(&self) -> String {
+        self.invocations.borrow().iter().map(|invoc| invoc.format(0)).collect()
+    }
+
     /// Checks the state invariants and returns broken invariants.
     pub fn check_state_invariants(&self) -> anyhow::Result<MessageAccumulator> {
         self.checkpoint();
@@ -1037,6 +1093,22 @@ pub struct InvocationTrace {
     pub subinvocations: Vec<InvocationTrace>,
 }
 
+impl InvocationTrace {
+    pub fn format(&self, depth: usize) -> String {
+        let mut out = format!(
+            "{}[{}:{}] params: {} bytes\n",
+            "  ".repeat(depth),
+            self.msg.to,
+            self.msg.method,
+            self.msg.params.len()
+        );
+        for sub in &self.subinvocations {
+            out.push_str(&sub.format(depth + 1));
+        }
+        out
+    }
+}
+
 pub struct ExpectInvocation {
     pub to: Address,
     // required
@@ -1051,6 +1123,21 @@ pub struct ExpectInvocation {
 }
 
 impl ExpectInvocation {
+    /// Builds an expectation matching every field of a recorded invocation and its
+    /// subinvocations, to be adjusted as needed by the test.
+    pub fn from_actual(invoc: &InvocationTrace) -> Self {
+        Self {
+            to: invoc.msg.to,
+            method: invoc.msg.method,
+            code: invoc.code,
+            from: Some(invoc.msg.from),
+            value: Some(invoc.msg.value.clone()),
+            params: Some(invoc.msg.params.clone()),
+            ret: invoc.ret.clone(),
+            subinvocs: Some(invoc.subinvocations.iter().map(Self::from_actual).collect()),
+        }
+    }
+
     // testing method that panics on no match
     pub fn matches(&self, invoc: &InvocationTrace) {
         let id = format!("[{}:{}]", invoc.msg.to, invoc.msg.method);
@@ -17,7 +17,7 @@ use fil_actor_power::{
     CreateMinerParams, CreateMinerReturn, Method as PowerMethod, UpdateClaimedPowerParams,
 };
 use fil_actor_reward::Method as RewardMethod;
-use fil_actor_verifreg::{Method as VerifregMethod, VerifierParams};
+use fil_actor_verifreg::{Method as VerifregMethod, NonNegDataCap, VerifierParams};
 use fvm_ipld_bitfield::{BitField, UnvalidatedBitField};
 use fvm_ipld_encoding::{BytesDe, Cbor, RawBytes};
 use fvm_shared::address::{Address, BLS_PUB_LEN};
@@ -55,13 +55,85 @@ pub fn create_accounts(v: &VM, count: u64, balance: TokenAmount) -> Vec<Address>
 }
 
 pub fn create_accounts_seeded(v: &VM, count: u64, balance: TokenAmount, seed: u64) -> Vec<Address> {
+    try_create_accounts_seeded(v, count, balance, seed).unwrap()
+}
+
+/// Like [`create_accounts`], but returns an error instead of panicking if an account
+/// can't be created.
+pub fn try_create_accounts(
+    v: &VM,
+    count: u64,
+    balance: TokenAmount,
+) -> Result<Vec<Address>, TestVMError> {
+    try_create_accounts_seeded(v, count, balance, ACCOUNT_SEED)
+}
+
+pub fn try_create_accounts_seeded(
+    v: &VM,
+    count: u64,
+    balance: TokenAmount,
+    seed: u64,
+) -> Result<Vec<Address>, TestVMError> {
     let pk_addrs = pk_addrs_from(seed, count);
     // Send funds from faucet to pk address, creating account actor
-    for pk_addr in pk_addrs.clone() {
-        apply_ok(v, TEST_FAUCET_ADDR, pk_addr, balance.clone(), METHOD_SEND, RawBytes::default());
+    for pk_addr in pk_addrs.iter() {
+        let res = v.apply_message(
+            TEST_FAUCET_ADDR,
+            *pk_addr,
+            balance.clone(),
+            METHOD_SEND,
+            RawBytes::default(),
+        )?;
+        if res.code != ExitCode::OK {
+            return Err(vm_err(&format!(
+                "failed to create account {}: exit code {:?}",
+                pk_addr, res.code
+            )));
+        }
     }
     // Normalize pk address to return id address of account actor
-    pk_addrs.iter().map(|&pk_addr| v.normalize_address(&pk_addr).unwrap()).collect()
+    pk_addrs
+        .iter()
+        .map(|pk_addr| {
+            v.normalize_address(pk_addr)
+                .ok_or_else(|| vm_err(&format!("no ID address for account {}", pk_addr)))
+        })
+        .collect()
+}
+
+/// Creates a multisig actor with `signers` and approval `threshold` through the init actor,
+/// funded with `balance` sent by the first signer, and returns its ID address.
+pub fn create_multisig(
+    v: &VM,
+    signers: Vec<Address>,
+    threshold: u64,
+    balance: TokenAmount,
+) -> Address {
+    assert!(!signers.is_empty());
+    let msig_ctor_params = serialize(
+        &fil_actor_multisig::ConstructorParams {
+            signers: signers.clone(),
+            num_approvals_threshold: threshold,
+            unlock_duration: 0,
+            start_epoch: 0,
+        },
+        "multisig ctor params",
+    )
+    .unwrap();
+    let msig_ctor_ret: ExecReturn = apply_ok(
+        v,
+        signers[0],
+        *INIT_ACTOR_ADDR,
+        balance,
+        fil_actor_init::Method::Exec as u64,
+        fil_actor_init::ExecParams {
+            code_cid: *MULTISIG_ACTOR_CODE_ID,
+            constructor_params: msig_ctor_params,
+        },
+    )
+    .deserialize()
+    .unwrap();
+    msig_ctor_ret.id_address
 }
 
 pub fn apply_ok<C: Cbor>(
@@ -89,6 +161,55 @@ pub fn apply_code<C: Cbor>(
     res.ret
 }
 
+/// Flushes the VM's actor cache and decodes the state of the actor at `addr` afresh from
+/// the blockstore, via the flushed state root.
+pub fn reload_state<S: Cbor>(v: &VM, addr: Address) -> S {
+    let root = v.checkpoint();
+    let actors = Hamt::<&MemoryBlockstore, Actor, BytesKey, Sha256>::load(&root, v.store).unwrap();
+    let actor = actors.get(&addr.to_bytes()).unwrap().unwrap();
+    v.store.get_cbor::<S>(&actor.head).unwrap().unwrap()
+}
+
+/// Asserts that the root of the VM's full state tree, once cached actor changes are flushed,
+/// is `expected`. The actual root is reported on mismatch, so a pinned golden value can be
+/// regenerated by running the test and copying the reported CID.
+pub fn assert_state_root_eq(v: &VM, expected: Cid) {
+    let actual = v.checkpoint();
+    assert_eq!(expected, actual, "state root mismatch: expected {}, got {}", expected, actual);
+}
+
+/// Asserts that the recorded invocation trees hold `expected` invocations in total,
+/// counting every top-level message and each of its nested subinvocations.
+pub fn assert_invocation_count(invocations: &[InvocationTrace], expected: usize) {
+    fn count(invoc: &InvocationTrace) -> usize {
+        1 + invoc.subinvocations.iter().map(count).sum::<usize>()
+    }
+    let actual: usize = invocations.iter().map(count).sum();
+    assert_eq!(
+        expected,
+        actual,
+        "unexpected invocation count: expected {}, got {}\n{}",
+        expected,
+        actual,
+        invocations.iter().map(|invoc| invoc.format(0)).collect::<String>()
+    );
+}
+
+/// Asserts that no invocation in the recorded trees, at any depth, calls `method` on `to`.
+pub fn assert_no_invocation(invocations: &[InvocationTrace], to: Address, method: MethodNum) {
+    fn calls(invoc: &InvocationTrace, to: Address, method: MethodNum) -> bool {
+        (invoc.msg.to == to && invoc.msg.method == method)
+            || invoc.subinvocations.iter().any(|sub| calls(sub, to, method))
+    }
+    assert!(
+        !invocations.iter().any(|invoc| calls(invoc, to, method)),
+        "unexpected invocation of [{}:{}]\n{}",
+        to,
+        method,
+        invocations.iter().map(|invoc| invoc.format(0)).collect::<String>()
+    );
+}
+
 pub fn create_miner(
     v: &mut VM,
     owner: Address,
@@ -521,7 +642,8 @@ pub fn submit_invalid_post(
 }
 
 pub fn add_verifier(v: &VM, verifier: Address, data_cap: StoragePower) {
-    let add_verifier_params = VerifierParams { address: verifier, allowance: data_cap };
+    let add_verifier_params =
+        VerifierParams { address: verifier, allowance: NonNegDataCap::try_from(data_cap).unwrap() };
     // root address is msig, send proposal from root key
     let proposal = ProposeParams {
         to: *VERIFIED_REGISTRY_ACTOR_ADDR,
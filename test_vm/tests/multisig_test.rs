@@ -1,11 +1,10 @@
-use fil_actor_init::ExecReturn;
 use fil_actor_multisig::{
     compute_proposal_hash, Method as MsigMethod, ProposeParams, RemoveSignerParams,
     State as MsigState, SwapSignerParams, Transaction, TxnID, TxnIDParams,
 };
 use fil_actors_runtime::cbor::serialize;
 use fil_actors_runtime::test_utils::*;
-use fil_actors_runtime::{make_map_with_root, INIT_ACTOR_ADDR, SYSTEM_ACTOR_ADDR};
+use fil_actors_runtime::{make_map_with_root, SYSTEM_ACTOR_ADDR};
 use fvm_ipld_blockstore::MemoryBlockstore;
 use fvm_ipld_encoding::RawBytes;
 use fvm_shared::address::Address;
@@ -16,7 +15,7 @@ use fvm_shared::METHOD_SEND;
 use integer_encoding::VarInt;
 use std::collections::HashSet;
 use std::iter::FromIterator;
-use test_vm::util::{apply_code, apply_ok, create_accounts};
+use test_vm::util::{apply_code, apply_ok, create_accounts, create_multisig};
 use test_vm::{ExpectInvocation, VM};
 
 #[test]
@@ -28,7 +27,7 @@ fn test_proposal_hash() {
     let bob = addrs[1];
     let sys_act_start_bal = v.get_actor(*SYSTEM_ACTOR_ADDR).unwrap().balance;
 
-    let msig_addr = create_msig(&v, addrs, 2);
+    let msig_addr = create_multisig(&v, addrs, 2, TokenAmount::zero());
 
     // fund msig and propose send funds to system actor
     let fil_delta = TokenAmount::from(3 * 1_000_000_000_u64); // 3 nFIL
@@ -105,7 +104,7 @@ fn test_delete_self() {
         let v = VM::new_with_singletons(&store);
         let addrs = create_accounts(&v, signers, TokenAmount::from(10_000e18 as u64));
 
-        let msig_addr = create_msig(&v, addrs.clone(), threshold as u64);
+        let msig_addr = create_multisig(&v, addrs.clone(), threshold as u64, TokenAmount::zero());
 
         let remove_params =
             RemoveSignerParams { signer: addrs[remove_idx], decrease: threshold == addrs.len() };
@@ -178,7 +177,7 @@ fn swap_self_1_of_2() {
     let addrs = create_accounts(&v, 3, TokenAmount::from(10_000e18 as u64));
 
     let (alice, bob, chuck) = (addrs[0], addrs[1], addrs[2]);
-    let msig_addr = create_msig(&v, vec![alice, bob], 1);
+    let msig_addr = create_multisig(&v, vec![alice, bob], 1, TokenAmount::zero());
     let swap_params = SwapSignerParams { from: alice, to: chuck };
     let propose_swap_signer_params = ProposeParams {
         to: msig_addr,
@@ -207,7 +206,7 @@ fn swap_self_2_of_3() {
     let addrs = create_accounts(&v, 4, TokenAmount::from(10_000e18 as u64));
     let (alice, bob, chuck, dinesh) = (addrs[0], addrs[1], addrs[2], addrs[3]);
 
-    let msig_addr = create_msig(&v, vec![alice, bob, chuck], 2);
+    let msig_addr = create_multisig(&v, vec![alice, bob, chuck], 2, TokenAmount::zero());
 
     // Case 1: swapped out is proposer, swap alice for dinesh
     let swap_params = SwapSignerParams { from: alice, to: dinesh };
@@ -273,34 +272,6 @@ fn swap_self_2_of_3() {
     v.assert_state_invariants();
 }
 
-fn create_msig(v: &VM, signers: Vec<Address>, threshold: u64) -> Address {
-    assert!(!signers.is_empty());
-    let msig_ctor_params = serialize(
-        &fil_actor_multisig::ConstructorParams {
-            signers: signers.clone(),
-            num_approvals_threshold: threshold,
-            unlock_duration: 0,
-            start_epoch: 0,
-        },
-        "multisig ctor params",
-    )
-    .unwrap();
-    let msig_ctor_ret: ExecReturn = apply_ok(
-        v,
-        signers[0],
-        *INIT_ACTOR_ADDR,
-        TokenAmount::from(0_u64),
-        fil_actor_init::Method::Exec as u64,
-        fil_actor_init::ExecParams {
-            code_cid: *MULTISIG_ACTOR_CODE_ID,
-            constructor_params: msig_ctor_params,
-        },
-    )
-    .deserialize()
-    .unwrap();
-    msig_ctor_ret.id_address
-}
-
 fn check_txs(v: &VM, msig_addr: Address, mut expect_txns: Vec<(TxnID, Transaction)>) {
     let st = v.get_state::<MsigState>(msig_addr).unwrap();
     let ptx = make_map_with_root::<_, Transaction>(&st.pending_txs, v.store).unwrap();
@@ -5,7 +5,7 @@ use fil_actor_market::{
 use fvm_shared::crypto::signature::{Signature, SignatureType};
 
 use fil_actor_miner::max_prove_commit_duration;
-use fil_actor_verifreg::{AddVerifierClientParams, Method as VerifregMethod};
+use fil_actor_verifreg::{AddVerifierClientParams, Method as VerifregMethod, NonNegDataCap};
 use fil_actors_runtime::cbor::serialize;
 use fil_actors_runtime::network::EPOCHS_IN_DAY;
 use fil_actors_runtime::runtime::Policy;
@@ -67,7 +67,7 @@ fn setup(store: &'_ MemoryBlockstore) -> (VM<'_>, Addrs, ChainEpoch) {
     add_verifier(&v, verifier, StoragePower::from((32_u64 << 40) as u128));
     let add_client_params = AddVerifierClientParams {
         address: verified_client,
-        allowance: StoragePower::from((1_u64 << 32) as u64),
+        allowance: NonNegDataCap::try_from(StoragePower::from((1_u64 << 32) as u64)).unwrap(),
     };
     apply_ok(
         &v,
@@ -8,7 +8,7 @@ use fil_actor_miner::{
 };
 use fil_actor_power::{Method as PowerMethod, State as PowerState};
 use fil_actor_reward::Method as RewardMethod;
-use fil_actor_verifreg::{Method as VerifregMethod, VerifierParams};
+use fil_actor_verifreg::{Method as VerifregMethod, NonNegDataCap, VerifierParams};
 use fil_actors_runtime::network::EPOCHS_IN_DAY;
 use fil_actors_runtime::runtime::Policy;
 use fil_actors_runtime::{
@@ -59,7 +59,7 @@ fn terminate_sectors() {
 
     let add_client_params = VerifierParams {
         address: verified_client,
-        allowance: StoragePower::from_i64(32 << 40_i64).unwrap(),
+        allowance: NonNegDataCap::try_from(StoragePower::from_i64(32 << 40_i64).unwrap()).unwrap(),
     };
     apply_ok(
         &v,
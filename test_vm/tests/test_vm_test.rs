@@ -1,13 +1,37 @@
+use cid::Cid;
 use fil_actor_account::State as AccountState;
+use fil_actor_multisig::{Method as MultisigMethod, ProposeParams, State as MultisigState};
+use fil_actor_verifreg::{
+    AddVerifiedClientsParams, AddVerifierClientParams, DataCap, Method as VerifregMethod,
+    NonNegDataCap, State as VerifregState, VerifierParams,
+};
+use fil_actors_runtime::cbor::serialize;
 use fil_actors_runtime::test_utils::{make_builtin, ACCOUNT_ACTOR_CODE_ID, PAYCH_ACTOR_CODE_ID};
+use fil_actors_runtime::{
+    make_map_with_root_and_bitwidth, BURNT_FUNDS_ACTOR_ADDR, CRON_ACTOR_ADDR, INIT_ACTOR_ADDR,
+    REWARD_ACTOR_ADDR, STORAGE_MARKET_ACTOR_ADDR, STORAGE_POWER_ACTOR_ADDR, SYSTEM_ACTOR_ADDR,
+    VERIFIED_REGISTRY_ACTOR_ADDR,
+};
 use fvm_ipld_blockstore::MemoryBlockstore;
-use fvm_ipld_encoding::RawBytes;
+use fvm_ipld_encoding::{to_vec, Cbor, RawBytes};
 use fvm_shared::address::Address;
+use fvm_shared::bigint::bigint_ser::BigIntDe;
 use fvm_shared::econ::TokenAmount;
 use fvm_shared::error::ExitCode;
-use fvm_shared::METHOD_SEND;
-use test_vm::util::pk_addrs_from;
-use test_vm::{actor, FIRST_TEST_USER_ADDR, TEST_FAUCET_ADDR, VM};
+use fvm_shared::{HAMT_BIT_WIDTH, METHOD_SEND};
+use num_traits::Zero;
+use serde::de::{self, Deserializer};
+use serde::ser::{self, Serializer};
+use serde::{Deserialize, Serialize};
+use test_vm::failing_blockstore::FailingBlockstore;
+use test_vm::util::{
+    add_verifier, apply_ok, assert_invocation_count, assert_no_invocation, assert_state_root_eq,
+    create_accounts, create_multisig, pk_addrs_from, reload_state, try_create_accounts,
+};
+use test_vm::{
+    actor, ExpectInvocation, InvocationTrace, FIRST_TEST_USER_ADDR, TEST_FAUCET_ADDR,
+    TEST_VERIFREG_ROOT_ADDR, TEST_VERIFREG_ROOT_SIGNER_ADDR, VM,
+};
 
 #[test]
 fn state_control() {
@@ -113,6 +137,330 @@ fn test_sent() {
     v.assert_state_invariants();
 }
 
+#[test]
+fn reload_state_round_trips_through_store() {
+    let store = MemoryBlockstore::new();
+    let v = VM::new_with_singletons(&store);
+    let addrs = create_accounts(&v, 1, TokenAmount::from(10_000e18 as i128));
+    add_verifier(&v, addrs[0], DataCap::from(1048576) * 2u64);
+
+    let cached = v.get_state::<VerifregState>(*VERIFIED_REGISTRY_ACTOR_ADDR).unwrap();
+    let reloaded = reload_state::<VerifregState>(&v, *VERIFIED_REGISTRY_ACTOR_ADDR);
+    assert_eq!(to_vec(&cached).unwrap(), to_vec(&reloaded).unwrap());
+
+    // A change still held in the actor cache is flushed before reloading.
+    let mut st = reloaded;
+    st.total_burned_allowance = DataCap::from(42);
+    let mut act = v.get_actor(*VERIFIED_REGISTRY_ACTOR_ADDR).unwrap();
+    act.head = v.put_store(&st);
+    v.set_actor(*VERIFIED_REGISTRY_ACTOR_ADDR, act);
+
+    let reloaded = reload_state::<VerifregState>(&v, *VERIFIED_REGISTRY_ACTOR_ADDR);
+    assert_eq!(DataCap::from(42), reloaded.total_burned_allowance);
+    assert_eq!(to_vec(&st).unwrap(), to_vec(&reloaded).unwrap());
+}
+
+#[test]
+fn dump_state_lists_all_actors() {
+    let store = MemoryBlockstore::new();
+    let v = VM::new_with_singletons(&store);
+    let addrs = create_accounts(&v, 1, TokenAmount::from(10_000e18 as i128));
+    add_verifier(&v, addrs[0], DataCap::from(1048576) * 2u64);
+
+    let state = v.dump_state();
+    for singleton in [
+        *SYSTEM_ACTOR_ADDR,
+        *INIT_ACTOR_ADDR,
+        *REWARD_ACTOR_ADDR,
+        *CRON_ACTOR_ADDR,
+        *STORAGE_POWER_ACTOR_ADDR,
+        *STORAGE_MARKET_ACTOR_ADDR,
+        *VERIFIED_REGISTRY_ACTOR_ADDR,
+        *BURNT_FUNDS_ACTOR_ADDR,
+    ] {
+        let act = &state[&singleton.id().unwrap()];
+        assert_eq!(v.get_actor(singleton).unwrap(), *act);
+    }
+    for addr in
+        [TEST_VERIFREG_ROOT_SIGNER_ADDR, TEST_VERIFREG_ROOT_ADDR, TEST_FAUCET_ADDR, addrs[0]]
+    {
+        assert!(state.contains_key(&addr.id().unwrap()), "missing actor {}", addr);
+    }
+}
+
+// Applies a fixed sequence of messages to a fresh VM.
+fn apply_fixed_sequence(v: &VM) {
+    let addrs = create_accounts(v, 2, TokenAmount::from(10_000e18 as i128));
+    add_verifier(v, addrs[0], DataCap::from(1048576) * 2u64);
+    apply_ok(v, addrs[1], addrs[0], TokenAmount::from(42u8), METHOD_SEND, RawBytes::default());
+}
+
+#[test]
+fn state_root_is_deterministic() {
+    let store = MemoryBlockstore::new();
+    let v = VM::new_with_singletons(&store);
+    apply_fixed_sequence(&v);
+    let golden = v.checkpoint();
+
+    // Replaying the same sequence from scratch must reproduce the root exactly.
+    let store = MemoryBlockstore::new();
+    let v = VM::new_with_singletons(&store);
+    apply_fixed_sequence(&v);
+    assert_state_root_eq(&v, golden);
+}
+
+// Root of the state tree after `apply_fixed_sequence` on a fresh VM with singletons. After a
+// change that intentionally alters actor state or the sequence, regenerate it by running
+//   cargo test -p test_vm --test test_vm_test state_root_matches_golden -- --ignored
+// and copying the actual root from the failure message.
+const GOLDEN_STATE_ROOT: &str = "";
+
+#[test]
+#[ignore = "GOLDEN_STATE_ROOT has not been generated yet"]
+fn state_root_matches_golden() {
+    let store = MemoryBlockstore::new();
+    let v = VM::new_with_singletons(&store);
+    apply_fixed_sequence(&v);
+    let golden: Cid = GOLDEN_STATE_ROOT.parse().unwrap_or_else(|_| {
+        panic!("state root mismatch: expected {:?}, got {}", GOLDEN_STATE_ROOT, v.checkpoint())
+    });
+    assert_state_root_eq(&v, golden);
+}
+
+#[test]
+#[should_panic(expected = "state root mismatch")]
+fn state_root_mismatch_is_reported() {
+    let store = MemoryBlockstore::new();
+    let v = VM::new_with_singletons(&store);
+    let before = v.checkpoint();
+    apply_fixed_sequence(&v);
+    assert_state_root_eq(&v, before);
+}
+
+#[test]
+fn try_create_accounts_surfaces_errors() {
+    let store = MemoryBlockstore::new();
+    let v = VM::new_with_singletons(&store);
+    let addrs = try_create_accounts(&v, 2, TokenAmount::from(10_000e18 as i128)).unwrap();
+    assert_eq!(2, addrs.len());
+
+    // The faucet can't fund accounts beyond its own balance.
+    let faucet_balance = v.get_actor(TEST_FAUCET_ADDR).unwrap().balance;
+    let err = try_create_accounts(&v, 1, faucet_balance + 1u8).unwrap_err();
+    let code = format!("{:?}", ExitCode::SYS_INSUFFICIENT_FUNDS);
+    assert!(err.to_string().contains(&code), "{}", err);
+}
+
+#[test]
+fn format_invocations_prints_call_tree() {
+    let store = MemoryBlockstore::new();
+    let v = VM::new_with_singletons(&store);
+    let verifier = create_accounts(&v, 1, TokenAmount::from(10_000e18 as i128))[0];
+    v.take_invocations();
+
+    let add_verifier_params = VerifierParams {
+        address: verifier,
+        allowance: NonNegDataCap::try_from(DataCap::from(1 << 20)).unwrap(),
+    };
+    let inner_params = serialize(&add_verifier_params, "verifreg add verifier params").unwrap();
+    let proposal = ProposeParams {
+        to: *VERIFIED_REGISTRY_ACTOR_ADDR,
+        value: TokenAmount::zero(),
+        method: VerifregMethod::AddVerifier as u64,
+        params: inner_params.clone(),
+    };
+    let outer_params = serialize(&proposal, "propose params").unwrap();
+    apply_ok(
+        &v,
+        TEST_VERIFREG_ROOT_SIGNER_ADDR,
+        TEST_VERIFREG_ROOT_ADDR,
+        TokenAmount::zero(),
+        MultisigMethod::Propose as u64,
+        proposal,
+    );
+
+    let expected = format!(
+        "[{}:{}] params: {} bytes\n  [{}:{}] params: {} bytes\n",
+        TEST_VERIFREG_ROOT_ADDR,
+        MultisigMethod::Propose as u64,
+        outer_params.len(),
+        *VERIFIED_REGISTRY_ACTOR_ADDR,
+        VerifregMethod::AddVerifier as u64,
+        inner_params.len()
+    );
+    assert_eq!(expected, v.format_invocations());
+    // Formatting leaves the trace in place.
+    assert_eq!(1, v.take_invocations().len());
+}
+
+#[test]
+fn expect_invocation_from_actual() {
+    let store = MemoryBlockstore::new();
+    let v = VM::new_with_singletons(&store);
+    let addrs = create_accounts(&v, 2, TokenAmount::from(10_000e18 as i128));
+    let (verifier, client) = (addrs[0], addrs[1]);
+    let allowance = DataCap::from(1 << 21);
+    add_verifier(&v, verifier, allowance.clone());
+
+    let params = AddVerifierClientParams {
+        address: client,
+        allowance: NonNegDataCap::try_from(allowance).unwrap(),
+    };
+    apply_ok(
+        &v,
+        verifier,
+        *VERIFIED_REGISTRY_ACTOR_ADDR,
+        TokenAmount::zero(),
+        VerifregMethod::AddVerifiedClient as u64,
+        params,
+    );
+    let invoc = v.take_invocations().pop().unwrap();
+
+    let mut expect = ExpectInvocation::from_actual(&invoc);
+    assert_eq!(*VERIFIED_REGISTRY_ACTOR_ADDR, expect.to);
+    assert_eq!(VerifregMethod::AddVerifiedClient as u64, expect.method);
+    assert_eq!(v.normalize_address(&verifier), expect.from);
+    expect.matches(&invoc);
+
+    // Loosen the captured expectation and match again.
+    expect.params = None;
+    expect.ret = None;
+    expect.matches(&invoc);
+}
+
+fn propose_add_verifier(v: &VM) -> Vec<InvocationTrace> {
+    let verifier = create_accounts(v, 1, TokenAmount::from(10_000e18 as i128))[0];
+    v.take_invocations();
+    let add_verifier_params = VerifierParams {
+        address: verifier,
+        allowance: NonNegDataCap::try_from(DataCap::from(1 << 20)).unwrap(),
+    };
+    let proposal = ProposeParams {
+        to: *VERIFIED_REGISTRY_ACTOR_ADDR,
+        value: TokenAmount::zero(),
+        method: VerifregMethod::AddVerifier as u64,
+        params: serialize(&add_verifier_params, "verifreg add verifier params").unwrap(),
+    };
+    apply_ok(
+        v,
+        TEST_VERIFREG_ROOT_SIGNER_ADDR,
+        TEST_VERIFREG_ROOT_ADDR,
+        TokenAmount::zero(),
+        MultisigMethod::Propose as u64,
+        proposal,
+    );
+    v.take_invocations()
+}
+
+#[test]
+fn assert_no_invocation_passes_for_absent_call() {
+    let store = MemoryBlockstore::new();
+    let v = VM::new_with_singletons(&store);
+    let invocs = propose_add_verifier(&v);
+    assert_no_invocation(
+        &invocs,
+        *VERIFIED_REGISTRY_ACTOR_ADDR,
+        VerifregMethod::AddVerifiedClient as u64,
+    );
+    assert_no_invocation(&invocs, TEST_VERIFREG_ROOT_ADDR, MultisigMethod::Approve as u64);
+}
+
+#[test]
+#[should_panic(expected = "unexpected invocation")]
+fn assert_no_invocation_finds_nested_call() {
+    let store = MemoryBlockstore::new();
+    let v = VM::new_with_singletons(&store);
+    let invocs = propose_add_verifier(&v);
+    // The AddVerifier call is a subinvocation of the multisig proposal.
+    assert_no_invocation(
+        &invocs,
+        *VERIFIED_REGISTRY_ACTOR_ADDR,
+        VerifregMethod::AddVerifier as u64,
+    );
+}
+
+#[test]
+fn add_verified_clients_is_one_invocation() {
+    let store = MemoryBlockstore::new();
+    let v = VM::new_with_singletons(&store);
+    let addrs = create_accounts(&v, 4, TokenAmount::from(10_000e18 as i128));
+    let verifier = addrs[0];
+    let allowance = DataCap::from(1 << 21);
+    add_verifier(&v, verifier, allowance.clone() * 3u64);
+
+    let clients = addrs[1..]
+        .iter()
+        .map(|client| AddVerifierClientParams {
+            address: *client,
+            allowance: NonNegDataCap::try_from(allowance.clone()).unwrap(),
+        })
+        .collect();
+    apply_ok(
+        &v,
+        verifier,
+        *VERIFIED_REGISTRY_ACTOR_ADDR,
+        TokenAmount::zero(),
+        VerifregMethod::AddVerifiedClients as u64,
+        AddVerifiedClientsParams { clients },
+    );
+    // There's no datacap token actor to mint through, so granting to all three clients
+    // happens within the one AddVerifiedClients call.
+    assert_invocation_count(&v.take_invocations(), 1);
+}
+
+#[test]
+fn failing_blockstore_surfaces_flush_errors() {
+    let root = Address::new_id(100);
+    let min_allocation_size = DataCap::from(1 << 20);
+
+    let err = VerifregState::new(&FailingBlockstore::new(0), root, min_allocation_size.clone())
+        .unwrap_err();
+    assert!(err.to_string().contains("Failed to create empty map"), "{}", err);
+
+    // The empty map flush succeeds, so the removal log flush is the first to fail.
+    let err = VerifregState::new(&FailingBlockstore::new(1), root, min_allocation_size.clone())
+        .unwrap_err();
+    assert!(err.to_string().contains("Failed to create empty removal log"), "{}", err);
+
+    VerifregState::new(&FailingBlockstore::new(2), root, min_allocation_size.clone()).unwrap();
+
+    let store = FailingBlockstore::new(usize::MAX);
+    let mut st = VerifregState::new(&store, root, min_allocation_size).unwrap();
+    let mut verifiers =
+        make_map_with_root_and_bitwidth::<_, BigIntDe>(&st.verifiers, &store, HAMT_BIT_WIDTH)
+            .unwrap();
+    verifiers
+        .set(Address::new_id(101).to_bytes().into(), BigIntDe(DataCap::from(1 << 30)))
+        .unwrap();
+    st.verifiers = verifiers.flush().unwrap();
+
+    // Loading the verifiers map is the only read, so writing the scaled map fails.
+    store.fail_after(1);
+    let err = st.scale_verifiers(&store, 1, 2).unwrap_err();
+    assert!(err.to_string().contains("failed to flush verifiers"), "{}", err);
+    store.fail_after(2);
+    st.scale_verifiers(&store, 1, 2).unwrap();
+    assert_eq!(
+        vec![(Address::new_id(101), DataCap::from(1 << 29))],
+        st.export_verifiers(&store).unwrap()
+    );
+}
+
+#[test]
+fn create_multisig_two_of_three() {
+    let store = MemoryBlockstore::new();
+    let v = VM::new_with_singletons(&store);
+    let signers = create_accounts(&v, 3, TokenAmount::from(10_000e18 as u64));
+    let balance = TokenAmount::from(1_000e18 as u64);
+
+    let msig = create_multisig(&v, signers.clone(), 2, balance.clone());
+    let st = v.get_state::<MultisigState>(msig).unwrap();
+    assert_eq!(signers, st.signers);
+    assert_eq!(2, st.num_approvals_threshold);
+    assert_eq!(balance, v.get_actor(msig).unwrap().balance);
+    v.assert_state_invariants();
+}
+
 #[test]
 fn test_pk_gen() {
     let addrs = pk_addrs_from(5, 2);
@@ -121,3 +469,112 @@ fn test_pk_gen() {
     let first_addr_seeded_six = addrs[0];
     assert_ne!(second_addr_seeded_five, first_addr_seeded_six);
 }
+
+struct UnserializableParams;
+
+impl Serialize for UnserializableParams {
+    fn serialize<S>(&self, _serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        Err(ser::Error::custom("Automatic fail when serializing UnserializableParams"))
+    }
+}
+
+impl<'de> Deserialize<'de> for UnserializableParams {
+    fn deserialize<D>(_deserializer: D) -> Result<Self, <D as Deserializer<'de>>::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Err(de::Error::custom("Automatic fail when deserializing UnserializableParams"))
+    }
+}
+
+impl Cbor for UnserializableParams {}
+
+#[test]
+fn apply_message_reports_unserializable_params() {
+    let store = MemoryBlockstore::new();
+    let v = VM::new_with_singletons(&store);
+
+    let err = v
+        .apply_message(
+            TEST_FAUCET_ADDR,
+            TEST_FAUCET_ADDR,
+            TokenAmount::from(0u8),
+            METHOD_SEND,
+            UnserializableParams,
+        )
+        .err()
+        .unwrap();
+    let msg = err.to_string();
+    assert!(msg.contains("failed to serialize params of type"), "{}", msg);
+    assert!(msg.contains("UnserializableParams"), "{}", msg);
+
+    // The failed message must not have touched the sender.
+    assert_eq!(0, v.get_actor(TEST_FAUCET_ADDR).unwrap().call_seq_num);
+}
+
+#[test]
+#[should_panic(expected = "UnserializableParams")]
+fn apply_ok_panics_with_params_type() {
+    let store = MemoryBlockstore::new();
+    let v = VM::new_with_singletons(&store);
+    apply_ok(
+        &v,
+        TEST_FAUCET_ADDR,
+        TEST_FAUCET_ADDR,
+        TokenAmount::from(0u8),
+        METHOD_SEND,
+        UnserializableParams,
+    );
+}
+
+#[test]
+fn apply_message_with_origin_sets_immediate_caller() {
+    let store = MemoryBlockstore::new();
+    let v = VM::new_with_singletons(&store);
+    let addrs = create_accounts(&v, 3, TokenAmount::from(10_000e18 as i128));
+    let (verifier, other, client) = (addrs[0], addrs[1], addrs[2]);
+    let allowance = DataCap::from(1 << 21);
+    add_verifier(&v, verifier, allowance.clone());
+    let params = AddVerifierClientParams {
+        address: client,
+        allowance: NonNegDataCap::try_from(allowance).unwrap(),
+    };
+
+    // Originated by the verifier, but the verifreg sees a non-verifier caller.
+    let ret = v
+        .apply_message_with_origin(
+            verifier,
+            other,
+            *VERIFIED_REGISTRY_ACTOR_ADDR,
+            TokenAmount::zero(),
+            VerifregMethod::AddVerifiedClient as u64,
+            params.clone(),
+        )
+        .unwrap();
+    assert_eq!(ExitCode::USR_NOT_FOUND, ret.code);
+
+    // Originated elsewhere, but called by the verifier.
+    let ret = v
+        .apply_message_with_origin(
+            other,
+            verifier,
+            *VERIFIED_REGISTRY_ACTOR_ADDR,
+            TokenAmount::zero(),
+            VerifregMethod::AddVerifiedClient as u64,
+            params,
+        )
+        .unwrap();
+    assert_eq!(ExitCode::OK, ret.code);
+
+    ExpectInvocation {
+        to: *VERIFIED_REGISTRY_ACTOR_ADDR,
+        method: VerifregMethod::AddVerifiedClient as u64,
+        from: v.normalize_address(&verifier),
+        ..Default::default()
+    }
+    .matches(v.take_invocations().last().unwrap());
+    v.assert_state_invariants();
+}
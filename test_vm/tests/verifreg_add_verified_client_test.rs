@@ -0,0 +1,66 @@
+use fil_actor_verifreg::{
+    AddVerifierClientParams, AddressRole, DataCap, Method as VerifregMethod, NonNegDataCap,
+};
+use fil_actors_runtime::VERIFIED_REGISTRY_ACTOR_ADDR;
+use fvm_ipld_blockstore::MemoryBlockstore;
+use fvm_shared::address::Address;
+use fvm_shared::bigint::Zero;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::error::ExitCode;
+use fvm_shared::METHOD_SEND;
+use test_vm::util::{add_verifier, apply_ok, assert_no_invocation, create_accounts};
+use test_vm::{ExpectInvocation, VM};
+
+#[test]
+fn add_verified_client_beyond_verifier_allowance() {
+    let store = MemoryBlockstore::new();
+    let v = VM::new_with_singletons(&store);
+    let addrs = create_accounts(&v, 2, TokenAmount::from(10_000e18 as i128));
+    let (verifier, client) = (addrs[0], addrs[1]);
+    let allowance = DataCap::from(1 << 21);
+    add_verifier(&v, verifier, allowance.clone());
+
+    let params = AddVerifierClientParams {
+        address: client,
+        allowance: NonNegDataCap::try_from(&allowance + 1).unwrap(),
+    };
+    let ret = v
+        .apply_message(
+            verifier,
+            *VERIFIED_REGISTRY_ACTOR_ADDR,
+            TokenAmount::zero(),
+            VerifregMethod::AddVerifiedClient as u64,
+            params,
+        )
+        .unwrap();
+    assert_eq!(ExitCode::USR_ILLEGAL_ARGUMENT, ret.code);
+
+    // The verifier's cap is checked before anything is granted or sent.
+    let invocs = v.take_invocations();
+    let rejected = &invocs[invocs.len() - 1..];
+    ExpectInvocation {
+        to: *VERIFIED_REGISTRY_ACTOR_ADDR,
+        method: VerifregMethod::AddVerifiedClient as u64,
+        code: Some(ExitCode::USR_ILLEGAL_ARGUMENT),
+        subinvocs: Some(vec![]),
+        ..Default::default()
+    }
+    .matches(&rejected[0]);
+    assert_no_invocation(rejected, client, METHOD_SEND);
+
+    let role = |addr: Address| -> AddressRole {
+        apply_ok(
+            &v,
+            verifier,
+            *VERIFIED_REGISTRY_ACTOR_ADDR,
+            TokenAmount::zero(),
+            VerifregMethod::GetAddressRole as u64,
+            addr,
+        )
+        .deserialize()
+        .unwrap()
+    };
+    assert_eq!(AddressRole::Verifier(allowance), role(verifier));
+    assert_eq!(AddressRole::None, role(client));
+    v.assert_state_invariants();
+}
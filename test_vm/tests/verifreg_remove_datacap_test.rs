@@ -1,8 +1,8 @@
 use fil_actor_verifreg::{
-    AddVerifierClientParams, RemoveDataCapParams, RemoveDataCapRequest, RemoveDataCapReturn,
-    SIGNATURE_DOMAIN_SEPARATION_REMOVE_DATA_CAP,
+    AddVerifierClientParams, DataCap, RemoveDataCapParams, RemoveDataCapRequest,
+    RemoveDataCapReturn, SIGNATURE_DOMAIN_SEPARATION_REMOVE_DATA_CAP,
 };
-use fil_actor_verifreg::{AddrPairKey, Method as VerifregMethod};
+use fil_actor_verifreg::{Method as VerifregMethod, NonNegDataCap, VerifierClientKey};
 use fil_actor_verifreg::{RemoveDataCapProposal, RemoveDataCapProposalID, State as VerifregState};
 use fil_actors_runtime::cbor::serialize;
 use fil_actors_runtime::{make_map_with_root_and_bitwidth, VERIFIED_REGISTRY_ACTOR_ADDR};
@@ -28,7 +28,7 @@ fn remove_datacap_simple_successful_path() {
     let verifier1_id_addr = v.normalize_address(&verifier1).unwrap();
     let verifier2_id_addr = v.normalize_address(&verifier2).unwrap();
     let verified_client_id_addr = v.normalize_address(&verified_client).unwrap();
-    let verifier_allowance = StoragePower::from(2 * 1048576);
+    let verifier_allowance = DataCap::from(1048576) * 2u64;
     let allowance_to_remove: StoragePower = verifier_allowance.clone().div(2);
 
     // register verifier1 and verifier2
@@ -36,8 +36,10 @@ fn remove_datacap_simple_successful_path() {
     add_verifier(&v, verifier2, verifier_allowance.clone());
 
     // register the verified client
-    let add_verified_client_params =
-        AddVerifierClientParams { address: verified_client, allowance: verifier_allowance.clone() };
+    let add_verified_client_params = AddVerifierClientParams {
+        address: verified_client,
+        allowance: NonNegDataCap::try_from(verifier_allowance.clone()).unwrap(),
+    };
     let add_verified_client_params_ser =
         serialize(&add_verified_client_params, "add verifier params").unwrap();
     apply_ok(
@@ -91,12 +93,12 @@ fn remove_datacap_simple_successful_path() {
     .unwrap();
 
     assert!(proposal_ids
-        .get(&AddrPairKey::new(verifier1_id_addr, verified_client_id_addr).to_bytes())
+        .get(&VerifierClientKey::new(verifier1_id_addr, verified_client_id_addr).to_bytes())
         .unwrap()
         .is_none());
 
     assert!(proposal_ids
-        .get(&AddrPairKey::new(verifier2_id_addr, verified_client_id_addr).to_bytes())
+        .get(&VerifierClientKey::new(verifier2_id_addr, verified_client_id_addr).to_bytes())
         .unwrap()
         .is_none());
 
@@ -181,14 +183,14 @@ fn remove_datacap_simple_successful_path() {
             .unwrap();
 
     let verifier1_proposal_id: &RemoveDataCapProposalID = proposal_ids
-        .get(&AddrPairKey::new(verifier1_id_addr, verified_client_id_addr).to_bytes())
+        .get(&VerifierClientKey::new(verifier1_id_addr, verified_client_id_addr).to_bytes())
         .unwrap()
         .unwrap();
 
     assert_eq!(1u64, verifier1_proposal_id.0);
 
     let verifier2_proposal_id: &RemoveDataCapProposalID = proposal_ids
-        .get(&AddrPairKey::new(verifier2_id_addr, verified_client_id_addr).to_bytes())
+        .get(&VerifierClientKey::new(verifier2_id_addr, verified_client_id_addr).to_bytes())
         .unwrap()
         .unwrap();
 
@@ -272,14 +274,14 @@ fn remove_datacap_simple_successful_path() {
             .unwrap();
 
     let verifier1_proposal_id: &RemoveDataCapProposalID = proposal_ids
-        .get(&AddrPairKey::new(verifier1_id_addr, verified_client_id_addr).to_bytes())
+        .get(&VerifierClientKey::new(verifier1_id_addr, verified_client_id_addr).to_bytes())
         .unwrap()
         .unwrap();
 
     assert_eq!(2u64, verifier1_proposal_id.0);
 
     let verifier2_proposal_id: &RemoveDataCapProposalID = proposal_ids
-        .get(&AddrPairKey::new(verifier2_id_addr, verified_client_id_addr).to_bytes())
+        .get(&VerifierClientKey::new(verifier2_id_addr, verified_client_id_addr).to_bytes())
         .unwrap()
         .unwrap();
 
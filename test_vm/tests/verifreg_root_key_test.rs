@@ -0,0 +1,36 @@
+use fil_actor_verifreg::Method as VerifregMethod;
+use fil_actors_runtime::VERIFIED_REGISTRY_ACTOR_ADDR;
+use fvm_ipld_blockstore::MemoryBlockstore;
+use fvm_ipld_encoding::RawBytes;
+use fvm_shared::address::Address;
+use fvm_shared::bigint::Zero;
+use fvm_shared::econ::TokenAmount;
+use test_vm::util::{apply_ok, create_accounts};
+use test_vm::{ExpectInvocation, TEST_VERIFREG_ROOT_ADDR, VM};
+
+#[test]
+fn get_root_key() {
+    let store = MemoryBlockstore::new();
+    let v = VM::new_with_singletons(&store);
+    let addrs = create_accounts(&v, 1, TokenAmount::from(10_000e18 as i128));
+
+    let ret = apply_ok(
+        &v,
+        addrs[0],
+        *VERIFIED_REGISTRY_ACTOR_ADDR,
+        TokenAmount::zero(),
+        VerifregMethod::GetRootKey as u64,
+        RawBytes::default(),
+    );
+    let root_key: Address = ret.deserialize().unwrap();
+    assert_eq!(TEST_VERIFREG_ROOT_ADDR, root_key);
+
+    ExpectInvocation {
+        to: *VERIFIED_REGISTRY_ACTOR_ADDR,
+        method: VerifregMethod::GetRootKey as u64,
+        subinvocs: Some(vec![]),
+        ..Default::default()
+    }
+    .matches(v.take_invocations().last().unwrap());
+    v.assert_state_invariants();
+}